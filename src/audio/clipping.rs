@@ -0,0 +1,110 @@
+//! Clipping detection, including inter-sample (true peak) overs
+//!
+//! A sample stream can stay below full scale and still clip once a DAC or
+//! lossy encoder reconstructs the waveform between samples. We estimate that
+//! reconstruction with 4x Catmull-Rom oversampling.
+
+/// Points evaluated between each pair of samples
+const OVERSAMPLE: usize = 4;
+
+/// Result of scanning a buffer for clipping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipReport {
+    pub clipped_samples: usize, // Samples (or the span after them) that exceed full scale
+    pub max_overshoot: f32,     // Largest amount above 1.0 (0.0 = clean)
+    pub worst_index: usize,     // Sample index of the largest overshoot
+    pub true_peak: f32,         // Highest absolute level, including inter-sample peaks
+}
+
+impl ClipReport {
+    /// True if anything exceeds full scale
+    pub fn is_clipping(&self) -> bool {
+        self.clipped_samples > 0
+    }
+
+    /// Time of the worst overshoot in seconds
+    pub fn worst_time(&self, sample_rate: u32) -> f32 {
+        self.worst_index as f32 / sample_rate as f32
+    }
+
+    /// True peak in dBFS
+    pub fn true_peak_dbfs(&self) -> f32 {
+        20.0 * self.true_peak.max(1e-10).log10()
+    }
+}
+
+/// Scan samples for sample-peak and inter-sample clipping
+pub fn check_clipping(samples: &[f32]) -> ClipReport {
+    let mut report = ClipReport {
+        clipped_samples: 0,
+        max_overshoot: 0.0,
+        worst_index: 0,
+        true_peak: 0.0,
+    };
+
+    let at = |i: isize| -> f32 {
+        let clamped = i.clamp(0, samples.len() as isize - 1);
+        samples[clamped as usize]
+    };
+
+    for i in 0..samples.len() {
+        // Peak over the sample itself and the reconstructed curve up to the next sample
+        let mut peak = samples[i].abs();
+        if i + 1 < samples.len() {
+            let (p0, p1, p2, p3) = (at(i as isize - 1), samples[i], samples[i + 1], at(i as isize + 2));
+            for step in 1..OVERSAMPLE {
+                let t = step as f32 / OVERSAMPLE as f32;
+                peak = peak.max(catmull_rom(p0, p1, p2, p3, t).abs());
+            }
+        }
+
+        report.true_peak = report.true_peak.max(peak);
+        if peak > 1.0 {
+            report.clipped_samples += 1;
+            if peak - 1.0 > report.max_overshoot {
+                report.max_overshoot = peak - 1.0;
+                report.worst_index = i;
+            }
+        }
+    }
+
+    report
+}
+
+/// Catmull-Rom spline between p1 and p2 (t = 0.0-1.0)
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (-p0 + p2) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_sine_is_clean() {
+        let samples: Vec<f32> = (0..4410)
+            .map(|i| 0.8 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let report = check_clipping(&samples);
+        assert!(!report.is_clipping());
+        assert_eq!(report.max_overshoot, 0.0);
+    }
+
+    #[test]
+    fn test_detects_inter_sample_peak() {
+        // No sample exceeds 0.99, but the reconstructed curve between them does
+        let samples: Vec<f32> = vec![0.0, 0.0, 0.99, 0.99, 0.0, 0.0];
+        assert!(samples.iter().all(|s| s.abs() < 1.0));
+
+        let report = check_clipping(&samples);
+        assert!(report.is_clipping());
+        assert!(report.true_peak > 1.0);
+        assert_eq!(report.worst_index, 2);
+        assert!((report.worst_time(44100) - 2.0 / 44100.0).abs() < 1e-9);
+    }
+}
@@ -1,8 +1,15 @@
 use crate::synthesis::get_sample_rate;
+use super::error::SaveError;
+use super::wav::{write_wav, BitDepth};
 /// MP3 encoding for file size optimization
 use std::fs::File;
 use std::io::Write;
 
+/// Map an mp3lame error (builder, encode and flush each have their own type) to SaveError
+fn lame_error<E: std::fmt::Debug>(step: &'static str) -> impl Fn(E) -> SaveError {
+    move |e| SaveError::Encode(format!("{}: {:?}", step, e))
+}
+
 /// Encode float32 samples to MP3 file
 /// Reduces file size by ~85% (10-20MB WAV → 1-3MB MP3)
 pub fn encode_to_mp3(
@@ -10,7 +17,7 @@ pub fn encode_to_mp3(
     filename: &str,
     _title: &str,
     _artist: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), SaveError> {
     // Convert float samples to i16 for MP3 encoder
     let pcm_samples: Vec<i16> = samples
         .iter()
@@ -18,19 +25,20 @@ pub fn encode_to_mp3(
         .collect();
 
     // Create encoder with parameters using builder
-    let mut encoder = mp3lame_encoder::Builder::new().expect("Failed to create encoder builder");
-    encoder.set_num_channels(1).expect("Failed to set channels");
+    let mut encoder = mp3lame_encoder::Builder::new()
+        .ok_or_else(|| SaveError::Encode("Failed to create encoder builder".to_string()))?;
+    encoder.set_num_channels(1).map_err(lame_error("Failed to set channels"))?;
     encoder
         .set_sample_rate(get_sample_rate() as u32)
-        .expect("Failed to set sample rate");
+        .map_err(lame_error("Failed to set sample rate"))?;
     encoder
         .set_brate(mp3lame_encoder::Birtate::Kbps192)
-        .expect("Failed to set bitrate");
+        .map_err(lame_error("Failed to set bitrate"))?;
     encoder
         .set_quality(mp3lame_encoder::Quality::Best)
-        .expect("Failed to set quality");
+        .map_err(lame_error("Failed to set quality"))?;
 
-    let mut encoder = encoder.build().expect("Failed to build encoder");
+    let mut encoder = encoder.build().map_err(lame_error("Failed to build encoder"))?;
 
     // Encode audio - allocate buffer for MP3 data using MaybeUninit
     use std::mem::MaybeUninit;
@@ -44,12 +52,12 @@ pub fn encode_to_mp3(
             mp3lame_encoder::MonoPcm(&pcm_samples),
             mp3_buffer.as_mut_slice(),
         )
-        .expect("Failed to encode");
+        .map_err(lame_error("Failed to encode"))?;
 
     // Flush remaining data
     let flushed_size = encoder
         .flush::<mp3lame_encoder::FlushNoGap>(&mut mp3_buffer[encoded_size..])
-        .expect("Failed to flush");
+        .map_err(lame_error("Failed to flush"))?;
 
     // Convert MaybeUninit to initialized bytes
     let total_size = encoded_size + flushed_size;
@@ -64,3 +72,345 @@ pub fn encode_to_mp3(
 
     Ok(())
 }
+
+/// FLAC block size (samples per frame)
+const FLAC_BLOCK_SIZE: usize = 4096;
+
+/// Encode float32 samples to a lossless 16-bit mono FLAC file
+/// Uses fixed linear predictors + Rice coding, and writes TITLE/ARTIST
+/// as Vorbis comments. No external encoder needed.
+pub fn encode_to_flac(
+    samples: &[f32],
+    filename: &str,
+    title: &str,
+    artist: &str,
+) -> Result<(), SaveError> {
+    let pcm_samples: Vec<i32> = samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i32)
+        .collect();
+
+    let sample_rate = get_sample_rate();
+    let mut out = Vec::with_capacity(pcm_samples.len() + 1024);
+    out.extend_from_slice(b"fLaC");
+
+    // STREAMINFO metadata block (type 0, 34 bytes)
+    let block_size = FLAC_BLOCK_SIZE.min(pcm_samples.len().max(16)) as u16;
+    let mut info = BitWriter::new();
+    info.write(block_size as u64, 16); // min block size
+    info.write(block_size as u64, 16); // max block size
+    info.write(0, 24); // min frame size (unknown)
+    info.write(0, 24); // max frame size (unknown)
+    info.write(sample_rate as u64, 20);
+    info.write(0, 3); // channels - 1 (mono)
+    info.write(15, 5); // bits per sample - 1
+    info.write(pcm_samples.len() as u64, 36);
+    let mut streaminfo = info.into_bytes();
+    streaminfo.extend_from_slice(&[0u8; 16]); // MD5 (unset)
+    write_flac_metadata_header(&mut out, false, 0, streaminfo.len());
+    out.extend_from_slice(&streaminfo);
+
+    // VORBIS_COMMENT metadata block (type 4, little-endian fields)
+    let vendor = b"rust-beats";
+    let comments = [format!("TITLE={}", title), format!("ARTIST={}", artist)];
+    let mut comment_block = Vec::new();
+    comment_block.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    comment_block.extend_from_slice(vendor);
+    comment_block.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in &comments {
+        comment_block.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        comment_block.extend_from_slice(comment.as_bytes());
+    }
+    write_flac_metadata_header(&mut out, true, 4, comment_block.len());
+    out.extend_from_slice(&comment_block);
+
+    // Audio frames
+    for (frame_number, block) in pcm_samples.chunks(FLAC_BLOCK_SIZE).enumerate() {
+        write_flac_frame(&mut out, block, frame_number as u64);
+    }
+
+    let mut file = File::create(filename)?;
+    file.write_all(&out)?;
+
+    Ok(())
+}
+
+/// Encode float32 samples to an OGG Vorbis file via the `oggenc` CLI (vorbis-tools)
+/// Smaller than MP3 at similar quality; title/artist are written as Vorbis comments
+pub fn encode_to_ogg(
+    samples: &[f32],
+    filename: &str,
+    title: &str,
+    artist: &str,
+) -> Result<(), SaveError> {
+    use std::process::{Command, Stdio};
+
+    // oggenc reads a 16-bit WAV stream from stdin
+    let mut wav_bytes = std::io::Cursor::new(Vec::with_capacity(samples.len() * 2 + 44));
+    write_wav(&mut wav_bytes, samples, get_sample_rate(), BitDepth::Int16)?;
+
+    let mut child = Command::new("oggenc")
+        .args(["-Q", "-q", "6", "-t", title, "-a", artist, "-o", filename, "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| launch_error("oggenc", "vorbis-tools", e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| SaveError::Encode("Failed to open oggenc stdin".to_string()))?
+        .write_all(wav_bytes.get_ref())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(SaveError::Encode(format!(
+            "oggenc failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Why an external encoder could not be started: a missing binary says which package to install
+fn launch_error(program: &str, package: &str, error: std::io::Error) -> SaveError {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        SaveError::Encode(format!("{} not found: install {} to export this format", program, package))
+    } else {
+        SaveError::Encode(format!("Failed to run {}: {}", program, error))
+    }
+}
+
+/// Write a FLAC metadata block header (last flag, block type, 24-bit length)
+fn write_flac_metadata_header(out: &mut Vec<u8>, is_last: bool, block_type: u8, length: usize) {
+    out.push(((is_last as u8) << 7) | block_type);
+    out.extend_from_slice(&(length as u32).to_be_bytes()[1..]);
+}
+
+/// Write one FLAC frame containing a single FIXED subframe
+fn write_flac_frame(out: &mut Vec<u8>, block: &[i32], frame_number: u64) {
+    let mut frame = BitWriter::new();
+
+    // Frame header
+    frame.write(0b11_1111_1111_1110, 14); // sync code
+    frame.write(0, 1); // reserved
+    frame.write(0, 1); // fixed block size stream
+    frame.write(0b0111, 4); // block size: 16-bit (n-1) at end of header
+    frame.write(0b0000, 4); // sample rate: from STREAMINFO
+    frame.write(0b0000, 4); // channel assignment: mono
+    frame.write(0b100, 3); // sample size: 16 bits
+    frame.write(0, 1); // reserved
+    frame.write_utf8_number(frame_number);
+    frame.write(block.len() as u64 - 1, 16);
+    let crc8 = flac_crc8(frame.bytes());
+    frame.write(crc8 as u64, 8);
+
+    // Subframe: pick the fixed predictor order with the smallest residual
+    let order = (0..=4usize.min(block.len()))
+        .min_by_key(|&order| {
+            fixed_residuals(block, order)
+                .iter()
+                .map(|r| r.unsigned_abs())
+                .sum::<u64>()
+        })
+        .unwrap_or(0);
+    let residuals = fixed_residuals(block, order);
+
+    frame.write(0, 1); // zero padding
+    frame.write(0b001000 | order as u64, 6); // SUBFRAME_FIXED
+    frame.write(0, 1); // no wasted bits
+    for &warmup in &block[..order] {
+        frame.write((warmup as u64) & 0xFFFF, 16);
+    }
+
+    // Residual: Rice coding, partition order 0, single parameter
+    let rice_param = best_rice_parameter(&residuals);
+    frame.write(0b00, 2);
+    frame.write(0, 4);
+    frame.write(rice_param as u64, 4);
+    for &residual in &residuals {
+        let folded = if residual >= 0 {
+            (residual as u64) << 1
+        } else {
+            (((-residual) as u64) << 1) - 1
+        };
+        frame.write_unary(folded >> rice_param);
+        frame.write(folded & ((1 << rice_param) - 1), rice_param);
+    }
+
+    let mut bytes = frame.into_bytes();
+    let crc16 = flac_crc16(&bytes);
+    bytes.extend_from_slice(&crc16.to_be_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+/// Residuals of the FLAC fixed polynomial predictor of the given order
+fn fixed_residuals(block: &[i32], order: usize) -> Vec<i64> {
+    (order..block.len())
+        .map(|i| {
+            let s = |k: usize| block[i - k] as i64;
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Estimate the Rice parameter from the mean folded residual
+fn best_rice_parameter(residuals: &[i64]) -> u32 {
+    if residuals.is_empty() {
+        return 0;
+    }
+    let sum: u64 = residuals.iter().map(|r| r.unsigned_abs() * 2).sum();
+    let mean = sum / residuals.len() as u64;
+    // 4-bit parameter, 15 is reserved as an escape code
+    (64 - mean.leading_zeros()).min(14)
+}
+
+/// CRC-8 (poly 0x07) over the frame header
+fn flac_crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// CRC-16 (poly 0x8005) over the whole frame
+fn flac_crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// MSB-first bit writer for FLAC bitstreams
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u8,
+    filled: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self { bytes: Vec::new(), current: 0, filled: 0 }
+    }
+
+    /// Write the lowest `bits` bits of `value`
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        self.current = (self.current << 1) | bit as u8;
+        self.filled += 1;
+        if self.filled == 8 {
+            self.bytes.push(self.current);
+            self.current = 0;
+            self.filled = 0;
+        }
+    }
+
+    /// Unary code: `value` zeros followed by a one
+    fn write_unary(&mut self, value: u64) {
+        for _ in 0..value {
+            self.write_bit(false);
+        }
+        self.write_bit(true);
+    }
+
+    /// FLAC frame numbers use UTF-8 style variable-length coding
+    fn write_utf8_number(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+        let mut continuation = Vec::new();
+        let mut remaining = value;
+        let mut first_bits = 6;
+        while remaining >= (1 << first_bits) {
+            continuation.push(0x80 | (remaining & 0x3F));
+            remaining >>= 6;
+            first_bits -= 1;
+        }
+        let byte_count = continuation.len() as u32 + 1;
+        let prefix = (0xFF00u64 >> byte_count) & 0xFF;
+        self.write(prefix | remaining, 8);
+        for byte in continuation.iter().rev() {
+            self.write(*byte, 8);
+        }
+    }
+
+    /// Bytes completed so far (excludes a partially filled byte)
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Finish the stream, zero-padding to a byte boundary
+    fn into_bytes(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.current <<= 8 - self.filled;
+            self.bytes.push(self.current);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_encoder_names_its_package() {
+        let missing = launch_error("oggenc", "vorbis-tools", std::io::ErrorKind::NotFound.into());
+        assert!(matches!(&missing, SaveError::Encode(msg) if msg.contains("oggenc not found") && msg.contains("vorbis-tools")));
+
+        let denied = launch_error("oggenc", "vorbis-tools", std::io::ErrorKind::PermissionDenied.into());
+        assert!(matches!(&denied, SaveError::Encode(msg) if msg.starts_with("Failed to run oggenc")));
+    }
+
+    #[test]
+    fn test_flac_header_and_streaminfo() {
+        let samples: Vec<f32> = (0..10000).map(|i| (i as f32 * 0.01).sin() * 0.5).collect();
+        let path = std::env::temp_dir().join("rust_beats_test_encoder.flac");
+        let path = path.to_str().unwrap();
+
+        encode_to_flac(&samples, path, "Swamp Test", "Tester").unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(&bytes[0..4], b"fLaC");
+        // STREAMINFO block header: type 0, not last, length 34
+        assert_eq!(bytes[4], 0);
+        assert_eq!(&bytes[5..8], &[0, 0, 34]);
+        // Min/max block size
+        assert_eq!(u16::from_be_bytes([bytes[8], bytes[9]]) as usize, FLAC_BLOCK_SIZE);
+        // Vorbis comment block follows and carries the tags
+        assert_eq!(bytes[42], 0x80 | 4);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("TITLE=Swamp Test"));
+        assert!(text.contains("ARTIST=Tester"));
+    }
+
+    #[test]
+    fn test_flac_crc8() {
+        // Reference value from the FLAC spec CRC-8 (poly 0x07)
+        assert_eq!(flac_crc8(b"123456789"), 0xF4);
+        assert_eq!(flac_crc16(b"123456789"), 0xFEE8);
+    }
+}
@@ -0,0 +1,58 @@
+/// Errors from writing songs to disk
+use std::fmt;
+use std::io;
+
+/// Why saving a song (audio or metadata) failed
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),    // File could not be created or written
+    Encode(String),   // Encoder setup or encoding failed (MP3, OGG, ...)
+    Metadata(String), // Metadata could not be serialized
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "I/O error: {}", e),
+            SaveError::Encode(msg) => write!(f, "Encoding failed: {}", msg),
+            SaveError::Metadata(msg) => write!(f, "Metadata error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<hound::Error> for SaveError {
+    fn from(e: hound::Error) -> Self {
+        match e {
+            hound::Error::IoError(e) => SaveError::Io(e),
+            other => SaveError::Encode(other.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Metadata(e.to_string())
+    }
+}
+
+/// Write song metadata as pretty-printed JSON
+pub fn write_metadata_json(path: &str, metadata: &serde_json::Value) -> Result<(), SaveError> {
+    let json = serde_json::to_string_pretty(metadata)?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
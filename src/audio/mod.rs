@@ -1,6 +1,12 @@
 // Audio utilities for metal generation
+pub mod clipping;
 pub mod encoder;
+pub mod error;
 pub mod voice;
+pub mod wav;
 
 // Core exports
-pub use encoder::encode_to_mp3;
+pub use clipping::{check_clipping, ClipReport};
+pub use encoder::{encode_to_flac, encode_to_mp3, encode_to_ogg};
+pub use error::{write_metadata_json, SaveError};
+pub use wav::{read_wav, save_wav, write_wav, BitDepth};
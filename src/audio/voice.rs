@@ -2,8 +2,11 @@ use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -31,90 +34,248 @@ pub struct VoiceSegment {
     pub samples: Vec<f32>,
 }
 
+/// Speaking voice for a wisdom segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceType {
+    Male,
+    Female,
+}
+
+impl VoiceType {
+    /// Playback-rate factor applied to backends that only have one voice (gTTS speaks
+    /// with a female voice): below 1.0 the clip plays slower and lower
+    pub fn playback_ratio(&self) -> f32 {
+        match self {
+            VoiceType::Male => 0.8,
+            VoiceType::Female => 1.0,
+        }
+    }
+
+    /// Glottal pitch of the built-in formant voice (Hz)
+    pub fn glottal_hz(&self) -> f32 {
+        match self {
+            VoiceType::Male => 110.0,
+            VoiceType::Female => 200.0,
+        }
+    }
+}
+
+/// How voices are assigned across the intro / chorus / outro segments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceCasting {
+    Single(VoiceType), // One voice throughout
+    CallResponse,      // Male narrator on intro and outro, female chorus
+    Alternating,       // Male and female take turns, segment by segment
+}
+
+impl VoiceCasting {
+    /// Voice for the segment at `index` (playback order); `chorus` marks chorus lines
+    pub fn voice_for(&self, index: usize, chorus: bool) -> VoiceType {
+        match self {
+            VoiceCasting::Single(voice) => *voice,
+            VoiceCasting::CallResponse if chorus => VoiceType::Female,
+            VoiceCasting::CallResponse => VoiceType::Male,
+            VoiceCasting::Alternating => match index % 2 {
+                0 => VoiceType::Male,
+                _ => VoiceType::Female,
+            },
+        }
+    }
+}
+
+/// Script the Python (gTTS) backend runs
+const TTS_SCRIPT: &str = "scripts/generate_tts.py";
+
+/// Why a text-to-speech backend couldn't produce audio
+#[derive(Debug)]
+pub enum TtsBackendError {
+    MissingScript(PathBuf),            // The gTTS script isn't where we expect it
+    PythonUnavailable(std::io::Error), // `python3` couldn't be started
+    ScriptFailed(String),              // The script ran and failed (its stderr, e.g. gTTS not installed)
+    InvalidAudio(String),              // The script's WAV was unreadable or not mono
+}
+
+impl fmt::Display for TtsBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TtsBackendError::MissingScript(path) => write!(f, "TTS script not found at {}", path.display()),
+            TtsBackendError::PythonUnavailable(err) => write!(f, "python3 is not available: {}", err),
+            TtsBackendError::ScriptFailed(stderr) => write!(f, "Python TTS generation failed: {}", stderr.trim()),
+            TtsBackendError::InvalidAudio(reason) => write!(f, "TTS produced unusable audio: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TtsBackendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TtsBackendError::PythonUnavailable(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 /// Generate TTS audio from text using gTTS (Google Text-to-Speech) Python package
-/// Returns (samples, sample_rate) tuple
-pub fn generate_tts(
+/// Returns (samples, sample_rate) tuple. gTTS has a single (female) voice per language,
+/// so a male voice is the same clip reported at a lower sample rate (`playback_ratio`).
+/// With the `formant-tts` feature, falls back to the built-in formant voice when the
+/// Python backend is unavailable.
+pub fn generate_tts(text: &str, language: &str, voice: VoiceType) -> Result<(Vec<i16>, u32), TtsBackendError> {
+    generate_tts_with_script(text, language, voice, Path::new(TTS_SCRIPT))
+}
+
+fn generate_tts_with_script(
     text: &str,
     language: &str,
-) -> Result<(Vec<i16>, u32), Box<dyn std::error::Error>> {
+    voice: VoiceType,
+    script: &Path,
+) -> Result<(Vec<i16>, u32), TtsBackendError> {
+    python_tts(text, language, script)
+        .map(|(samples, sample_rate)| (samples, (sample_rate as f32 * voice.playback_ratio()).round() as u32))
+        .or_else(|err| fallback_tts(text, voice, err))
+}
+
+#[cfg(feature = "formant-tts")]
+fn fallback_tts(text: &str, voice: VoiceType, _unavailable: TtsBackendError) -> Result<(Vec<i16>, u32), TtsBackendError> {
+    Ok(formant_tts(text, voice))
+}
+
+#[cfg(not(feature = "formant-tts"))]
+fn fallback_tts(_text: &str, _voice: VoiceType, unavailable: TtsBackendError) -> Result<(Vec<i16>, u32), TtsBackendError> {
+    Err(unavailable)
+}
+
+/// Run the gTTS script and read back its WAV
+fn python_tts(text: &str, language: &str, script: &Path) -> Result<(Vec<i16>, u32), TtsBackendError> {
+    // Detect a missing script up front instead of reporting a failed subprocess
+    if !script.is_file() {
+        return Err(TtsBackendError::MissingScript(script.to_path_buf()));
+    }
+
     // Create temporary output file
     let temp_wav = format!("/tmp/gtts_tts_{}.wav", std::process::id());
 
     // Call Python script: python3 scripts/generate_tts.py <text> <language> <output_wav>
     let output = Command::new("python3")
-        .arg("scripts/generate_tts.py")
+        .arg(script)
         .arg(text)
         .arg(language)
         .arg(&temp_wav)
-        .output()?;
+        .output()
+        .map_err(TtsBackendError::PythonUnavailable)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Python TTS generation failed: {}", stderr).into());
+        return Err(TtsBackendError::ScriptFailed(stderr.into_owned()));
     }
 
     // Read the WAV file
-    let reader = hound::WavReader::open(&temp_wav)?;
+    let invalid = |err: hound::Error| TtsBackendError::InvalidAudio(err.to_string());
+    let reader = hound::WavReader::open(&temp_wav).map_err(invalid)?;
     let spec = reader.spec();
     let sample_rate = spec.sample_rate;
 
     // Read all samples
     let samples_i16: Vec<i16> = reader
         .into_samples::<i16>()
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid)?;
 
     // Clean up temp file
     let _ = fs::remove_file(&temp_wav);
 
     // Verify it's mono (pyttsx3 outputs mono)
     if spec.channels != 1 {
-        return Err(format!(
+        return Err(TtsBackendError::InvalidAudio(format!(
             "Expected mono audio, got {} channels",
             spec.channels
-        )
-        .into());
+        )));
     }
 
     Ok((samples_i16, sample_rate))
 }
 
-/// Resample audio from 22050 Hz to 44100 Hz (2x upsampling)
-pub fn resample_22050_to_44100(input: Vec<i16>) -> Result<Vec<i16>, Box<dyn std::error::Error>> {
-    if input.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    // Convert i16 to f32 for resampling (-1.0 to 1.0 range)
-    let input_f32: Vec<f32> = input.iter().map(|&s| s as f32 / 32768.0).collect();
+/// Sample rate of the built-in formant voice
+#[cfg(feature = "formant-tts")]
+const FORMANT_TTS_SAMPLE_RATE: u32 = 22050;
+
+/// Offline robot voice: each letter becomes a short phone - vowels are a buzzing glottal
+/// source through two formant resonators, fricatives are noise, other consonants a dull
+/// hum, spaces and punctuation are pauses. Intelligibility is rough, but it needs nothing
+/// beyond this crate. Latin and Cyrillic vowels are recognised.
+#[cfg(feature = "formant-tts")]
+pub fn formant_tts(text: &str, voice: VoiceType) -> (Vec<i16>, u32) {
+    use rand::Rng;
+    use std::f32::consts::PI;
+
+    let sample_rate = FORMANT_TTS_SAMPLE_RATE as f32;
+    let mut rng = crate::utils::rng();
+    let mut samples = Vec::new();
+    let mut glottal_phase = 0.0_f32;
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        // (duration s, F1, F2, voiced, noise)
+        let (duration, f1, f2, voiced, noise) = match c {
+            'a' | 'а' | 'я' => (0.13, 730.0, 1090.0, true, 0.0),
+            'e' | 'е' | 'э' => (0.12, 530.0, 1840.0, true, 0.0),
+            'i' | 'y' | 'и' | 'й' => (0.11, 270.0, 2290.0, true, 0.0),
+            'o' | 'о' => (0.13, 570.0, 840.0, true, 0.0),
+            'u' | 'у' | 'ю' => (0.12, 300.0, 870.0, true, 0.0),
+            'ъ' => (0.10, 500.0, 1300.0, true, 0.0),
+            's' | 'z' | 'c' | 'x' | 'f' | 'h' | 'с' | 'з' | 'ц' | 'ф' | 'х' | 'ш' | 'ж' | 'щ' | 'ч' => {
+                (0.08, 2500.0, 5000.0, false, 0.6)
+            }
+            c if c.is_alphabetic() => (0.06, 250.0, 1500.0, true, 0.1),
+            _ => (0.12, 0.0, 0.0, false, 0.0), // Pause
+        };
 
-    // Create resampler parameters for high-quality 2x upsampling
-    let params = SincInterpolationParameters {
-        sinc_len: 256,
-        f_cutoff: 0.95,
-        interpolation: SincInterpolationType::Linear,
-        oversampling_factor: 256,
-        window: WindowFunction::BlackmanHarris2,
-    };
+        let phone_samples = (duration * sample_rate) as usize;
+        let ramp = (0.01 * sample_rate) as usize;
+        let resonator = |freq: f32| {
+            let r = (-PI * 120.0 / sample_rate).exp();
+            (2.0 * r * (2.0 * PI * freq / sample_rate).cos(), -r * r, 1.0 - r)
+        };
+        let (a1, a2, g1) = resonator(f1);
+        let (b1, b2, g2) = resonator(f2);
+        let (mut y1, mut y2, mut z1, mut z2) = (0.0_f32, 0.0_f32, 0.0_f32, 0.0_f32);
+
+        for i in 0..phone_samples {
+            let mut source = 0.0;
+            if voiced {
+                // Sawtooth glottal pulse at the voice's pitch
+                source += 2.0 * glottal_phase - 1.0;
+                glottal_phase = (glottal_phase + voice.glottal_hz() / sample_rate).fract();
+            }
+            if noise > 0.0 {
+                source += rng.gen_range(-1.0..1.0) * noise;
+            }
+            if f1 == 0.0 {
+                samples.push(0.0);
+                continue;
+            }
 
-    // Create resampler (ratio 2.0 for 22050 -> 44100)
-    let mut resampler = SincFixedIn::<f32>::new(
-        2.0,              // ratio: 44100 / 22050 = 2.0
-        2.0,              // max_ratio
-        params,
-        input_f32.len(),  // chunk_size
-        1,                // channels (mono)
-    )?;
+            let y = g1 * source + a1 * y1 + a2 * y2;
+            y2 = y1;
+            y1 = y;
+            let z = g2 * source + b1 * z1 + b2 * z2;
+            z2 = z1;
+            z1 = z;
 
-    // Resample (wrapping in vector for mono channel)
-    let output_waves = resampler.process(&[input_f32], None)?;
+            let envelope = (i.min(phone_samples - i) as f32 / ramp as f32).min(1.0);
+            samples.push((y + z * 0.5) * envelope);
+        }
+    }
 
-    // Convert back to i16
-    let output: Vec<i16> = output_waves[0]
-        .iter()
-        .map(|&s| (s * 32768.0).clamp(-32768.0, 32767.0) as i16)
-        .collect();
+    // Normalize to a comfortable level
+    let peak = samples.iter().fold(0.0_f32, |max, s| max.max(s.abs()));
+    let gain = if peak > 0.0 { 0.7 / peak } else { 0.0 };
+    let pcm = samples.iter().map(|s| (s * gain * 32767.0) as i16).collect();
+    (pcm, FORMANT_TTS_SAMPLE_RATE)
+}
 
-    Ok(output)
+/// Resample audio from 22050 Hz to 44100 Hz (2x upsampling)
+pub fn resample_22050_to_44100(input: Vec<i16>) -> Result<Vec<i16>, Box<dyn std::error::Error>> {
+    resample_to_target(input, 22050, 44100)
 }
 
 /// Convert i16 PCM samples to f32 format (-1.0 to 1.0)
@@ -122,6 +283,9 @@ pub fn convert_i16_to_f32(samples: &[i16]) -> Vec<f32> {
     samples.iter().map(|&s| s as f32 / 32768.0).collect()
 }
 
+/// Frames fed to the resampler per call; long clips stream through in chunks of this size
+const RESAMPLE_CHUNK: usize = 1024;
+
 /// Resample audio to target sample rate using high-quality resampling
 pub fn resample_to_target(input: Vec<i16>, from_rate: u32, to_rate: u32) -> Result<Vec<i16>, Box<dyn std::error::Error>> {
     if input.is_empty() || from_rate == to_rate {
@@ -129,11 +293,26 @@ pub fn resample_to_target(input: Vec<i16>, from_rate: u32, to_rate: u32) -> Resu
     }
 
     // Convert i16 to f32 for resampling (-1.0 to 1.0 range)
-    let input_f32: Vec<f32> = input.iter().map(|&s| s as f32 / 32768.0).collect();
+    let input_f32 = convert_i16_to_f32(&input);
 
     // Calculate resampling ratio
     let ratio = to_rate as f64 / from_rate as f64;
+    let output_f32 = resample_chunked(&input_f32, ratio)?;
+
+    // Convert back to i16
+    let output: Vec<i16> = output_f32
+        .iter()
+        .map(|&s| (s * 32768.0).clamp(-32768.0, 32767.0) as i16)
+        .collect();
 
+    Ok(output)
+}
+
+/// Resample mono audio by `ratio` through one persistent resampler, `RESAMPLE_CHUNK` frames
+/// at a time, so memory stays flat however long the clip is. The result is
+/// `input.len() * ratio` samples long and lines up with the input: `SincFixedIn` centres its
+/// first output frame on input frame 0, so there is no leading delay to trim.
+fn resample_chunked(input: &[f32], ratio: f64) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
     // Create resampler parameters for high-quality resampling
     let params = SincInterpolationParameters {
         sinc_len: 256,
@@ -143,24 +322,29 @@ pub fn resample_to_target(input: Vec<i16>, from_rate: u32, to_rate: u32) -> Resu
         window: WindowFunction::BlackmanHarris2,
     };
 
-    // Create resampler
-    let mut resampler = SincFixedIn::<f32>::new(
-        ratio,              // ratio
-        ratio.max(2.0),     // max_ratio
-        params,
-        input_f32.len(),    // chunk_size
-        1,                  // channels (mono)
-    )?;
-
-    // Resample (wrapping in vector for mono channel)
-    let output_waves = resampler.process(&[input_f32], None)?;
-
-    // Convert back to i16
-    let output: Vec<i16> = output_waves[0]
-        .iter()
-        .map(|&s| (s * 32768.0).clamp(-32768.0, 32767.0) as i16)
-        .collect();
+    // Create resampler (mono, fixed ratio)
+    let mut resampler = SincFixedIn::<f32>::new(ratio, 1.0, params, RESAMPLE_CHUNK, 1)?;
+
+    let expected = (input.len() as f64 * ratio).round() as usize;
+    let delay = resampler.output_delay();
+    let mut output = Vec::with_capacity(delay + expected + resampler.output_frames_max());
+    let mut chunk_in = vec![vec![0.0f32; RESAMPLE_CHUNK]];
+    let mut chunk_out = vec![vec![0.0f32; resampler.output_frames_max()]];
+    let mut position = 0;
+
+    // Feed the clip, then silence (zero-padded tail), flushing a filter delay past the
+    // clip's end so its last frames have fully settled
+    while output.len() < delay + expected {
+        let frames = (input.len() - position).min(RESAMPLE_CHUNK);
+        chunk_in[0][..frames].copy_from_slice(&input[position..position + frames]);
+        chunk_in[0][frames..].fill(0.0);
+        position += frames;
+
+        let (_, written) = resampler.process_into_buffer(&chunk_in, &mut chunk_out, None)?;
+        output.extend_from_slice(&chunk_out[0][..written]);
+    }
 
+    output.truncate(expected);
     Ok(output)
 }
 
@@ -168,9 +352,10 @@ pub fn resample_to_target(input: Vec<i16>, from_rate: u32, to_rate: u32) -> Resu
 pub fn generate_voice_segment(
     text: &str,
     language: &str,
+    voice: VoiceType,
 ) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
     // Generate TTS (returns samples and sample rate)
-    let (tts_samples, tts_sample_rate) = generate_tts(text, language)?;
+    let (tts_samples, tts_sample_rate) = generate_tts(text, language, voice)?;
 
     // Resample to 44100 Hz if needed
     let tts_44k = if tts_sample_rate != 44100 {
@@ -206,7 +391,17 @@ pub fn calculate_voice_envelope(voice_samples: &[f32], window_size: usize) -> Ve
     envelope
 }
 
-/// Mix voice with music, applying ducking to reduce music volume during speech
+/// Voice level (RMS) at which the music is ducked by the full `duck_db`
+const VOICE_DUCK_FULL_LEVEL: f32 = 0.05;
+
+/// Sidechain timing: how fast the music ducks under the voice, and how fast it comes back
+const VOICE_DUCK_ATTACK: f32 = 0.015; // seconds
+const VOICE_DUCK_RELEASE: f32 = 0.3; // seconds
+
+/// Mix voice with music, applying ducking to reduce music volume during speech.
+/// The voice envelope drives a sidechain compressor: the music gain follows it with
+/// attack/release smoothing (also recovering after the voice ends) instead of
+/// jumping with every transient.
 pub fn mix_with_ducking(
     music: &mut [f32],
     voice_segment: &VoiceSegment,
@@ -224,30 +419,39 @@ pub fn mix_with_ducking(
     // Apply fade in/out to voice (50ms)
     let fade_samples = (sample_rate as f32 * 0.05) as usize;
 
-    // Mix voice into music with ducking
-    for (i, &voice_sample) in voice_segment.samples.iter().enumerate() {
+    let attack_coef = (-1.0 / (VOICE_DUCK_ATTACK * sample_rate as f32)).exp();
+    let release_coef = (-1.0 / (VOICE_DUCK_RELEASE * sample_rate as f32)).exp();
+    let mut gain = 1.0_f32;
+
+    // Mix voice into music with ducking, then let the gain release past the voice's end
+    let release_tail = (VOICE_DUCK_RELEASE * 5.0 * sample_rate as f32) as usize;
+    for i in 0..voice_segment.samples.len() + release_tail {
         let music_idx = voice_segment.start_sample + i;
 
         if music_idx >= music.len() {
             break;
         }
 
-        // Apply fade in/out to voice
-        let fade_factor = if i < fade_samples {
-            i as f32 / fade_samples as f32
-        } else if i >= voice_segment.samples.len() - fade_samples {
-            (voice_segment.samples.len() - i) as f32 / fade_samples as f32
-        } else {
-            1.0
-        };
-
-        // Calculate ducking amount based on voice envelope
-        let voice_env = voice_envelope[i];
-        let duck_amount = 1.0 - (voice_env * (1.0 - duck_factor));
-
-        // Apply ducking to music and add voice
-        music[music_idx] *= duck_amount;
-        music[music_idx] += voice_sample * voice_volume * fade_factor;
+        // Sidechain: how far the voice wants the music down right now
+        let voice_env = voice_envelope.get(i).copied().unwrap_or(0.0);
+        let target = 1.0 - (voice_env / VOICE_DUCK_FULL_LEVEL).min(1.0) * (1.0 - duck_factor);
+        let coef = if target < gain { attack_coef } else { release_coef };
+        gain = coef * gain + (1.0 - coef) * target;
+
+        // Apply ducking to music
+        music[music_idx] *= gain;
+
+        // Add the voice with its fade in/out
+        if let Some(&voice_sample) = voice_segment.samples.get(i) {
+            let fade_factor = if i < fade_samples {
+                i as f32 / fade_samples as f32
+            } else if i >= voice_segment.samples.len() - fade_samples {
+                (voice_segment.samples.len() - i) as f32 / fade_samples as f32
+            } else {
+                1.0
+            };
+            music[music_idx] += voice_sample * voice_volume * fade_factor;
+        }
     }
 }
 
@@ -260,32 +464,21 @@ pub fn select_wisdom_with_chorus(
     wisdom_data: &WisdomData,
     seed: u64,
 ) -> (String, Vec<String>, String) {
-    use rand::{Rng, SeedableRng};
+    use rand::{seq::SliceRandom, SeedableRng};
     let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
 
     if wisdom_data.wisdom.is_empty() {
         return (String::new(), Vec::new(), String::new());
     }
 
-    // Select 5 unique quotes: 1 intro + 3 chorus + 1 outro
-    let mut selected_indices = std::collections::HashSet::new();
-    while selected_indices.len() < 5.min(wisdom_data.wisdom.len()) {
-        let idx = rng.gen_range(0..wisdom_data.wisdom.len());
-        selected_indices.insert(idx);
-    }
-
-    let mut quotes: Vec<String> = selected_indices
-        .iter()
-        .map(|&idx| wisdom_data.wisdom[idx].clone())
+    // Select 5 quotes: 1 intro + 3 chorus + 1 outro
+    // Cycle through a shuffled order so short files use every quote before repeating one
+    let mut order: Vec<usize> = (0..wisdom_data.wisdom.len()).collect();
+    order.shuffle(&mut rng);
+    let quotes: Vec<String> = (0..5)
+        .map(|i| wisdom_data.wisdom[order[i % order.len()]].clone())
         .collect();
 
-    // Ensure we have enough quotes
-    if quotes.len() < 5 {
-        while quotes.len() < 5 {
-            quotes.push(wisdom_data.wisdom[0].clone());
-        }
-    }
-
     let intro = quotes[0].clone();
     let chorus = vec![quotes[1].clone(), quotes[2].clone(), quotes[3].clone()];
     let outro = quotes[4].clone();
@@ -293,6 +486,70 @@ pub fn select_wisdom_with_chorus(
     (intro, chorus, outro)
 }
 
+/// How many times the chorus cycles in a song of `song_seconds`: the segment budget from
+/// `segments_per_minute`, less the intro and outro, in whole chorus cycles.
+/// Songs too short for more than the intro and outro get none.
+pub fn chorus_repeats(song_seconds: f32, segments_per_minute: f32, chorus_len: usize) -> usize {
+    if chorus_len == 0 {
+        return 0;
+    }
+    let budget = (song_seconds.max(0.0) / 60.0 * segments_per_minute.max(0.0)).round() as usize;
+    budget.saturating_sub(2) / chorus_len
+}
+
+/// Voice segment texts in playback order: intro, the chorus `repeats` times, outro.
+/// `repeats` of `None` derives the count from `segments_per_minute`; empty quotes are skipped.
+pub fn generate_voice_segments(
+    intro: &str,
+    chorus_quotes: &[String],
+    outro: &str,
+    song_seconds: f32,
+    segments_per_minute: f32,
+    repeats: Option<usize>,
+) -> Vec<String> {
+    let casting = VoiceCasting::Single(VoiceType::Female);
+    cast_voice_segments(intro, chorus_quotes, outro, song_seconds, segments_per_minute, repeats, casting)
+        .into_iter()
+        .map(|(text, _)| text)
+        .collect()
+}
+
+/// `generate_voice_segments` with a voice for each segment, assigned by `casting`
+pub fn cast_voice_segments(
+    intro: &str,
+    chorus_quotes: &[String],
+    outro: &str,
+    song_seconds: f32,
+    segments_per_minute: f32,
+    repeats: Option<usize>,
+    casting: VoiceCasting,
+) -> Vec<(String, VoiceType)> {
+    let chorus: Vec<&String> = chorus_quotes.iter().filter(|quote| !quote.is_empty()).collect();
+    let repeats = if chorus.is_empty() {
+        0
+    } else {
+        repeats.unwrap_or_else(|| chorus_repeats(song_seconds, segments_per_minute, chorus.len()))
+    };
+
+    let mut segments: Vec<(String, VoiceType)> = Vec::with_capacity(2 + chorus.len() * repeats);
+    let mut push = |text: &str, is_chorus: bool| {
+        let voice = casting.voice_for(segments.len(), is_chorus);
+        segments.push((text.to_string(), voice));
+    };
+    if !intro.is_empty() {
+        push(intro, false);
+    }
+    for _ in 0..repeats {
+        for quote in &chorus {
+            push(quote, true);
+        }
+    }
+    if !outro.is_empty() {
+        push(outro, false);
+    }
+    segments
+}
+
 /// Legacy function for backwards compatibility
 pub fn select_wisdom(
     wisdom_data: &WisdomData,
@@ -319,21 +576,62 @@ pub fn select_wisdom(
     selected
 }
 
+/// Where voice segments go in the song
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoicePlacement {
+    Intro,
+    IntroOutro,
+    Bridge,
+    IntroBridge,
+    Distributed,
+    EveryNBars(u32), // One segment on every Nth bar line
+}
+
+impl FromStr for VoicePlacement {
+    type Err = String;
+
+    /// Parse a `voice.placement` value: "intro", "intro_outro", "bridge", "intro_bridge",
+    /// "distributed" or "every_<n>_bars" (e.g. "every_8_bars")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "intro" => Ok(VoicePlacement::Intro),
+            "intro_outro" => Ok(VoicePlacement::IntroOutro),
+            "bridge" => Ok(VoicePlacement::Bridge),
+            "intro_bridge" => Ok(VoicePlacement::IntroBridge),
+            "distributed" => Ok(VoicePlacement::Distributed),
+            _ => s
+                .strip_prefix("every_")
+                .and_then(|rest| rest.strip_suffix("_bars"))
+                .and_then(|bars| bars.parse::<u32>().ok())
+                .filter(|&bars| bars > 0)
+                .map(VoicePlacement::EveryNBars)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown voice placement '{}' (expected intro, intro_outro, bridge, intro_bridge, distributed or every_<n>_bars)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
 /// Determine voice placement timestamps based on song arrangement
+/// `tempo` (BPM, 4/4) is only used by `EveryNBars`, which lands every segment on a bar line.
 pub fn calculate_voice_timings(
-    placement: &str,
+    placement: VoicePlacement,
     total_duration_samples: usize,
     num_segments: usize,
     sample_rate: u32,
+    tempo: u16,
 ) -> Vec<usize> {
     let mut timings = Vec::new();
 
     match placement {
-        "intro" => {
+        VoicePlacement::Intro => {
             // Place at the start (after 1 second)
             timings.push(sample_rate as usize);
         }
-        "intro_outro" => {
+        VoicePlacement::IntroOutro => {
             // Place at start and end
             timings.push(sample_rate as usize);
             if num_segments > 1 {
@@ -341,12 +639,12 @@ pub fn calculate_voice_timings(
                 timings.push(outro_time);
             }
         }
-        "bridge" => {
+        VoicePlacement::Bridge => {
             // Place in the middle
             let mid_time = total_duration_samples / 2;
             timings.push(mid_time);
         }
-        "intro_bridge" => {
+        VoicePlacement::IntroBridge => {
             // Place at intro and bridge
             timings.push(sample_rate as usize);
             if num_segments > 1 {
@@ -354,7 +652,7 @@ pub fn calculate_voice_timings(
                 timings.push(bridge_time);
             }
         }
-        "distributed" => {
+        VoicePlacement::Distributed => {
             // Distribute evenly throughout the song with gaps
             if num_segments == 0 {
                 return timings;
@@ -376,9 +674,18 @@ pub fn calculate_voice_timings(
                 }
             }
         }
-        _ => {
-            // Default: intro
-            timings.push(sample_rate as usize);
+        VoicePlacement::EveryNBars(bars) => {
+            // Bar N, 2N, 3N... as long as the segment still starts 10 seconds before the end
+            let bar_seconds = 4.0 * 60.0 / tempo.max(1) as f64;
+            let last_start = total_duration_samples.saturating_sub(sample_rate as usize * 10);
+            for i in 1..=num_segments {
+                let bar = bars as usize * i;
+                let time = (bar as f64 * bar_seconds * sample_rate as f64).round() as usize;
+                if time > last_start {
+                    break;
+                }
+                timings.push(time);
+            }
         }
     }
 
@@ -414,9 +721,170 @@ mod tests {
 
     #[test]
     fn test_calculate_voice_timings() {
-        let timings = calculate_voice_timings("intro_bridge", 88200 * 180, 2, 44100);
+        let timings = calculate_voice_timings(VoicePlacement::IntroBridge, 88200 * 180, 2, 44100, 120);
         assert_eq!(timings.len(), 2);
         assert_eq!(timings[0], 44100); // 1 second
         assert!(timings[1] > timings[0]);
     }
+
+    #[test]
+    fn test_voice_placement_parses_and_snaps_to_bars() {
+        assert_eq!("distributed".parse(), Ok(VoicePlacement::Distributed));
+        assert_eq!("every_8_bars".parse(), Ok(VoicePlacement::EveryNBars(8)));
+        for typo in ["distribued", "every_0_bars", "every_bars", ""] {
+            assert!(typo.parse::<VoicePlacement>().is_err(), "{:?}", typo);
+        }
+
+        // 120 BPM: a bar is 2 seconds, so every 4 bars = every 8 seconds
+        let timings = calculate_voice_timings(VoicePlacement::EveryNBars(4), 44100 * 60, 10, 44100, 120);
+        assert_eq!(timings, vec![44100 * 8, 44100 * 16, 44100 * 24, 44100 * 32, 44100 * 40, 44100 * 48]);
+    }
+
+    #[test]
+    fn test_missing_tts_script_is_a_typed_error() {
+        let missing = Path::new("scripts/no_such_tts.py");
+        let result = generate_tts_with_script("hello", "en", VoiceType::Female, missing);
+
+        #[cfg(not(feature = "formant-tts"))]
+        assert!(matches!(result, Err(TtsBackendError::MissingScript(path)) if path == missing));
+
+        // The offline voice takes over instead
+        #[cfg(feature = "formant-tts")]
+        {
+            let (samples, sample_rate) = result.unwrap();
+            assert_eq!(sample_rate, FORMANT_TTS_SAMPLE_RATE);
+            assert!(samples.iter().any(|&s| s.abs() > 10000));
+            assert!(formant_tts("   ", VoiceType::Male).0.iter().all(|&s| s == 0));
+        }
+    }
+
+    #[test]
+    fn test_ducking_is_smoothed() {
+        let sample_rate = 44100;
+        let step = sample_rate / 2;
+        // Silence, then a sudden loud voice for one second
+        let mut samples = vec![0.0; step];
+        samples.extend((0..sample_rate).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }));
+        let voice = VoiceSegment { text: String::new(), start_sample: 0, samples };
+
+        // Voice muted so the music is just the gain curve
+        let mut music = vec![1.0; sample_rate * 3];
+        mix_with_ducking(&mut music, &voice, 0.0, -6.0, sample_rate as u32);
+        let duck = 10f32.powf(-6.0 / 20.0);
+
+        // No sample-to-sample jump anywhere, and the attack takes a few milliseconds
+        assert!(music.windows(2).all(|w| (w[1] - w[0]).abs() < 0.005));
+        assert!(music[step] > 0.8, "ducked instantly: {}", music[step]);
+        assert!((music[step + sample_rate / 10] - duck).abs() < 0.02);
+
+        // After the voice ends the music recovers gradually rather than snapping back
+        let end = step + sample_rate;
+        assert!(music[end + sample_rate / 100] < 0.6);
+        assert!(music[end + sample_rate * 3 / 2 - 1] > 0.95);
+    }
+
+    #[test]
+    fn test_call_and_response_casting() {
+        let chorus = vec!["a".to_string(), "b".to_string()];
+        let cast = cast_voice_segments("in", &chorus, "out", 0.0, 0.0, Some(2), VoiceCasting::CallResponse);
+        let voices: Vec<VoiceType> = cast.iter().map(|(_, voice)| *voice).collect();
+        use VoiceType::{Female, Male};
+        assert_eq!(voices, [Male, Female, Female, Female, Female, Male]);
+
+        let alternating = cast_voice_segments("", &chorus, "out", 0.0, 0.0, Some(1), VoiceCasting::Alternating);
+        assert_eq!(alternating.iter().map(|(_, voice)| *voice).collect::<Vec<_>>(), [Male, Female, Male]);
+
+        // Backends with a single voice drop the male take in pitch
+        assert!(Male.playback_ratio() < Female.playback_ratio());
+        assert!(Male.glottal_hz() < Female.glottal_hz());
+    }
+
+    #[test]
+    fn test_voice_segments_chorus_repeats() {
+        let chorus = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        // 3 minutes at 3/min = 9 segments: intro + outro + 2 chorus cycles
+        assert_eq!(chorus_repeats(180.0, 3.0, 3), 2);
+        // Shorter than intro + outro: no chorus, no underflow
+        assert_eq!(chorus_repeats(20.0, 3.0, 3), 0);
+        assert_eq!(chorus_repeats(180.0, 3.0, 0), 0);
+
+        let derived = generate_voice_segments("in", &chorus, "out", 180.0, 3.0, None);
+        assert_eq!(derived, ["in", "a", "b", "c", "a", "b", "c", "out"]);
+
+        // An explicit count wins over song length
+        let fixed = generate_voice_segments("in", &chorus, "out", 600.0, 3.0, Some(1));
+        assert_eq!(fixed, ["in", "a", "b", "c", "out"]);
+
+        // No chorus quotes: just the intro and outro, no empty segments
+        let empty = generate_voice_segments("in", &[String::new()], "out", 600.0, 3.0, Some(4));
+        assert_eq!(empty, ["in", "out"]);
+    }
+
+    #[test]
+    fn test_wisdom_selection_uses_every_quote_before_repeating() {
+        for count in [2, 3, 10] {
+            let wisdom_data = WisdomData { wisdom: (0..count).map(|i| format!("quote {}", i)).collect() };
+            let (intro, chorus, outro) = select_wisdom_with_chorus(&wisdom_data, 7);
+            let mut picked = vec![intro];
+            picked.extend(chorus);
+            picked.push(outro);
+
+            let distinct: std::collections::HashSet<&String> = picked.iter().collect();
+            assert_eq!(distinct.len(), count.min(5), "{} quotes", count);
+            // Repeats only start once every quote has been used
+            let first: std::collections::HashSet<&String> = picked[..count.min(5)].iter().collect();
+            assert_eq!(first.len(), count.min(5));
+        }
+
+        // Same seed, same picks
+        let wisdom_data = WisdomData { wisdom: (0..10).map(|i| i.to_string()).collect() };
+        assert_eq!(select_wisdom_with_chorus(&wisdom_data, 3), select_wisdom_with_chorus(&wisdom_data, 3));
+    }
+
+    #[test]
+    fn test_resample_streams_long_clips() {
+        // 30 seconds of 441 Hz at 22050 Hz (whole cycles per second, so the phase can wrap)
+        let seconds = 30;
+        let input: Vec<i16> = (0..22050 * seconds)
+            .map(|i| (((i % 22050) as f32 * 441.0 * std::f32::consts::TAU / 22050.0).sin() * 16000.0) as i16)
+            .collect();
+
+        let output = resample_22050_to_44100(input).unwrap();
+        assert_eq!(output.len(), 44100 * seconds);
+
+        // Pitch and level survive, with no gaps between chunks: one upward swing per cycle
+        let mut cycles = 0usize;
+        let mut low = false;
+        for &sample in &output {
+            if sample < -8000 {
+                low = true;
+            } else if low && sample > 8000 {
+                low = false;
+                cycles += 1;
+            }
+        }
+        assert!(cycles.abs_diff(441 * seconds) <= 1, "{} cycles", cycles);
+        let middle = &output[44100 * 10..44100 * 20];
+        assert!(middle.windows(100).step_by(1000).all(|w| w.iter().any(|s| s.abs() > 14000)));
+    }
+
+    #[test]
+    fn test_resample_keeps_clicks_in_place() {
+        // A click at input frame N lands at output frame 2N when upsampling 2x, even in the tail
+        for at in [1000, 3990] {
+            let mut input = vec![0.0f32; 4000];
+            input[at] = 1.0;
+
+            let output = resample_chunked(&input, 2.0).unwrap();
+            assert_eq!(output.len(), 8000);
+            let peak = output
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+                .map(|(i, _)| i)
+                .unwrap();
+            assert!(peak.abs_diff(2 * at) <= 1, "click at {} landed at {}", at, peak);
+        }
+    }
 }
@@ -0,0 +1,254 @@
+/// WAV file writing with selectable sample format
+use super::clipping::{check_clipping, ClipReport};
+use super::error::SaveError;
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+
+/// Sample format for WAV output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    Int16,   // CD quality, smallest files
+    Int24,   // Studio headroom
+    Float32, // Full fidelity, no quantization
+}
+
+impl BitDepth {
+    /// Map a config `bit_depth` value (16 / 24 / 32) to a format
+    pub fn from_bits(bits: u16) -> Result<Self, String> {
+        match bits {
+            16 => Ok(BitDepth::Int16),
+            24 => Ok(BitDepth::Int24),
+            32 => Ok(BitDepth::Float32),
+            _ => Err(format!("unsupported bit depth {} (use 16, 24 or 32)", bits)),
+        }
+    }
+
+    /// Bits per sample as written in the fmt chunk
+    pub fn bits_per_sample(&self) -> u16 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+
+    /// Mono hound spec for this format. hound writes 24-bit and float files as
+    /// WAVE_FORMAT_EXTENSIBLE (tag 0xFFFE) with PCM or IEEE-float (3) as the sub-format.
+    fn wav_spec(&self, sample_rate: u32) -> hound::WavSpec {
+        hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: self.bits_per_sample(),
+            sample_format: match self {
+                BitDepth::Float32 => hound::SampleFormat::Float,
+                _ => hound::SampleFormat::Int,
+            },
+        }
+    }
+}
+
+/// Save audio samples to a mono WAV file in the given format. Integer formats clamp
+/// to full scale; float keeps overs as headroom. The returned report tells the caller
+/// what went past full scale, if anything.
+pub fn save_wav(
+    filename: &str,
+    samples: &[f32],
+    sample_rate: u32,
+    bit_depth: BitDepth,
+) -> Result<ClipReport, SaveError> {
+    let report = check_clipping(samples);
+    write_wav(BufWriter::new(File::create(filename)?), samples, sample_rate, bit_depth)?;
+    Ok(report)
+}
+
+/// Write mono samples as a WAV stream to any seekable writer (a file, or an
+/// in-memory buffer to pipe into an encoder)
+pub fn write_wav<W: Write + Seek>(
+    writer: W,
+    samples: &[f32],
+    sample_rate: u32,
+    bit_depth: BitDepth,
+) -> Result<(), SaveError> {
+    let mut writer = hound::WavWriter::new(writer, bit_depth.wav_spec(sample_rate))?;
+
+    // Write audio data in the requested format
+    for &sample in samples {
+        match bit_depth {
+            BitDepth::Int16 => writer.write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)?,
+            BitDepth::Int24 => writer.write_sample((sample.clamp(-1.0, 1.0) * 8_388_607.0) as i32)?,
+            BitDepth::Float32 => writer.write_sample(sample)?,
+        }
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Read a WAV file (16/24/32-bit PCM or 32-bit float) as normalized f32 samples.
+/// Returns (samples, sample rate, channels); multi-channel samples stay interleaved.
+pub fn read_wav(path: &str) -> Result<(Vec<f32>, u32, u16), hound::Error> {
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        hound::SampleFormat::Int => {
+            // Full scale of a signed integer sample at this bit depth
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+
+    Ok((samples, spec.sample_rate, spec.channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a short file and return its raw bytes
+    fn write_and_read(bit_depth: BitDepth) -> Vec<u8> {
+        let samples = vec![0.0, 0.5, -0.5, 1.0];
+        let path = std::env::temp_dir().join(format!("rust_beats_test_{:?}.wav", bit_depth));
+        let path = path.to_str().unwrap().to_string();
+        save_wav(&path, &samples, 48000, bit_depth).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        bytes
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_read_wav_round_trip() {
+        let samples: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.05).sin() * 0.8).collect();
+
+        for (bit_depth, tolerance) in [
+            (BitDepth::Int16, 1.0 / 16_000.0),
+            (BitDepth::Int24, 1.0 / 4_000_000.0),
+            (BitDepth::Float32, 0.0),
+        ] {
+            let path = std::env::temp_dir().join(format!("rust_beats_roundtrip_{:?}.wav", bit_depth));
+            let path = path.to_str().unwrap().to_string();
+            save_wav(&path, &samples, 48000, bit_depth).unwrap();
+            let (read, sample_rate, channels) = read_wav(&path).unwrap();
+            std::fs::remove_file(&path).ok();
+
+            assert_eq!(sample_rate, 48000);
+            assert_eq!(channels, 1);
+            assert_eq!(read.len(), samples.len());
+            for (a, b) in samples.iter().zip(&read) {
+                assert!((a - b).abs() <= tolerance, "{:?}: {} vs {}", bit_depth, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rendered_section_round_trip() {
+        use crate::composition::drum_humanizer::DrumHumanizer;
+        use crate::{MetalAudioRenderer, MetalSection, MetalSongGenerator, MetalSubgenre};
+
+        let generator = MetalSongGenerator::new(MetalSubgenre::ThrashMetal);
+        let riff = generator.generate_riff(MetalSection::Verse);
+        let audio = MetalAudioRenderer::new().render_section(
+            MetalSection::Verse, &riff, 2.0, 180, MetalSubgenre::ThrashMetal, &DrumHumanizer::thrash(),
+        );
+
+        let path = std::env::temp_dir().join("rust_beats_rendered_section.wav");
+        let path = path.to_str().unwrap().to_string();
+        let sample_rate = crate::get_sample_rate();
+        save_wav(&path, &audio, sample_rate, BitDepth::Int16).unwrap();
+        let (read, read_rate, _) = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        // Duration survives the round trip and the section isn't silent
+        assert_eq!(read_rate, sample_rate);
+        assert!((read.len() as f32 / read_rate as f32 - audio.len() as f32 / sample_rate as f32).abs() < 1e-3);
+        assert!(read.iter().any(|s| s.abs() > 0.01));
+    }
+
+    #[test]
+    fn test_save_reports_clamped_samples() {
+        let path = std::env::temp_dir().join("rust_beats_clipped.wav");
+        let path = path.to_str().unwrap().to_string();
+        for bit_depth in [BitDepth::Int16, BitDepth::Int24, BitDepth::Float32] {
+            let report = save_wav(&path, &[0.0, 1.5, 0.0, 0.2], 48000, bit_depth).unwrap();
+            let (read, _, _) = read_wav(&path).unwrap();
+            assert!(report.is_clipping(), "{:?}", bit_depth);
+            // Integer formats clamp; float keeps the over
+            if bit_depth == BitDepth::Float32 {
+                assert_eq!(read[1], 1.5);
+            } else {
+                assert!(read[1] <= 1.0, "{:?} wrote {}", bit_depth, read[1]);
+            }
+        }
+        std::fs::remove_file(&path).ok();
+        assert!(!save_wav(&path, &[0.0, 0.5], 48000, BitDepth::Int16).unwrap().is_clipping());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_unwritable_path_is_io_error() {
+        let path = std::env::temp_dir().join("rust_beats_missing_dir").join("song.wav");
+        let result = save_wav(path.to_str().unwrap(), &[0.0], 44100, BitDepth::Int16);
+        assert!(matches!(result, Err(SaveError::Io(_))));
+        assert!(matches!(read_wav(path.to_str().unwrap()), Err(hound::Error::IoError(_))));
+    }
+
+    #[test]
+    fn test_int16_header() {
+        let bytes = write_and_read(BitDepth::Int16);
+        assert_eq!(read_u16(&bytes, 20), 1); // PCM
+        assert_eq!(read_u32(&bytes, 24), 48000);
+        assert_eq!(read_u32(&bytes, 28), 96000); // byte rate
+        assert_eq!(read_u16(&bytes, 32), 2); // block align
+        assert_eq!(read_u16(&bytes, 34), 16);
+        assert_eq!(read_u32(&bytes, 40), 8); // data size
+        assert_eq!(bytes.len(), 44 + 8);
+    }
+
+    // Above 16 bits the fmt chunk is WAVE_FORMAT_EXTENSIBLE: the real format tag is
+    // the first field of the sub-format GUID at offset 44, and data starts at 68
+
+    #[test]
+    fn test_int24_header() {
+        let bytes = write_and_read(BitDepth::Int24);
+        assert_eq!(read_u16(&bytes, 20), 0xFFFE); // Extensible
+        assert_eq!(read_u16(&bytes, 44), 1); // PCM
+        assert_eq!(read_u32(&bytes, 28), 144000);
+        assert_eq!(read_u16(&bytes, 32), 3);
+        assert_eq!(read_u16(&bytes, 34), 24);
+        assert_eq!(read_u32(&bytes, 64), 12);
+        // Full-scale sample is the last 3 bytes
+        assert_eq!(&bytes[bytes.len() - 3..], &[0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_float32_header() {
+        let bytes = write_and_read(BitDepth::Float32);
+        assert_eq!(read_u16(&bytes, 20), 0xFFFE);
+        assert_eq!(read_u16(&bytes, 44), 3); // IEEE float
+        assert_eq!(read_u32(&bytes, 28), 192000);
+        assert_eq!(read_u16(&bytes, 32), 4);
+        assert_eq!(read_u16(&bytes, 34), 32);
+        assert_eq!(read_u32(&bytes, 4), 60 + 16); // RIFF size
+        let second = f32::from_le_bytes(bytes[72..76].try_into().unwrap());
+        assert_eq!(second, 0.5);
+    }
+
+    #[test]
+    fn test_unsupported_bit_depth_is_rejected() {
+        assert_eq!(BitDepth::from_bits(24), Ok(BitDepth::Int24));
+        assert!(BitDepth::from_bits(8).is_err());
+        assert!(BitDepth::from_bits(20).is_err());
+    }
+}
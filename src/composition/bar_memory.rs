@@ -1,6 +1,7 @@
 use crate::composition::music_theory::MidiNote;
-use crate::composition::metal_song_generator::RhythmPattern;
+use crate::composition::metal_song_generator::{MetalRiff, MetalSection, RhythmPattern};
 use rand::Rng;
+use std::collections::HashMap;
 
 /// Types of mutations that can be applied to a bar
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +30,7 @@ pub struct BarMotifStore {
     pub mutation_history: Vec<MutationType>,
     pub mutation_depth: usize,
     pub max_mutations: usize,
+    section_motifs: HashMap<MetalSection, MetalRiff>, // First riff written for each section
 }
 
 impl BarMotifStore {
@@ -41,9 +43,25 @@ impl BarMotifStore {
             mutation_history: Vec::new(),
             mutation_depth: 0,
             max_mutations: 3,
+            section_motifs: HashMap::new(),
         }
     }
 
+    /// Remember a section's riff so later repeats of that section can restate it
+    pub fn remember_section(&mut self, section: MetalSection, riff: &MetalRiff) {
+        self.section_motifs.insert(section, riff.clone());
+    }
+
+    /// The riff first written for a section, if any
+    pub fn recall_section(&self, section: MetalSection) -> Option<&MetalRiff> {
+        self.section_motifs.get(&section)
+    }
+
+    /// Forget all section riffs (start of a new song)
+    pub fn forget_sections(&mut self) {
+        self.section_motifs.clear();
+    }
+
     /// Store a new bar for future mutations
     pub fn store_bar(&mut self, notes: &[MidiNote], rhythms: &[RhythmPattern]) {
         self.previous_notes = notes.to_vec();
@@ -113,7 +131,7 @@ impl BarMotifStore {
 
     /// Insert slides instead of re-rolling notes
     pub fn insert_slides(&self, probability: f32) -> Vec<NoteModifier> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut modifiers = Vec::with_capacity(self.previous_notes.len());
         
         for i in 0..self.previous_notes.len() {
@@ -144,7 +162,7 @@ impl BarMotifStore {
 
     /// Insert trills (rapid alternation between two notes)
     pub fn insert_trills(&self, probability: f32) -> Vec<NoteModifier> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut modifiers = Vec::with_capacity(self.previous_notes.len());
         
         for _ in 0..self.previous_notes.len() {
@@ -160,7 +178,7 @@ impl BarMotifStore {
 
     /// Generate a mutated bar based on the previous bar
     pub fn mutate_bar(&mut self, root: MidiNote) -> (Vec<MidiNote>, Vec<RhythmPattern>, Vec<NoteModifier>) {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Choose mutation type
         let mutation = match rng.gen_range(0..5) {
@@ -251,4 +269,27 @@ mod tests {
         store.reset();
         assert_eq!(store.mutation_depth, 0);
     }
+
+    #[test]
+    fn test_section_motifs_are_keyed_by_section() {
+        let mut store = BarMotifStore::new();
+        let riff = MetalRiff {
+            notes: vec![40, 43, 40, 46],
+            chord_types: vec![crate::composition::metal_song_generator::ChordType::Power; 4],
+            palm_muted: vec![true; 4],
+            rhythms: vec![RhythmPattern::SixteenthNote; 4],
+            velocities: Vec::new(),
+            fret_positions: Vec::new(),
+            harmony_notes: None,
+            playability_score: 1.0,
+            clean: false,
+        };
+
+        store.remember_section(MetalSection::Verse, &riff);
+        assert_eq!(store.recall_section(MetalSection::Verse).map(|r| r.notes.clone()), Some(riff.notes));
+        assert!(store.recall_section(MetalSection::Chorus).is_none());
+
+        store.forget_sections();
+        assert!(store.recall_section(MetalSection::Verse).is_none());
+    }
 }
@@ -16,6 +16,47 @@ pub enum BassMode {
     Follow,
 }
 
+/// Lowest guitar note at which death and prog bass lock in unison with the guitar
+/// (Drop C and below): an octave under that is felt more than heard
+const TIGHT_UNISON_BELOW: MidiNote = 37;
+
+/// Pitch of the bass against the guitar note it follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BassPitchRelation {
+    /// Same pitch as the guitar: tight and audible on low tunings
+    Unison,
+    /// An octave below the guitar (the classic doubling)
+    OctaveDown,
+    /// The power chord's fifth, under the guitar root
+    Fifth,
+}
+
+impl BassPitchRelation {
+    /// Offset from the guitar note in semitones
+    pub fn offset(self) -> i8 {
+        match self {
+            BassPitchRelation::Unison => 0,
+            BassPitchRelation::OctaveDown => -12,
+            BassPitchRelation::Fifth => -5,
+        }
+    }
+
+    /// Relation for a subgenre, given how low the guitar already sits: extended-range
+    /// tunings always get unison, death and prog lock in unison from Drop C down,
+    /// prog otherwise plays the fifth against standard-tuned riffs
+    pub fn for_subgenre(subgenre: MetalSubgenre, tuning: &GuitarTuning) -> Self {
+        if tuning.bass_should_use_unison() {
+            return BassPitchRelation::Unison;
+        }
+        let low_tuning = tuning.lowest_note() < TIGHT_UNISON_BELOW;
+        match subgenre {
+            MetalSubgenre::DeathMetal | MetalSubgenre::ProgressiveMetal if low_tuning => BassPitchRelation::Unison,
+            MetalSubgenre::ProgressiveMetal => BassPitchRelation::Fifth,
+            _ => BassPitchRelation::OctaveDown,
+        }
+    }
+}
+
 /// Bass line generator for metal music
 /// Follows the guitar riff but simplified and lower
 pub struct MetalBassGenerator {
@@ -64,7 +105,7 @@ impl MetalBassGenerator {
     /// Simplify a guitar note to a bass note
     /// Bass emphasizes root notes and power chord roots
     fn simplify_to_bass(&self, guitar_note: MidiNote, root: MidiNote) -> MidiNote {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Calculate the note within the octave
         let note_class = guitar_note % 12;
@@ -121,7 +162,7 @@ impl MetalBassGenerator {
         let mut current_note = 28 + (root % 12); // Bass register
         bass_line.push(current_note);
         
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         for _ in 1..length {
             // Walk up or down the scale
@@ -157,7 +198,7 @@ impl MetalBassGenerator {
         // Breakdown: mostly root notes with occasional fifth
         let fifth = bass_root + 7;
         let mut bass_line = Vec::with_capacity(length);
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         for i in 0..length {
             if i % 4 == 0 {
@@ -239,7 +280,7 @@ impl MetalBassGenerator {
         let scale = key.get_scale_notes();
         
         let mut bass_line = Vec::with_capacity(length);
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Counterpoint: walk through scale notes, emphasizing root and fifth
         let fifth = bass_root + 7;
@@ -269,6 +310,39 @@ mod tests {
     use super::*;
     use crate::composition::music_theory::ScaleType;
 
+    #[test]
+    fn test_unison_bass_on_low_tunings() {
+        assert_eq!(BassPitchRelation::Unison.offset(), 0);
+        for subgenre in MetalSubgenre::ALL {
+            assert_eq!(BassPitchRelation::for_subgenre(subgenre, &GuitarTuning::DropA7), BassPitchRelation::Unison);
+        }
+        // Death metal tightens up from Drop C; heavy metal still doubles an octave down
+        assert_eq!(BassPitchRelation::for_subgenre(MetalSubgenre::DeathMetal, &GuitarTuning::DropC), BassPitchRelation::Unison);
+        assert_eq!(BassPitchRelation::for_subgenre(MetalSubgenre::HeavyMetal, &GuitarTuning::DropC), BassPitchRelation::OctaveDown);
+    }
+
+    #[test]
+    fn test_octave_down_bass_on_standard_tunings() {
+        assert_eq!(BassPitchRelation::OctaveDown.offset(), -12);
+        for subgenre in [MetalSubgenre::HeavyMetal, MetalSubgenre::ThrashMetal, MetalSubgenre::DeathMetal, MetalSubgenre::DoomMetal] {
+            assert_eq!(BassPitchRelation::for_subgenre(subgenre, &GuitarTuning::EStandard), BassPitchRelation::OctaveDown);
+        }
+    }
+
+    #[test]
+    fn test_fifth_bass_for_prog() {
+        // E2 guitar root: the bass plays the B below it
+        assert_eq!((40 + BassPitchRelation::Fifth.offset()) as u8 % 12, (40 + 7) % 12);
+        assert_eq!(
+            BassPitchRelation::for_subgenre(MetalSubgenre::ProgressiveMetal, &GuitarTuning::DStandard),
+            BassPitchRelation::Fifth
+        );
+        assert_eq!(
+            BassPitchRelation::for_subgenre(MetalSubgenre::ProgressiveMetal, &GuitarTuning::DropC),
+            BassPitchRelation::Unison
+        );
+    }
+
     #[test]
     fn test_bass_generator_creation() {
         let generator = MetalBassGenerator::new(GuitarTuning::DStandard);
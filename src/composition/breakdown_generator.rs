@@ -43,7 +43,7 @@ impl BreakdownGenerator {
     /// Apply syncopated silences to a note pattern
     /// Returns (note, is_silent) pairs
     pub fn apply_syncopated_silences(&self, notes: &[MidiNote]) -> Vec<(MidiNote, bool)> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut result = Vec::new();
 
         for (i, &note) in notes.iter().enumerate() {
@@ -71,7 +71,7 @@ impl BreakdownGenerator {
     /// Generate dotted-eighth stab pattern
     /// Returns positions in 16th notes where stabs occur
     pub fn generate_dotted_eighth_stabs(&self, bars: usize) -> Vec<usize> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let total_sixteenths = bars * 16;
         let mut positions = Vec::new();
 
@@ -104,7 +104,7 @@ impl BreakdownGenerator {
     /// Inject random silences into a pattern
     /// Returns indices where silences should occur
     pub fn random_silence_positions(&self, pattern_length: usize) -> Vec<usize> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut silences = Vec::new();
 
         for i in 1..pattern_length { // Skip first position
@@ -123,7 +123,7 @@ impl BreakdownGenerator {
         root_note: MidiNote,
         bars: usize,
     ) -> Vec<(usize, MidiNote, f32, bool)> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let stab_positions = self.generate_dotted_eighth_stabs(bars);
         let total_sixteenths = bars * 16;
         
@@ -134,9 +134,11 @@ impl BreakdownGenerator {
                 break;
             }
 
-            // Vary the note slightly (octave or fifth)
+            // Vary the note slightly (octave or fifth); the opening stab always hits
+            // the root so the breakdown stays anchored to its chord
+            let opening = pattern.is_empty();
             let note_variation = rng.gen_range(0..3);
-            let note = match note_variation {
+            let note = match if opening { 0 } else { note_variation } {
                 0 => root_note,                    // Root
                 1 => root_note.saturating_add(7),  // Fifth
                 _ => root_note.saturating_sub(12), // Octave down
@@ -146,7 +148,7 @@ impl BreakdownGenerator {
             let duration_mult = self.metric_modulation_multiplier(pos, total_sixteenths);
 
             // Check if this should be silent (random silence)
-            let is_silent = rng.gen_bool((self.silence_probability * 0.3) as f64);
+            let is_silent = rng.gen_bool((self.silence_probability * 0.3) as f64) && !opening;
 
             pattern.push((pos, note, duration_mult, is_silent));
         }
@@ -226,6 +228,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_breakdown_opens_on_the_root() {
+        let gen = BreakdownPattern::Chaotic.generator();
+        for _ in 0..50 {
+            if let Some(&(_, note, _, is_silent)) = gen.generate_breakdown_pattern(43, 2).first() {
+                assert_eq!(note, 43);
+                assert!(!is_silent);
+            }
+        }
+    }
+
     #[test]
     fn test_metric_modulation() {
         let gen = BreakdownGenerator::new();
@@ -71,7 +71,7 @@ impl HiHatPattern {
 
     /// Create mostly closed with occasional opens
     pub fn mostly_closed(length: usize, open_probability: f32) -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut pattern = Vec::with_capacity(length);
         for _ in 0..length {
             pattern.push(if rng.gen_bool(open_probability as f64) {
@@ -175,7 +175,9 @@ impl StaminaModel {
         // Decay starts after 16 hits, so we subtract 1 to make hits 1-16 return base velocity
         let decay_amount = ((hit_count - 1) / 16) as f32 * self.decay_rate;
         let decayed = base_velocity as f32 - decay_amount;
-        decayed.max(self.min_velocity as f32) as u8
+        // Floor only applies to hits that started above it (ghost notes stay soft)
+        let floor = self.min_velocity.min(base_velocity);
+        decayed.max(floor as f32) as u8
     }
 
     /// Reset stamina (after a rest)
@@ -309,7 +311,7 @@ impl DrumArticulationGenerator {
 
     /// Check if we should add a flam (25% probability on beat 4)
     pub fn should_flam(&self, beat_position: usize) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         beat_position % 4 == 3 && rng.gen_bool(0.25)
     }
 
@@ -368,6 +370,12 @@ mod tests {
         assert_eq!(stamina.hit_snare(100), 98);
     }
 
+    #[test]
+    fn test_stamina_keeps_ghost_notes_soft() {
+        let mut stamina = StaminaModel::new(2.0, 70);
+        assert_eq!(stamina.hit_snare(Articulation::GhostNote.base_velocity()), 30);
+    }
+
     #[test]
     fn test_stamina_reset() {
         let mut stamina = StaminaModel::new(2.0, 70);
@@ -72,7 +72,7 @@ impl DrumHumanizer {
     /// Humanize a velocity value
     /// Returns a velocity with randomization and optional accent
     pub fn humanize_velocity(&self, base_velocity: Velocity, is_accent: bool) -> Velocity {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Apply random variance (±)
         let variance = rng.gen_range(-(self.velocity_variance as i16)..=(self.velocity_variance as i16));
@@ -89,7 +89,7 @@ impl DrumHumanizer {
     /// Humanize timing offset
     /// Returns timing offset in ticks
     pub fn humanize_timing(&self) -> TimingOffset {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Apply bias + random variance
         let variance = rng.gen_range(-self.timing_variance..=self.timing_variance);
@@ -98,7 +98,7 @@ impl DrumHumanizer {
 
     /// Check if this hit should be accented (random based on probability)
     pub fn should_accent(&self) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         rng.gen::<f32>() < self.accent_probability
     }
 
@@ -126,7 +126,29 @@ pub enum BlastBeatStyle {
     Traditional,  // Kick and snare simultaneous
     Hammer,       // Kick and snare unison (same as traditional)
     Euro,         // Kick and snare alternate
-    Gravity,      // Uses rimshot articulations
+    Gravity,      // One-handed snare roll over half-rate kicks
+}
+
+/// Chance of a style switch, accent shift or fill when none is configured
+pub const DEFAULT_BLAST_VARIATION: f32 = 0.3;
+
+/// Bars per blast phrase: style switches happen at phrase starts, fills on phrase ends
+pub const BLAST_PHRASE_BARS: usize = 4;
+
+/// A multi-bar blast beat, one entry per subdivision
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlastPattern {
+    pub kicks: Vec<bool>,
+    pub snares: Vec<bool>,
+    pub accents: Vec<bool>,
+}
+
+impl BlastPattern {
+    /// (kicks, snares, accents) of one bar
+    pub fn bar(&self, bar: usize, subdivisions: usize) -> (&[bool], &[bool], &[bool]) {
+        let range = bar * subdivisions..(bar + 1) * subdivisions;
+        (&self.kicks[range.clone()], &self.snares[range.clone()], &self.accents[range])
+    }
 }
 
 /// Generate a blast beat pattern
@@ -154,10 +176,10 @@ pub fn generate_blast_beat(style: BlastBeatStyle, subdivisions: usize) -> (Vec<b
             }
         }
         BlastBeatStyle::Gravity => {
-            // Similar to traditional but with rimshot articulation
-            // (articulation would be handled separately in MIDI)
+            // The gravity roll keeps the snare going on every subdivision,
+            // the feet drop to every other one
             for i in 0..subdivisions {
-                kicks[i] = true;
+                kicks[i] = i % 2 == 0;
                 snares[i] = true;
             }
         }
@@ -166,6 +188,51 @@ pub fn generate_blast_beat(style: BlastBeatStyle, subdivisions: usize) -> (Vec<b
     (kicks, snares)
 }
 
+/// Blast beat over `bars` bars of `subdivisions` hits that doesn't repeat one bar forever.
+/// With probability `variation` (0.0 = the same traditional bar throughout) each phrase
+/// may switch between traditional and gravity blasts, each bar may shift its accent off
+/// the downbeat, and a phrase's last bar may end in a fill (feet drop out under a snare
+/// roll for the last beat, every stroke accented).
+pub fn generate_varied_blast(bars: usize, subdivisions: usize, variation: f32) -> BlastPattern {
+    let mut rng = crate::utils::rng();
+    let variation = variation.clamp(0.0, 1.0) as f64;
+    let mut pattern = BlastPattern { kicks: Vec::new(), snares: Vec::new(), accents: Vec::new() };
+    let mut style = BlastBeatStyle::Traditional;
+
+    for bar in 0..bars {
+        let phrase_start = bar.is_multiple_of(BLAST_PHRASE_BARS);
+        if phrase_start && bar > 0 && rng.gen_bool(variation) {
+            style = match style {
+                BlastBeatStyle::Gravity => BlastBeatStyle::Traditional,
+                _ => BlastBeatStyle::Gravity,
+            };
+        }
+        let (mut kicks, mut snares) = generate_blast_beat(style, subdivisions);
+
+        // Accent on the downbeat, or shifted to the offbeats
+        let shifted = rng.gen_bool(variation);
+        let mut accents: Vec<bool> = (0..subdivisions)
+            .map(|i| if shifted { i % 2 == 1 } else { i == 0 })
+            .collect();
+
+        let phrase_end = (bar + 1).is_multiple_of(BLAST_PHRASE_BARS);
+        if phrase_end && rng.gen_bool(variation) {
+            let fill_start = subdivisions - subdivisions / 4;
+            for i in fill_start..subdivisions {
+                kicks[i] = false;
+                snares[i] = true;
+                accents[i] = true;
+            }
+        }
+
+        pattern.kicks.extend(kicks);
+        pattern.snares.extend(snares);
+        pattern.accents.extend(accents);
+    }
+
+    pattern
+}
+
 /// Velocity profile for blast beats
 /// Based on research: blast beats have lower velocity due to smaller range of motion
 pub fn blast_beat_velocity(base_velocity: Velocity, is_first_beat: bool) -> Velocity {
@@ -284,6 +351,30 @@ mod tests {
         assert!(snares.iter().all(|&x| x));
     }
 
+    #[test]
+    fn test_varied_blast_changes_between_bars() {
+        let bars = 16;
+        let distinct_bars = |pattern: &BlastPattern| {
+            let mut seen: Vec<(&[bool], &[bool], &[bool])> = Vec::new();
+            for bar in 0..bars {
+                let current = pattern.bar(bar, 8);
+                if !seen.contains(&current) {
+                    seen.push(current);
+                }
+            }
+            seen.len()
+        };
+
+        let varied = generate_varied_blast(bars, 8, 0.5);
+        assert_eq!(varied.kicks.len(), bars * 8);
+        assert!(distinct_bars(&generate_varied_blast(bars, 8, 1.0)) >= 2);
+
+        // No variation: the same traditional bar all the way through
+        let steady = generate_varied_blast(bars, 8, 0.0);
+        assert_eq!(distinct_bars(&steady), 1);
+        assert!(steady.kicks.iter().all(|&hit| hit));
+    }
+
     #[test]
     fn test_blast_beat_euro() {
         let (kicks, snares) = generate_blast_beat(BlastBeatStyle::Euro, 8);
@@ -0,0 +1,333 @@
+//! Section drum patterns on the 16th-note grid: which steps the kick, snare and
+//! cymbals hit for each rhythmic feel, before any sound is chosen or rendered.
+
+use crate::composition::{
+    drum_humanizer::{generate_varied_blast, DEFAULT_BLAST_VARIATION},
+    metal_song_generator::{MetalSection, MetalSubgenre, RhythmicFeel},
+    phrase_drums::{GuitarContext, PhraseAwareDrumGenerator},
+    rhythm_generator,
+};
+use crate::utils::get_sample_rate;
+use serde::Serialize;
+
+/// Bars per block of `DrumGrid::chart`
+const CHART_BARS_PER_LINE: usize = 4;
+
+/// How often the time-keeping cymbal is struck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CymbalDensity {
+    /// Crash on each bar's downbeat only
+    Sparse,
+    /// Every beat
+    Quarter,
+    /// Every eighth note
+    Eighth,
+}
+
+impl CymbalDensity {
+    /// Distance between cymbal hits on the 16th-note grid
+    pub fn step_interval(&self) -> usize {
+        match self {
+            CymbalDensity::Sparse => 16,
+            CymbalDensity::Quarter => 4,
+            CymbalDensity::Eighth => 2,
+        }
+    }
+}
+
+/// Which cymbal keeps time between the downbeat crashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeKeeper {
+    ClosedHiHat,
+    Ride,
+}
+
+/// Cymbal part for a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CymbalStyle {
+    pub density: CymbalDensity,
+    pub time_keeper: TimeKeeper,
+}
+
+impl CymbalStyle {
+    pub fn new(density: CymbalDensity, time_keeper: TimeKeeper) -> Self {
+        Self { density, time_keeper }
+    }
+
+    /// Tight hats in verses, the chorus opens up on an eighth-note ride
+    pub fn for_section(section: MetalSection) -> Self {
+        match section {
+            MetalSection::Intro | MetalSection::Outro => Self::new(CymbalDensity::Sparse, TimeKeeper::ClosedHiHat),
+            MetalSection::Verse => Self::new(CymbalDensity::Quarter, TimeKeeper::ClosedHiHat),
+            MetalSection::PreChorus => Self::new(CymbalDensity::Eighth, TimeKeeper::ClosedHiHat),
+            MetalSection::Chorus | MetalSection::Solo => Self::new(CymbalDensity::Eighth, TimeKeeper::Ride),
+            MetalSection::Breakdown => Self::new(CymbalDensity::Quarter, TimeKeeper::Ride),
+        }
+    }
+}
+
+/// A section's drum pattern before any audio: one hit flag per voice for every
+/// 16th-note step (`DrumGrid::STEPS_PER_BAR` per 4/4 bar)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DrumGrid {
+    pub kick: Vec<bool>,
+    pub snare: Vec<bool>,
+    pub cymbal: Vec<bool>,
+    pub accent: Vec<bool>, // Steps struck harder: bar downbeats, or a blast's own accents
+}
+
+impl DrumGrid {
+    pub const STEPS_PER_BAR: usize = 16;
+
+    /// Number of 16th-note steps
+    pub fn steps(&self) -> usize {
+        self.kick.len()
+    }
+
+    /// Number of bars (a partial last bar counts)
+    pub fn bars(&self) -> usize {
+        self.steps().div_ceil(Self::STEPS_PER_BAR)
+    }
+
+    /// Steps a voice hits on (e.g. `grid.hit_steps(&grid.snare)`)
+    pub fn hit_steps(&self, voice: &[bool]) -> Vec<usize> {
+        voice.iter().enumerate().filter(|(_, &hit)| hit).map(|(step, _)| step).collect()
+    }
+
+    /// Drum chart, `CHART_BARS_PER_LINE` bars per block: a row per voice with `x` for a
+    /// hit, `X` for an accented one and `.` for a rest, bars split by `|`
+    pub fn chart(&self) -> String {
+        let mut out = String::new();
+        let block_steps = CHART_BARS_PER_LINE * Self::STEPS_PER_BAR;
+        for start in (0..self.steps()).step_by(block_steps) {
+            let end = (start + block_steps).min(self.steps());
+            for (name, voice) in [("Cymbal", &self.cymbal), ("Snare", &self.snare), ("Kick", &self.kick)] {
+                out.push_str(&format!("   {:<6} |", name));
+                for (step, (&hit, &accent)) in voice.iter().zip(&self.accent).enumerate().take(end).skip(start) {
+                    out.push(match (hit, accent) {
+                        (true, true) => 'X',
+                        (true, false) => 'x',
+                        _ => '.',
+                    });
+                    if (step + 1) % Self::STEPS_PER_BAR == 0 || step + 1 == end {
+                        out.push('|');
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Drummer choices that shape a grid (the default is what the renderer plays unless told otherwise)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrumPatternOptions {
+    pub blast_variation: f32,              // Chance of a style switch, accent shift or fill per blast bar
+    pub double_bass: bool,                 // Death and thrash verses roll double bass
+    pub cymbal_style: Option<CymbalStyle>, // None = `CymbalStyle::for_section`
+}
+
+impl Default for DrumPatternOptions {
+    fn default() -> Self {
+        Self { blast_variation: DEFAULT_BLAST_VARIATION, double_bass: true, cymbal_style: None }
+    }
+}
+
+impl DrumPatternOptions {
+    /// Cymbal part for a section, honouring an explicit style
+    pub fn cymbal_style_for(&self, section: MetalSection) -> CymbalStyle {
+        self.cymbal_style.unwrap_or_else(|| CymbalStyle::for_section(section))
+    }
+
+    /// Rests per bar in the double-bass run, or `None` when the section uses the Euclidean kick
+    fn double_bass_gaps(&self, subgenre: MetalSubgenre, section: MetalSection) -> Option<usize> {
+        if !self.double_bass || section != MetalSection::Verse {
+            return None;
+        }
+        match subgenre {
+            MetalSubgenre::DeathMetal => Some(1),
+            MetalSubgenre::ThrashMetal => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// Drum grid for `duration` seconds of `section` at `tempo`, in the given feel (Tempo Decoupling).
+/// Kicks lock to the chugs in `guitar`; only blasts draw from the song RNG.
+pub fn generate_drum_grid(
+    section: MetalSection,
+    duration: f32,
+    tempo: u16,
+    subgenre: MetalSubgenre,
+    feel: RhythmicFeel,
+    guitar: &GuitarContext,
+    options: &DrumPatternOptions,
+) -> DrumGrid {
+    let beat_duration = 60.0 / tempo as f32;
+    let sixteenth_duration = beat_duration / 4.0;
+
+    let steps = (duration / sixteenth_duration).ceil() as usize;
+
+    let mut kick = vec![false; steps];
+    let mut snare = vec![false; steps];
+    let mut cymbal = vec![false; steps];
+    let mut accent: Vec<bool> = (0..steps).map(|i| i % 16 == 0).collect();
+
+    match feel {
+        RhythmicFeel::HalfTime => {
+            // HALF TIME LOGIC (Breakdowns)
+            // Snare on beat 3 (Step 8 in a 0-15 grid)
+            // Kick is sparse, Euclidean pulses reduced
+            let pulses = 3;
+            kick = rhythm_generator::generate_euclidean_pattern(steps, pulses);
+
+            for i in 0..steps {
+                // Snare on beat 3
+                if feel.is_backbeat(i) {
+                    snare[i] = true;
+                    kick[i] = false; // Don't kick on snare
+                }
+                // China/Crash on beat 1
+                if i % 16 == 0 { cymbal[i] = true; kick[i] = true; }
+            }
+        },
+        RhythmicFeel::DoubleTime => {
+            // SKANK BEAT
+            // Kick on every beat, snare on every "and", cymbal riding the eighths
+            for i in 0..steps {
+                if i % 4 == 0 { kick[i] = true; }
+                if feel.is_backbeat(i) { snare[i] = true; }
+                if i % 2 == 0 { cymbal[i] = true; }
+            }
+        },
+        RhythmicFeel::Blast => {
+            // BLAST LOGIC
+            // Every 2nd step (8th note at high tempo), varied bar to bar
+            let blast = generate_varied_blast(steps.div_ceil(16), 8, options.blast_variation);
+            for i in 0..steps {
+                accent[i] = feel.is_backbeat(i) && blast.accents[i / 2];
+                if feel.is_backbeat(i) {
+                    kick[i] = blast.kicks[i / 2];
+                    snare[i] = blast.snares[i / 2];
+                    cymbal[i] = true;
+                }
+            }
+        },
+        RhythmicFeel::Normal => {
+            // STANDARD METAL
+            // Snare on 2 and 4 (Steps 4 and 12)
+            let double_bass = options.double_bass_gaps(subgenre, section);
+            let pulses = if matches!(subgenre, MetalSubgenre::ProgressiveMetal) { 7 } else { 5 };
+            kick = match double_bass {
+                Some(gaps) => rhythm_generator::generate_double_bass_pattern(steps, gaps),
+                None => rhythm_generator::generate_euclidean_pattern(steps, pulses),
+            };
+
+            for i in 0..steps {
+                if feel.is_backbeat(i) {
+                    snare[i] = true;
+                    // Clear kick for snare (double bass keeps rolling underneath)
+                    if double_bass.is_none() {
+                        kick[i] = false;
+                    }
+                }
+                // Crash and kick on beat 1 of each bar
+                if i % 16 == 0 {
+                    cymbal[i] = true;
+                    kick[i] = true;
+                }
+            }
+        },
+    }
+
+    // Time-keeping cymbal between the crashes (blasts already ride every eighth)
+    if !matches!(feel, RhythmicFeel::Blast | RhythmicFeel::DoubleTime) {
+        let interval = options.cymbal_style_for(section).density.step_interval();
+        for hit in cymbal.iter_mut().step_by(interval) {
+            *hit = true;
+        }
+    }
+
+    if section == MetalSection::PreChorus {
+        add_build_up(&mut kick, &mut snare);
+    }
+
+    // Rhythmic unison: kick under every guitar chug (blasts and skank beats keep their own pulse)
+    if !matches!(feel, RhythmicFeel::Blast | RhythmicFeel::DoubleTime) {
+        let unison = PhraseAwareDrumGenerator::new(get_sample_rate(), tempo).kick_unison(guitar, steps);
+        for (kick_hit, accent) in kick.iter_mut().zip(unison) {
+            *kick_hit |= accent;
+        }
+    }
+
+    DrumGrid { kick, snare, cymbal, accent }
+}
+
+/// Snare-and-kick accelerando over the back half of a build-up: quarters, then eighths,
+/// then a sixteenth-note roll through the last bar
+fn add_build_up(kick: &mut [bool], snare: &mut [bool]) {
+    let steps = snare.len();
+    let last_bar = steps.saturating_sub(16);
+    for (i, (kick_hit, snare_hit)) in kick.iter_mut().zip(snare.iter_mut()).enumerate() {
+        let interval = if i >= last_bar {
+            1
+        } else if i * 4 >= steps * 3 {
+            2
+        } else if i * 2 >= steps {
+            4
+        } else {
+            continue;
+        };
+        if i.is_multiple_of(interval) {
+            *snare_hit = true;
+            *kick_hit = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_grids_outside_blasts_leave_the_rng_alone() {
+        let guitar = GuitarContext::default();
+        let options = DrumPatternOptions::default();
+        crate::utils::seed_rng(11);
+        let before: u64 = crate::utils::rng().gen();
+
+        crate::utils::seed_rng(11);
+        for section in [MetalSection::Verse, MetalSection::PreChorus, MetalSection::Breakdown] {
+            let feel = section.rhythmic_feel_for(MetalSubgenre::ThrashMetal);
+            let grid = generate_drum_grid(section, 8.0, 120, MetalSubgenre::ThrashMetal, feel, &guitar, &options);
+            assert_eq!(grid.bars(), 4);
+            assert_eq!(grid.hit_steps(&grid.accent), vec![0, 16, 32, 48]);
+        }
+        let after: u64 = crate::utils::rng().gen();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_chart_marks_hits_accents_and_bars() {
+        let mut grid = DrumGrid {
+            kick: vec![false; 20],
+            snare: vec![false; 20],
+            cymbal: vec![false; 20],
+            accent: vec![false; 20],
+        };
+        grid.kick[0] = true;
+        grid.accent[0] = true;
+        grid.snare[4] = true;
+        grid.cymbal[16] = true;
+
+        let chart = grid.chart();
+        let rows: Vec<&str> = chart.lines().collect();
+        assert_eq!(rows, vec![
+            "   Cymbal |................|x...|",
+            "   Snare  |....x...........|....|",
+            "   Kick   |X...............|....|",
+        ]);
+    }
+}
@@ -94,6 +94,9 @@ impl Ord for PathNode {
     }
 }
 
+/// Widest fret span allowed across a sweep shape
+const SWEEP_MAX_SPAN: u8 = 5;
+
 /// Fretboard pathfinding for playable riff generation
 /// Based on research: ensures riffs are biomechanically feasible
 pub struct FretboardPathfinder {
@@ -178,20 +181,48 @@ impl FretboardPathfinder {
         positions
     }
 
+    /// Move a note by octaves into the fretboard's range: notes below the lowest open
+    /// string come up, notes above the highest fret come down. Playable notes are unchanged.
+    pub fn playable_note(&self, note: MidiNote) -> MidiNote {
+        let string_notes = self.tuning.string_notes();
+        let lowest = string_notes.iter().copied().min().unwrap_or(0);
+        let highest = string_notes.iter().copied().max().unwrap_or(0).saturating_add(self.max_fret);
+
+        let mut note = note;
+        while note < lowest {
+            match note.checked_add(12) {
+                Some(raised) => note = raised,
+                None => break,
+            }
+        }
+        while note > highest && note - 12 >= lowest {
+            note -= 12;
+        }
+        note
+    }
+
+    /// Octave-shift every note into range so each one gets a fret position
+    pub fn fit_to_range(&self, notes: &[MidiNote]) -> Vec<MidiNote> {
+        notes.iter().map(|&note| self.playable_note(note)).collect()
+    }
+
+    /// Positions for a note after shifting it into range (never empty)
+    fn positions_in_range(&self, note: MidiNote) -> Vec<FretPosition> {
+        self.get_positions_for_note(self.playable_note(note))
+    }
+
     /// Find the most playable path through a sequence of notes using A* algorithm
-    /// Returns the optimal fret positions for each note
+    /// Returns the optimal fret positions for each note (unplayable notes are
+    /// transposed by octaves, so the path always has one position per note)
     pub fn find_playable_path(&self, notes: &[MidiNote]) -> Vec<FretPosition> {
-        if notes.is_empty() {
+        if notes.is_empty() || self.tuning.validate().is_err() {
             return Vec::new();
         }
 
         let mut path = Vec::with_capacity(notes.len());
         
         // Start with the lowest position for the first note (most comfortable)
-        let first_positions = self.get_positions_for_note(notes[0]);
-        if first_positions.is_empty() {
-            return Vec::new(); // Note not playable on this tuning
-        }
+        let first_positions = self.positions_in_range(notes[0]);
         
         // Choose starting position (prefer lower frets, lower strings for metal)
         let mut current_pos = first_positions.iter()
@@ -203,10 +234,7 @@ impl FretboardPathfinder {
 
         // For each subsequent note, find the best position
         for &note in &notes[1..] {
-            let positions = self.get_positions_for_note(note);
-            if positions.is_empty() {
-                continue; // Skip unplayable notes
-            }
+            let positions = self.positions_in_range(note);
 
             // Filter positions based on mode's max stretch
             let max_stretch = self.mode.max_stretch();
@@ -243,10 +271,51 @@ impl FretboardPathfinder {
         path
     }
 
+    /// Lay a sweep arpeggio out one note per string: each note moves to the adjacent
+    /// string in the direction of the pitch change. Picks the start with the smallest
+    /// fretting-hand span; None if the run can't be swept on this tuning.
+    pub fn find_sweep_path(&self, notes: &[MidiNote]) -> Option<Vec<FretPosition>> {
+        let string_notes = self.tuning.string_notes();
+        let mut best: Option<(u8, Vec<FretPosition>)> = None;
+
+        for start in self.get_positions_for_note(*notes.first()?) {
+            let mut path = vec![start];
+            for pair in notes.windows(2) {
+                let current = path[path.len() - 1];
+                let next_string = match pair[1].cmp(&pair[0]) {
+                    Ordering::Greater => current.string as usize + 1,
+                    Ordering::Less if current.string > 0 => current.string as usize - 1,
+                    _ => break, // Repeated note or off the low string
+                };
+                match string_notes.get(next_string) {
+                    Some(&open) if pair[1] >= open && pair[1] - open <= self.max_fret => {
+                        path.push(FretPosition::new(next_string as u8, pair[1] - open));
+                    }
+                    _ => break,
+                }
+            }
+            if path.len() < notes.len() {
+                continue;
+            }
+
+            // Span of the fretting hand (open strings need no finger)
+            let fretted = path.iter().map(|p| p.fret).filter(|&f| f > 0);
+            let span = match (fretted.clone().min(), fretted.max()) {
+                (Some(low), Some(high)) => high - low,
+                _ => 0,
+            };
+            if span <= SWEEP_MAX_SPAN && best.as_ref().is_none_or(|(best_span, _)| span < *best_span) {
+                best = Some((span, path));
+            }
+        }
+
+        best.map(|(_, path)| path)
+    }
+
     /// Find an aggressive path that prioritizes melodic contour over comfort
     /// Allows spicy stretches for better note flow
     pub fn find_aggressive_path(&self, notes: &[MidiNote]) -> Vec<FretPosition> {
-        if notes.is_empty() {
+        if notes.is_empty() || self.tuning.validate().is_err() {
             return Vec::new();
         }
 
@@ -254,10 +323,7 @@ impl FretboardPathfinder {
         let melodic_weight = self.mode.melodic_weight();
         
         // For aggressive mode, prefer higher positions for better sustain
-        let first_positions = self.get_positions_for_note(notes[0]);
-        if first_positions.is_empty() {
-            return Vec::new();
-        }
+        let first_positions = self.positions_in_range(notes[0]);
         
         // In aggressive mode, prefer mid-range frets (5-12) for better tone
         let mut current_pos = first_positions.iter()
@@ -276,10 +342,7 @@ impl FretboardPathfinder {
 
         // For each subsequent note, prioritize melodic flow
         for (i, &note) in notes[1..].iter().enumerate() {
-            let positions = self.get_positions_for_note(note);
-            if positions.is_empty() {
-                continue;
-            }
+            let positions = self.positions_in_range(note);
 
             // Calculate melodic direction (ascending/descending)
             let prev_note = notes[i];
@@ -338,10 +401,10 @@ impl FretboardPathfinder {
 
     /// Check if a sequence of notes is playable (total cost below threshold)
     pub fn is_playable(&self, notes: &[MidiNote], max_total_cost: f32) -> bool {
-        let path = self.find_playable_path(notes);
-        if path.len() != notes.len() {
-            return false; // Some notes couldn't be played
+        if notes.iter().any(|&note| self.get_positions_for_note(note).is_empty()) {
+            return false; // Some notes need transposing to be played
         }
+        let path = self.find_playable_path(notes);
 
         let total_cost: f32 = path.windows(2)
             .map(|window| window[0].movement_cost(&window[1]))
@@ -351,7 +414,7 @@ impl FretboardPathfinder {
     }
 
     /// Optimize a riff for playability by adjusting notes if needed
-    /// Returns (optimized_notes, fret_positions)
+    /// Returns (optimized_notes, fret_positions); out-of-range notes come back octave-shifted
     pub fn optimize_riff(&self, notes: &[MidiNote]) -> (Vec<MidiNote>, Vec<FretPosition>) {
         let path = self.find_playable_path(notes);
         
@@ -370,21 +433,20 @@ impl FretboardPathfinder {
     /// chaos: 0.0 = normal, 1.0 = maximum chaos
     pub fn find_spiteful_path(&self, notes: &[MidiNote], chaos: f32) -> Vec<FretPosition> {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
-        if notes.is_empty() {
+        if notes.is_empty() || self.tuning.validate().is_err() {
             return Vec::new();
         }
 
+        let first_positions = self.positions_in_range(notes[0]);
+
         let mut path = Vec::with_capacity(notes.len());
-        let mut current_pos = self.get_positions_for_note(notes[0])[0];
+        let mut current_pos = first_positions[0];
         path.push(current_pos);
 
         for &note in &notes[1..] {
-            let positions = self.get_positions_for_note(note);
-            if positions.is_empty() {
-                continue;
-            }
+            let positions = self.positions_in_range(note);
 
             // CHAOS: 20% chance of sudden large jump (>5 frets)
             if rng.gen_bool((chaos * 0.2) as f64) {
@@ -470,15 +532,20 @@ impl FretboardPathfinder {
     }
 }
 
+/// Sum of hand movement costs along a path of fret positions
+pub fn total_movement_cost(positions: &[FretPosition]) -> f32 {
+    positions.windows(2)
+        .map(|window| window[0].movement_cost(&window[1]))
+        .sum()
+}
+
 /// Helper function to calculate playability score (0.0 = impossible, 1.0 = very easy)
 pub fn calculate_playability_score(positions: &[FretPosition]) -> f32 {
     if positions.len() < 2 {
         return 1.0;
     }
 
-    let total_cost: f32 = positions.windows(2)
-        .map(|window| window[0].movement_cost(&window[1]))
-        .sum();
+    let total_cost = total_movement_cost(positions);
 
     let max_possible_cost = (positions.len() - 1) as f32 * 15.0; // Worst case scenario
     let normalized_cost = total_cost / max_possible_cost;
@@ -490,6 +557,39 @@ pub fn calculate_playability_score(positions: &[FretPosition]) -> f32 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_invalid_custom_tunings_give_empty_paths() {
+        let notes = vec![40, 43, 45];
+        for strings in [vec![], vec![250]] {
+            let pathfinder = FretboardPathfinder::new(GuitarTuning::Custom(strings));
+            assert!(pathfinder.find_playable_path(&notes).is_empty());
+            assert!(pathfinder.find_aggressive_path(&notes).is_empty());
+            assert!(pathfinder.find_spiteful_path(&notes, 0.5).is_empty());
+        }
+
+        // Raising a note toward a very high open string stops short of overflowing
+        let high = FretboardPathfinder::new(GuitarTuning::Custom(vec![250]));
+        assert_eq!(high.playable_note(5), 245);
+    }
+
+    #[test]
+    fn test_sweep_path_uses_adjacent_strings() {
+        let pathfinder = FretboardPathfinder::new(GuitarTuning::EStandard);
+        let sweep = vec![45, 48, 52, 57, 60, 64, 60, 57, 52, 48, 45]; // A minor, up and back
+
+        let path = pathfinder.find_sweep_path(&sweep).expect("A minor sweep fits E standard");
+        assert_eq!(path.len(), sweep.len());
+        for pair in path.windows(2) {
+            assert_eq!((pair[0].string as i16 - pair[1].string as i16).abs(), 1, "{:?}", path);
+        }
+
+        // Every position still sounds the right pitch
+        let string_notes = GuitarTuning::EStandard.string_notes();
+        for (pos, &note) in path.iter().zip(&sweep) {
+            assert_eq!(string_notes[pos.string as usize] + pos.fret, note);
+        }
+    }
+
     #[test]
     fn test_fret_position_creation() {
         let pos = FretPosition::new(2, 5);
@@ -575,6 +675,28 @@ mod tests {
         assert_eq!(positions.len(), notes.len());
     }
 
+    #[test]
+    fn test_unplayable_notes_are_transposed_into_range() {
+        let pathfinder = FretboardPathfinder::with_mode(GuitarTuning::EStandard, PlayabilityMode::Aggressive);
+        // 30 sits below the low E (40); 100 is above the 24th fret of the high E
+        let notes = vec![40, 30, 43, 100, 45];
+        let expected = vec![40, 42, 43, 88, 45];
+        assert_eq!(pathfinder.fit_to_range(&notes), expected);
+
+        let string_notes = GuitarTuning::EStandard.string_notes();
+        for path in [
+            pathfinder.find_playable_path(&notes),
+            pathfinder.find_aggressive_path(&notes),
+            pathfinder.find_spiteful_path(&notes, 1.0),
+        ] {
+            assert_eq!(path.len(), notes.len());
+            for (pos, &note) in path.iter().zip(&expected) {
+                assert_eq!(string_notes[pos.string as usize] + pos.fret, note);
+            }
+        }
+        assert!(!pathfinder.is_playable(&notes, f32::MAX));
+    }
+
     #[test]
     fn test_playability_score() {
         // Easy path (small movements)
@@ -611,4 +733,16 @@ mod tests {
         // Should be playable (metal riffs are designed for playability)
         assert!(score > 0.6);
     }
+
+    #[test]
+    fn test_spiteful_path_costs_more_at_high_chaos() {
+        let pathfinder = FretboardPathfinder::with_mode(GuitarTuning::DropC, PlayabilityMode::Aggressive);
+        let riff: Vec<MidiNote> = [36, 43, 48, 46, 51, 43, 55, 41].repeat(16);
+
+        let standard_cost = total_movement_cost(&FretboardPathfinder::new(GuitarTuning::DropC).find_playable_path(&riff));
+        let spiteful_cost = total_movement_cost(&pathfinder.find_spiteful_path(&riff, 1.0));
+
+        assert!(spiteful_cost > standard_cost,
+            "spiteful {} should exceed standard {}", spiteful_cost, standard_cost);
+    }
 }
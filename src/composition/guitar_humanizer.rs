@@ -0,0 +1,121 @@
+use crate::composition::metal_song_generator::MetalSubgenre;
+use rand::Rng;
+
+/// Guitar humanization: a rhythm player never lands every pick exactly on the grid
+/// or lets every note ring for exactly its full value. Times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuitarHumanizer {
+    /// Onset randomization range (±)
+    pub timing_variance: f32,
+    /// Onset bias (positive = drag/late, negative = rush/early)
+    pub timing_bias: f32,
+    /// Average share of each note's value that actually rings (1.0 = legato, lower = staccato)
+    pub articulation: f32,
+    /// Randomization of that share (±)
+    pub length_variance: f32,
+}
+
+impl GuitarHumanizer {
+    /// Create a new guitar humanizer with default settings
+    pub fn new() -> Self {
+        GuitarHumanizer {
+            timing_variance: 0.004, // ±4ms
+            timing_bias: 0.0,       // On the grid
+            articulation: 0.95,
+            length_variance: 0.04,
+        }
+    }
+
+    /// No humanization: every note on the grid at full length
+    pub fn quantized() -> Self {
+        GuitarHumanizer {
+            timing_variance: 0.0,
+            timing_bias: 0.0,
+            articulation: 1.0,
+            length_variance: 0.0,
+        }
+    }
+
+    /// Preset for thrash/death picking (very tight, clipped, slightly rushed)
+    pub fn tight() -> Self {
+        GuitarHumanizer {
+            timing_variance: 0.0015,
+            timing_bias: -0.001,
+            articulation: 0.9,
+            length_variance: 0.02,
+        }
+    }
+
+    /// Preset for doom (loose, dragged, notes left to ring)
+    pub fn loose() -> Self {
+        GuitarHumanizer {
+            timing_variance: 0.01,
+            timing_bias: 0.005,
+            articulation: 1.0,
+            length_variance: 0.08,
+        }
+    }
+
+    /// Per-subgenre feel
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        match subgenre {
+            MetalSubgenre::ThrashMetal | MetalSubgenre::DeathMetal => Self::tight(),
+            MetalSubgenre::DoomMetal => Self::loose(),
+            MetalSubgenre::HeavyMetal | MetalSubgenre::ProgressiveMetal => Self::new(),
+        }
+    }
+
+    /// Humanize an onset: offset from the grid in seconds
+    pub fn humanize_timing(&self) -> f32 {
+        if self.timing_variance <= 0.0 {
+            return self.timing_bias;
+        }
+        let mut rng = crate::utils::rng();
+        self.timing_bias + rng.gen_range(-self.timing_variance..=self.timing_variance)
+    }
+
+    /// Humanize a note length: how much of `duration` (seconds) rings
+    pub fn humanize_length(&self, duration: f32) -> f32 {
+        let share = if self.length_variance <= 0.0 {
+            self.articulation
+        } else {
+            let mut rng = crate::utils::rng();
+            self.articulation + rng.gen_range(-self.length_variance..=self.length_variance)
+        };
+        duration * share.clamp(0.5, 1.0)
+    }
+}
+
+impl Default for GuitarHumanizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guitar_humanizer_presets() {
+        let thrash = GuitarHumanizer::for_subgenre(MetalSubgenre::ThrashMetal);
+        let doom = GuitarHumanizer::for_subgenre(MetalSubgenre::DoomMetal);
+        assert!(thrash.timing_variance < doom.timing_variance);
+        assert!(thrash.timing_bias < 0.0 && doom.timing_bias > 0.0);
+
+        for _ in 0..100 {
+            let length = doom.humanize_length(0.2);
+            assert!((0.1..=0.2).contains(&length));
+            assert!(thrash.humanize_timing().abs() <= 0.0025 + 1e-6);
+        }
+
+        // Two identical notes don't ring for exactly the same time
+        crate::utils::seed_rng(3);
+        let humanizer = GuitarHumanizer::new();
+        assert_ne!(humanizer.humanize_length(0.2), humanizer.humanize_length(0.2));
+
+        let quantized = GuitarHumanizer::quantized();
+        assert_eq!(quantized.humanize_length(0.2), 0.2);
+        assert_eq!(quantized.humanize_timing(), 0.0);
+    }
+}
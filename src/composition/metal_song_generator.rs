@@ -1,18 +1,84 @@
 use crate::composition::{
-    drum_humanizer::{DrumHumanizer, BlastBeatStyle, generate_blast_beat, blast_beat_velocity},
-    fretboard::{FretboardPathfinder, PlayabilityMode, calculate_playability_score},
-    music_theory::{Key, ScaleType, MidiNote},
+    drum_humanizer::{DrumHumanizer, generate_varied_blast, blast_beat_velocity, DEFAULT_BLAST_VARIATION},
+    fretboard::{FretboardPathfinder, FretPosition, PlayabilityMode, calculate_playability_score},
+    music_theory::{note_name, Chord, ChordType as ArpeggioChord, Interval, Key, ScaleType, MidiNote},
     tuning::GuitarTuning,
     rhythm::{euclidean_rhythm, rotate_rhythm, OddSubdivisionPattern, DisplacedAccentGenerator, PolymetricInterference},
-    riff_generator::{MetalMarkovPresets, PedalPointGenerator, ChromaticMutator},
+    riff_generator::{generate_sweep_arpeggio, MetalMarkovPresets, PedalPointGenerator, ChromaticMutator, SweepDirection},
     riff_motifs::{RiffMotif, MotifLibrary, MotifRecombinator},
     drum_articulations::DrumArticulationGenerator,
     breakdown_generator::{BreakdownGenerator, BreakdownPattern},
     bar_memory::BarMotifStore,
     phrase_drums::{PhraseAwareDrumGenerator, GuitarContext},
+    drum_patterns::{generate_drum_grid, DrumGrid, DrumPatternOptions},
 };
 use crate::synthesis::aggressive_mix::AggressiveMixPipeline;
+use crate::utils::RenderContext;
 use rand::Rng;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// Riff notes per line of tab in `MetalSong::describe`
+const TAB_NOTES_PER_LINE: usize = 16;
+
+/// How many times a riff is regenerated when it falls below `min_playability`
+const MAX_PLAYABILITY_ATTEMPTS: usize = 5;
+
+/// A riff needs at least this share of its notes picked (not rests) to count as audible
+const MIN_AUDIBLE_SHARE: f32 = 0.25;
+
+/// How many times a riff that is mostly rests is regenerated before its rests are filled in
+const MAX_SILENT_RIFF_ATTEMPTS: usize = 5;
+
+/// Above this chaos level, fret positions come from the spiteful pathfinder
+const SPITEFUL_CHAOS_THRESHOLD: f32 = 0.6;
+
+/// Tempo range a song can be given (BPM)
+pub const MIN_TEMPO: u16 = 40;
+pub const MAX_TEMPO: u16 = 320;
+
+/// Above this chaos level, polymetric riffs take meter drops, overlaps and chaos bars
+const CHAOTIC_METER_THRESHOLD: f32 = 0.65;
+
+/// Section order used by `generate_song` and `generate_song_with_memory`
+const STANDARD_LAYOUT: [MetalSection; 14] = [
+    MetalSection::Intro,
+    MetalSection::Verse,
+    MetalSection::PreChorus,
+    MetalSection::Chorus,
+    MetalSection::Verse,
+    MetalSection::PreChorus,
+    MetalSection::Chorus,
+    MetalSection::Verse,
+    MetalSection::Breakdown,
+    MetalSection::Solo,
+    MetalSection::Chorus,
+    MetalSection::Breakdown,
+    MetalSection::Chorus,
+    MetalSection::Outro,
+];
+
+/// Notes this far above the root count as high register (tremolo candidates)
+const TREMOLO_REGISTER_OFFSET: MidiNote = 7;
+
+/// Notes at the end of a remembered riff that are varied when its section repeats (one bar)
+const MOTIF_VARIATION_NOTES: usize = 8;
+
+/// Sweeps are picked lightly so the arpeggio stays clean
+const SWEEP_VELOCITY: f32 = 0.7;
+
+/// Length of the verse's opening cell that the chorus is developed from
+const CHORUS_CELL_NOTES: usize = 4;
+
+/// Clean intro progression as scale degrees (i - VI - iv - v in a minor key), one chord per half bar
+const INTRO_ARPEGGIO_DEGREES: [usize; 4] = [0, 5, 3, 4];
+
+/// Order the chord tones are picked in: root, fifth, octave, third
+const INTRO_ARPEGGIO_PATTERN: [usize; 4] = [0, 2, 3, 1];
+
+/// Default playability threshold (0.0 = accept anything, 1.0 = only trivial riffs)
+pub const DEFAULT_MIN_PLAYABILITY: f32 = 0.5;
 
 /// Legacy genre enum for compatibility
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,7 +87,7 @@ pub enum Genre {
 }
 
 /// Defines the rhythmic feel of the drums relative to the tempo
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum RhythmicFeel {
     HalfTime,    // Drums feel like tempo is 50% (Breakdowns, Sludge)
     Normal,      // Standard 4/4
@@ -29,11 +95,35 @@ pub enum RhythmicFeel {
     Blast,       // Maximum density
 }
 
+impl RhythmicFeel {
+    /// 16th-note steps between snare hits: HalfTime halves the Normal backbeat rate,
+    /// DoubleTime doubles it and Blast hits every eighth
+    pub fn backbeat_interval(&self) -> usize {
+        match self {
+            RhythmicFeel::HalfTime => 16, // Beat 3
+            RhythmicFeel::Normal => 8,    // Beats 2 and 4
+            RhythmicFeel::DoubleTime => 4, // Every "and" (skank beat)
+            RhythmicFeel::Blast => 2,
+        }
+    }
+
+    /// Whether the snare plays on this step of the 16th grid (blasts hit in unison
+    /// with the kick, every other feel halfway between its downbeats)
+    pub fn is_backbeat(&self, step: usize) -> bool {
+        let interval = self.backbeat_interval();
+        match self {
+            RhythmicFeel::Blast => step.is_multiple_of(interval),
+            _ => step % interval == interval / 2,
+        }
+    }
+}
+
 /// Metal song structure sections
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum MetalSection {
     Intro,
     Verse,
+    PreChorus, // Build-up that leads into the chorus
     Chorus,
     Breakdown,
     Solo,
@@ -42,7 +132,7 @@ pub enum MetalSection {
 
 /// Intensity level for song sections
 /// Used to vary riff generation and mixing
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum SectionIntensity {
     Low,      // Intro, build-up
     Medium,   // Verse
@@ -51,11 +141,43 @@ pub enum SectionIntensity {
 }
 
 impl MetalSection {
+    /// Snake-case name used for config keys (e.g. "pre_chorus")
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            MetalSection::Intro => "intro",
+            MetalSection::Verse => "verse",
+            MetalSection::PreChorus => "pre_chorus",
+            MetalSection::Chorus => "chorus",
+            MetalSection::Breakdown => "breakdown",
+            MetalSection::Solo => "solo",
+            MetalSection::Outro => "outro",
+        }
+    }
+
+    /// Length of the section in 4/4 bars when the song is rendered
+    pub fn default_bars(&self) -> usize {
+        match self {
+            MetalSection::Intro => 4,
+            MetalSection::Verse => 8,
+            MetalSection::PreChorus => 4,
+            MetalSection::Chorus => 8,
+            MetalSection::Breakdown => 4,
+            MetalSection::Solo => 12,
+            MetalSection::Outro => 4,
+        }
+    }
+
+    /// Seconds the section lasts at `tempo` (`default_bars` of 4/4)
+    pub fn default_duration(&self, tempo: u16) -> f32 {
+        self.default_bars() as f32 * 4.0 * 60.0 / tempo.max(1) as f32
+    }
+
     /// Get the intensity level for this section
     pub fn intensity(&self) -> SectionIntensity {
         match self {
             MetalSection::Intro => SectionIntensity::Low,
             MetalSection::Verse => SectionIntensity::Medium,
+            MetalSection::PreChorus => SectionIntensity::High, // Rises out of the verse
             MetalSection::Chorus => SectionIntensity::High,
             MetalSection::Breakdown => SectionIntensity::Extreme,
             MetalSection::Solo => SectionIntensity::High,
@@ -67,16 +189,45 @@ impl MetalSection {
     pub fn rhythmic_feel(&self) -> RhythmicFeel {
         match self {
             MetalSection::Intro => RhythmicFeel::Normal,
-            MetalSection::Verse => RhythmicFeel::Normal, // Could be Blast for Death Metal logic
+            MetalSection::Verse => RhythmicFeel::Normal, // Death metal blasts (see rhythmic_feel_for)
+            MetalSection::PreChorus => RhythmicFeel::Normal, // Accelerando comes from the snare build
             MetalSection::Chorus => RhythmicFeel::Normal,
             MetalSection::Breakdown => RhythmicFeel::HalfTime, // CRITICAL: Fixes DnB feel
             MetalSection::Solo => RhythmicFeel::Normal,
             MetalSection::Outro => RhythmicFeel::HalfTime,
         }
     }
+
+    /// Rhythmic feel in a given subgenre: death metal verses blast
+    pub fn rhythmic_feel_for(&self, subgenre: MetalSubgenre) -> RhythmicFeel {
+        match (subgenre, self) {
+            (MetalSubgenre::DeathMetal, MetalSection::Verse) => RhythmicFeel::Blast,
+            _ => self.rhythmic_feel(),
+        }
+    }
 }
 
 impl RhythmPattern {
+    /// The same figure at double the note length (straight values only)
+    pub fn augmented(&self) -> Self {
+        match self {
+            RhythmPattern::ThirtySecondNote => RhythmPattern::SixteenthNote,
+            RhythmPattern::SixteenthNote => RhythmPattern::EighthNote,
+            RhythmPattern::EighthNote => RhythmPattern::QuarterNote,
+            other => *other,
+        }
+    }
+
+    /// Straight note value spanning `steps` sixteenths (capped at a quarter note)
+    pub fn from_sixteenths(steps: usize) -> Self {
+        match steps {
+            0 | 1 => RhythmPattern::SixteenthNote,
+            2 => RhythmPattern::EighthNote,
+            3 => RhythmPattern::DottedEighth,
+            _ => RhythmPattern::QuarterNote,
+        }
+    }
+
     /// Convert rhythm pattern to duration in beats
     pub fn to_beats(&self) -> f32 {
         match self {
@@ -85,13 +236,64 @@ impl RhythmPattern {
             RhythmPattern::SixteenthNote => 0.25,
             RhythmPattern::ThirtySecondNote => 0.125,
             RhythmPattern::Gallop => 0.5, // Gallop is a compound pattern
-            RhythmPattern::Quintuplet => 0.8, // 5 notes in 4 beats = 4/5 per note
-            RhythmPattern::Septuplet => 0.571, // 7 notes in 4 beats = 4/7 per note
+            RhythmPattern::Quintuplet | RhythmPattern::Septuplet => {
+                TUPLET_SPAN_BEATS / self.tuplet_size().unwrap_or(1) as f32 // 4/5 or 4/7 per note
+            },
             RhythmPattern::DottedEighth => 0.75, // 3/16 of a bar
+            RhythmPattern::Tremolo { .. } => 1.0, // One sustained beat
             RhythmPattern::Rest => 0.0,
         }
     }
 
+    /// Variant name without parameters (histogram/metadata key)
+    pub fn name(&self) -> &'static str {
+        match self {
+            RhythmPattern::QuarterNote => "QuarterNote",
+            RhythmPattern::EighthNote => "EighthNote",
+            RhythmPattern::SixteenthNote => "SixteenthNote",
+            RhythmPattern::ThirtySecondNote => "ThirtySecondNote",
+            RhythmPattern::Gallop => "Gallop",
+            RhythmPattern::Quintuplet => "Quintuplet",
+            RhythmPattern::Septuplet => "Septuplet",
+            RhythmPattern::DottedEighth => "DottedEighth",
+            RhythmPattern::Tremolo { .. } => "Tremolo",
+            RhythmPattern::Rest => "Rest",
+        }
+    }
+
+    /// One-character symbol for the plan printout (`-` for rests)
+    pub fn symbol(&self) -> char {
+        match self {
+            RhythmPattern::QuarterNote => 'Q',
+            RhythmPattern::EighthNote => 'E',
+            RhythmPattern::SixteenthNote => 'S',
+            RhythmPattern::ThirtySecondNote => 'T',
+            RhythmPattern::Gallop => 'G',
+            RhythmPattern::Quintuplet => '5',
+            RhythmPattern::Septuplet => '7',
+            RhythmPattern::DottedEighth => 'D',
+            RhythmPattern::Tremolo { .. } => '~',
+            RhythmPattern::Rest => '-',
+        }
+    }
+
+    /// Notes in one tuplet group (5 or 7), `None` for straight values
+    pub fn tuplet_size(&self) -> Option<usize> {
+        match self {
+            RhythmPattern::Quintuplet => Some(5),
+            RhythmPattern::Septuplet => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Number of picks a tremolo note is expanded into over `duration` seconds
+    pub fn tremolo_picks(&self, duration: f32) -> usize {
+        match self {
+            RhythmPattern::Tremolo { rate_hz } => ((duration * *rate_hz as f32).round() as usize).max(1),
+            _ => 1,
+        }
+    }
+
     /// Get the number of notes in this pattern (for gallop)
     pub fn note_count(&self) -> usize {
         match self {
@@ -114,7 +316,7 @@ impl RhythmPattern {
 }
 
 /// Metal subgenre for style-specific generation
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum MetalSubgenre {
     HeavyMetal,     // Traditional heavy metal
     ThrashMetal,    // Fast, aggressive
@@ -124,6 +326,40 @@ pub enum MetalSubgenre {
 }
 
 impl MetalSubgenre {
+    /// Every subgenre, in menu order
+    pub const ALL: [MetalSubgenre; 5] = [
+        MetalSubgenre::HeavyMetal,
+        MetalSubgenre::ThrashMetal,
+        MetalSubgenre::DeathMetal,
+        MetalSubgenre::DoomMetal,
+        MetalSubgenre::ProgressiveMetal,
+    ];
+
+    /// Kebab-case name used on the command line (e.g. "death-metal")
+    pub fn cli_name(&self) -> &'static str {
+        match self {
+            MetalSubgenre::HeavyMetal => "heavy-metal",
+            MetalSubgenre::ThrashMetal => "thrash-metal",
+            MetalSubgenre::DeathMetal => "death-metal",
+            MetalSubgenre::DoomMetal => "doom-metal",
+            MetalSubgenre::ProgressiveMetal => "progressive-metal",
+        }
+    }
+
+    /// Parse a subgenre name ("death-metal", "death", "DeathMetal"), case-insensitive
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let normalized = normalized.strip_suffix("metal").unwrap_or(&normalized);
+
+        Self::ALL.into_iter().find(|subgenre| {
+            subgenre.cli_name().strip_suffix("-metal") == Some(normalized)
+        })
+    }
+
     /// Get appropriate guitar tuning for subgenre
     pub fn default_tuning(&self) -> GuitarTuning {
         match self {
@@ -146,6 +382,22 @@ impl MetalSubgenre {
         }
     }
 
+    /// How far the fretboard pathfinder may stretch for the melody
+    pub fn playability_mode(&self) -> PlayabilityMode {
+        match self {
+            MetalSubgenre::ProgressiveMetal | MetalSubgenre::DeathMetal => PlayabilityMode::Aggressive,
+            _ => PlayabilityMode::Standard,
+        }
+    }
+
+    /// Default shuffle on the 16th grid (0.0 = straight, 1.0 = full triplet swing)
+    pub fn default_swing(&self) -> f32 {
+        match self {
+            MetalSubgenre::DoomMetal => 0.25, // Slight lope under the slow riffs
+            _ => 0.0,
+        }
+    }
+
     /// Get tempo range for subgenre (min, max BPM)
     pub fn tempo_range(&self) -> (u16, u16) {
         match self {
@@ -159,17 +411,35 @@ impl MetalSubgenre {
 }
 
 /// Type of chord to play
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum ChordType {
     Single,     // Single note
     Power,      // Root + 5th + Octave (Power Chord)
     Minor,      // Root + b3 + 5 (Minor Triad)
     Diminished, // Root + b3 + b5 (Diminished Triad)
     Octave,     // Root + Octave
+    MinorInversion(Inversion), // Minor triad with its third or fifth in the bass
+}
+
+/// Chord tone voiced lowest in an inverted triad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Inversion {
+    First,  // Third in the bass
+    Second, // Fifth in the bass
+}
+
+impl Inversion {
+    /// Semitones from the chord's root up to its lowest voice (minor triad)
+    pub fn bass_interval(&self) -> u8 {
+        match self {
+            Inversion::First => 3,
+            Inversion::Second => 7,
+        }
+    }
 }
 
 /// Rhythm patterns for metal riffs
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum RhythmPattern {
     QuarterNote,    // Whole beat
     EighthNote,     // Half beat
@@ -179,6 +449,7 @@ pub enum RhythmPattern {
     Quintuplet,     // 5 notes in 4 beats
     Septuplet,      // 7 notes in 4 beats
     DottedEighth,   // Dotted 8th note (3/16)
+    Tremolo { rate_hz: u16 }, // Held beat re-picked rapidly (black/death metal)
     Rest,           // Silence
 }
 
@@ -198,7 +469,103 @@ pub struct MetalRiff {
     pub chord_types: Vec<ChordType>,
     pub palm_muted: Vec<bool>,
     pub rhythms: Vec<RhythmPattern>, // New: rhythm patterns for each note
+    pub velocities: Vec<f32>, // Picking strength per note (0.0-1.0)
+    pub fret_positions: Vec<FretPosition>, // Fretboard realization chosen by the pathfinder
+    pub harmony_notes: Option<Vec<MidiNote>>, // Second lead voice (harmonized solos)
     pub playability_score: f32,
+    pub clean: bool, // Played on the clean channel (no distortion)
+}
+
+/// Beats a whole quintuplet or septuplet group spans
+pub const TUPLET_SPAN_BEATS: f32 = 4.0;
+
+/// Where the picks of a gallop land within its beat: eighth + two sixteenths
+pub const GALLOP_ONSETS: [f32; 3] = [0.0, 0.5, 0.75];
+
+impl MetalRiff {
+    /// Notes that are actually picked (everything but rests)
+    pub fn audible_notes(&self) -> usize {
+        (0..self.notes.len())
+            .filter(|&i| self.rhythms.get(i) != Some(&RhythmPattern::Rest))
+            .count()
+    }
+
+    /// ASCII tab of the riff, `TAB_NOTES_PER_LINE` notes per block: a rhythm row
+    /// (`RhythmPattern::symbol`), a palm-mute row and one row per string, high to low
+    pub fn tab(&self, tuning: &GuitarTuning) -> String {
+        let positions = if self.fret_positions.len() == self.notes.len() {
+            self.fret_positions.clone()
+        } else {
+            FretboardPathfinder::new(tuning.clone()).find_playable_path(&self.notes)
+        };
+        let strings = tuning.note_names();
+        let mut out = String::new();
+
+        for start in (0..self.notes.len()).step_by(TAB_NOTES_PER_LINE) {
+            let end = (start + TAB_NOTES_PER_LINE).min(self.notes.len());
+            let rhythm = |i: usize| self.rhythms.get(i).copied().unwrap_or(RhythmPattern::SixteenthNote);
+            let row = |cell: &dyn Fn(usize) -> String| (start..end).map(cell).collect::<String>();
+
+            let _ = writeln!(out, "   {:>2}  {}", "", row(&|i| format!("{:<3}", rhythm(i).symbol())).trim_end());
+            let _ = writeln!(out, "   {:>2}  {}", "PM", row(&|i| {
+                let muted = self.palm_muted.get(i).copied().unwrap_or(false) && rhythm(i) != RhythmPattern::Rest;
+                format!("{:<3}", if muted { "x" } else { "" })
+            }).trim_end());
+            for (string, name) in strings.iter().enumerate().rev() {
+                let _ = writeln!(out, "   {:>2} |{}|", name, row(&|i| match positions.get(i) {
+                    Some(pos) if pos.string as usize == string && rhythm(i) != RhythmPattern::Rest => {
+                        format!("{:-<3}", pos.fret)
+                    },
+                    _ => "---".to_string(),
+                }));
+            }
+        }
+        out
+    }
+
+    /// Whether enough notes are picked for the riff to be heard (`MIN_AUDIBLE_SHARE`,
+    /// and at least one)
+    pub fn is_audible(&self) -> bool {
+        let required = (self.notes.len() as f32 * MIN_AUDIBLE_SHARE).ceil().max(1.0) as usize;
+        self.audible_notes() >= required.min(self.notes.len())
+    }
+
+    /// Last resort for a riff that is mostly rests: turn rests into sixteenths,
+    /// downbeats first, until it's audible
+    fn fill_rests(&mut self) {
+        let mut rests: Vec<usize> = (0..self.notes.len().min(self.rhythms.len()))
+            .filter(|&i| self.rhythms[i] == RhythmPattern::Rest)
+            .collect();
+        rests.sort_by_key(|&i| !i.is_multiple_of(4));
+        for i in rests {
+            if self.is_audible() {
+                break;
+            }
+            self.rhythms[i] = RhythmPattern::SixteenthNote;
+        }
+    }
+
+    /// Every picked onset as (note index, position in beats on the straight grid).
+    /// Rests take a sixteenth and aren't picked; a gallop takes a whole beat and is
+    /// picked three times (`GALLOP_ONSETS`), just as the renderer plays it
+    pub fn onset_beats(&self) -> Vec<(usize, f32)> {
+        let mut onsets = Vec::with_capacity(self.notes.len());
+        let mut position = 0.0;
+        for i in 0..self.notes.len() {
+            match self.rhythms.get(i).copied().unwrap_or(RhythmPattern::SixteenthNote) {
+                RhythmPattern::Rest => position += 0.25,
+                RhythmPattern::Gallop => {
+                    onsets.extend(GALLOP_ONSETS.iter().map(|&offset| (i, position + offset)));
+                    position += 1.0;
+                },
+                rhythm => {
+                    onsets.push((i, position));
+                    position += rhythm.to_beats();
+                },
+            }
+        }
+        onsets
+    }
 }
 
 /// A complete metal song structure
@@ -212,6 +579,221 @@ pub struct MetalSong {
     pub drum_humanizer: DrumHumanizer,
 }
 
+impl MetalSong {
+    /// Text plan of the song (structure, then each section's drum chart, rhythm, palm
+    /// mutes and tab), for checking a seed without rendering any audio.
+    /// Blast sections draw their variations from the RNG, as rendering would.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:?} in {} {:?}, {} BPM, {} ({})",
+            self.subgenre,
+            note_name(self.key.root),
+            self.key.scale_type,
+            self.tempo,
+            self.tuning.display_name(),
+            self.tuning.note_names().join(" "),
+        );
+        let layout: Vec<String> = self.sections.iter().map(|(section, _)| format!("{:?}", section)).collect();
+        let _ = writeln!(out, "Structure: {}", layout.join(" > "));
+
+        for (i, (section, riff)) in self.sections.iter().enumerate() {
+            let feel = section.rhythmic_feel_for(self.subgenre);
+            let _ = writeln!(
+                out,
+                "\n{}. {:?} ({:?}, {:?} feel{}) - {} notes, playability {:.2}",
+                i + 1,
+                section,
+                section.intensity(),
+                feel,
+                if riff.clean { ", clean" } else { "" },
+                riff.notes.len(),
+                riff.playability_score,
+            );
+            out.push_str(&self.section_drum_grid(*section, Some(riff), self.subgenre).chart());
+            out.push_str(&riff.tab(&self.tuning));
+        }
+        out
+    }
+
+    /// Drum grid the renderer plays by default for `section` (`MetalSection::default_bars` long
+    /// at the song's tempo), with kicks locked to the song's riff for that section if it has one.
+    /// Nothing is rendered, and only blast sections draw from the RNG.
+    pub fn drum_patterns_for_section(&self, section: MetalSection, subgenre: MetalSubgenre) -> DrumGrid {
+        let riff = self.sections.iter().find(|(s, _)| *s == section).map(|(_, riff)| riff);
+        self.section_drum_grid(section, riff, subgenre)
+    }
+
+    /// Default drum grid for `section` played under `riff`
+    fn section_drum_grid(&self, section: MetalSection, riff: Option<&MetalRiff>, subgenre: MetalSubgenre) -> DrumGrid {
+        let guitar = riff.map(GuitarContext::from_riff).unwrap_or_default();
+        let duration = section.default_duration(self.tempo);
+        let feel = section.rhythmic_feel_for(subgenre);
+        generate_drum_grid(section, duration, self.tempo, subgenre, feel, &guitar, &DrumPatternOptions::default())
+    }
+}
+
+/// Per-section summary for song metadata
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionMetadata {
+    pub section: MetalSection,
+    pub intensity: SectionIntensity,
+    pub note_count: usize,
+    pub rhythm_histogram: BTreeMap<&'static str, usize>, // Rhythm name -> note count
+    pub playability_score: f32,
+    pub clean: bool,
+}
+
+/// Structured description of a generated song (written as JSON next to the audio)
+#[derive(Debug, Clone, Serialize)]
+pub struct MetalSongMetadata {
+    pub subgenre: MetalSubgenre,
+    pub key: Key,
+    pub tempo: u16,
+    pub tuning: GuitarTuning,
+    pub tuning_notes: Vec<MidiNote>, // Open strings, low to high
+    pub tuning_name: &'static str,
+    pub tuning_note_names: Vec<String>,
+    pub sections: Vec<SectionMetadata>,
+    pub average_playability: f32,
+}
+
+impl MetalSongMetadata {
+    pub fn from_song(song: &MetalSong) -> Self {
+        let sections: Vec<SectionMetadata> = song
+            .sections
+            .iter()
+            .map(|(section, riff)| {
+                let mut rhythm_histogram = BTreeMap::new();
+                for rhythm in &riff.rhythms {
+                    *rhythm_histogram.entry(rhythm.name()).or_insert(0) += 1;
+                }
+                SectionMetadata {
+                    section: *section,
+                    intensity: section.intensity(),
+                    note_count: riff.notes.len(),
+                    rhythm_histogram,
+                    playability_score: riff.playability_score,
+                    clean: riff.clean,
+                }
+            })
+            .collect();
+
+        let average_playability = if sections.is_empty() {
+            0.0
+        } else {
+            sections.iter().map(|s| s.playability_score).sum::<f32>() / sections.len() as f32
+        };
+
+        MetalSongMetadata {
+            subgenre: song.subgenre,
+            key: song.key,
+            tempo: song.tempo,
+            tuning: song.tuning.clone(),
+            tuning_notes: song.tuning.string_notes(),
+            tuning_name: song.tuning.display_name(),
+            tuning_note_names: song.tuning.note_names(),
+            sections,
+            average_playability,
+        }
+    }
+}
+
+/// Builds a `MetalSongGenerator`, overriding any subset of the subgenre's defaults:
+/// `MetalSongGenerator::builder().subgenre(MetalSubgenre::DoomMetal).tempo(70).seed(7).build()`
+#[derive(Debug, Clone)]
+pub struct MetalSongGeneratorBuilder {
+    subgenre: MetalSubgenre,
+    tuning: Option<GuitarTuning>,
+    key: Option<Key>,
+    tempo: Option<u16>,
+    seed: Option<u64>,
+    chaos_level: Option<f32>,
+    playability_mode: Option<PlayabilityMode>,
+}
+
+impl Default for MetalSongGeneratorBuilder {
+    fn default() -> Self {
+        MetalSongGeneratorBuilder {
+            subgenre: MetalSubgenre::HeavyMetal,
+            tuning: None,
+            key: None,
+            tempo: None,
+            seed: None,
+            chaos_level: None,
+            playability_mode: None,
+        }
+    }
+}
+
+impl MetalSongGeneratorBuilder {
+    /// Subgenre whose defaults fill in everything not set (heavy metal if unset)
+    pub fn subgenre(mut self, subgenre: MetalSubgenre) -> Self {
+        self.subgenre = subgenre;
+        self
+    }
+
+    /// Guitar tuning; without an explicit key the song is rooted on its lowest string
+    pub fn tuning(mut self, tuning: GuitarTuning) -> Self {
+        self.tuning = Some(tuning);
+        self
+    }
+
+    /// Song key instead of the tuning's lowest note in the subgenre's scale
+    pub fn key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Tempo in BPM (clamped to 40-320) instead of a random one from the subgenre's range
+    pub fn tempo(mut self, tempo: u16) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+
+    /// Seed the song RNG before building, so the generator and its songs are reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// 0.0-1.0; high chaos takes spiteful fingerings and sabotaged polymeters
+    pub fn chaos_level(mut self, chaos_level: f32) -> Self {
+        self.chaos_level = Some(chaos_level);
+        self
+    }
+
+    /// How far the fretboard pathfinder may stretch for the melody
+    pub fn playability_mode(mut self, mode: PlayabilityMode) -> Self {
+        self.playability_mode = Some(mode);
+        self
+    }
+
+    pub fn build(self) -> MetalSongGenerator {
+        if let Some(seed) = self.seed {
+            crate::utils::seed_rng(seed);
+        }
+        let mut generator = MetalSongGenerator::new(self.subgenre);
+        if let Some(tuning) = self.tuning {
+            generator.key.root = tuning.lowest_note();
+            generator.tuning = tuning;
+        }
+        if let Some(key) = self.key {
+            generator.key = key;
+        }
+        if let Some(tempo) = self.tempo {
+            generator = generator.with_tempo(tempo);
+        }
+        if let Some(chaos_level) = self.chaos_level {
+            generator.chaos_level = chaos_level.clamp(0.0, 1.0);
+        }
+        let mode = self.playability_mode.unwrap_or_else(|| self.subgenre.playability_mode());
+        generator.aggressive_pathfinder = FretboardPathfinder::with_mode(generator.tuning.clone(), mode);
+        generator
+    }
+}
+
 /// Metal song generator - integrates all components
 pub struct MetalSongGenerator {
     subgenre: MetalSubgenre,
@@ -227,12 +809,84 @@ pub struct MetalSongGenerator {
     pub polymeter: PolymetricInterference,
     pub mix_pipeline: AggressiveMixPipeline,
     pub chaos_level: f32,
+    /// Riffs scoring below this are regenerated or re-fingered (0.8 conservative, 0.2 chaos)
+    pub min_playability: f32,
+    /// Diatonic harmony voice added to solos (None = single lead)
+    pub solo_harmony: Option<Interval>,
+    /// Intro riffs are marked for the clean channel
+    pub clean_intro: bool,
+    /// Minor chords may be inverted when that keeps the fretting hand closer to the last note
+    pub chord_inversions: bool,
+    /// User chord progression; each section pedals on its chord's root (empty = the key's root)
+    progression: Vec<Chord>,
+    /// Share of palm-muted notes per section (sections left out keep their own voicing)
+    palm_mute_ratios: HashMap<MetalSection, f32>,
 }
 
 impl MetalSongGenerator {
     /// Create a new metal song generator
     pub fn new(subgenre: MetalSubgenre) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::with_min_playability(subgenre, DEFAULT_MIN_PLAYABILITY)
+    }
+
+    /// Builder for overriding any subset of tuning, key, tempo, seed, chaos and playability
+    pub fn builder() -> MetalSongGeneratorBuilder {
+        MetalSongGeneratorBuilder::default()
+    }
+
+    /// Create a generator in a specific key (any root/scale), keeping the
+    /// subgenre's tempo range, tuning, drums and riff behaviour
+    pub fn with_key(subgenre: MetalSubgenre, key: Key) -> Self {
+        let mut generator = Self::new(subgenre);
+        generator.key = key;
+        generator
+    }
+
+    /// Create a generator whose riffs are built around a chord progression, e.g.
+    /// `[Em, C, G, D]`. The first chord sets the key; verses, choruses, breakdowns and
+    /// solos take the chords in turn (cycling short progressions) and pedal on their
+    /// roots, intro and outro stay on the first chord. An empty progression is `new`
+    pub fn from_progression(subgenre: MetalSubgenre, progression: &[Chord]) -> Self {
+        let mut generator = Self::new(subgenre);
+        generator.progression = progression.to_vec();
+        if let Some(first) = progression.first() {
+            generator.key = generator.chord_key(first);
+        }
+        generator
+    }
+
+    /// Override the randomly chosen tempo (clamped to `MIN_TEMPO`-`MAX_TEMPO` BPM)
+    pub fn with_tempo(mut self, tempo: u16) -> Self {
+        self.tempo = tempo.clamp(MIN_TEMPO, MAX_TEMPO);
+        self.phrase_drums = PhraseAwareDrumGenerator::new(self.phrase_drums.sample_rate, self.tempo);
+        self
+    }
+
+    /// Palm-mute a share of a section's notes (0.0 = all open, 1.0 = all muted;
+    /// `None` = the section's own voicing: chugging verses, open choruses, ...)
+    pub fn set_palm_mute_ratio(&mut self, section: MetalSection, ratio: Option<f32>) {
+        match ratio {
+            Some(ratio) => self.palm_mute_ratios.insert(section, ratio.clamp(0.0, 1.0)),
+            None => self.palm_mute_ratios.remove(&section),
+        };
+    }
+
+    /// Override the subgenre's chromatic mutation intensity (0.0 = stay in the scale,
+    /// 1.0 = bends, tritones and chromatic runs everywhere)
+    pub fn set_chromatic_intensity(&mut self, intensity: f32) {
+        self.chromatic_mutator = ChromaticMutator::new(intensity.clamp(0.0, 1.0));
+    }
+
+    /// Build the sample-rate dependent parts for `context` instead of the global rate
+    pub fn with_context(mut self, context: RenderContext) -> Self {
+        self.phrase_drums = PhraseAwareDrumGenerator::new(context.sample_rate, self.tempo);
+        self.mix_pipeline = AggressiveMixPipeline::new(context.sample_rate);
+        self
+    }
+
+    /// Create a generator that rejects riffs below a playability threshold (0.0-1.0)
+    pub fn with_min_playability(subgenre: MetalSubgenre, min_playability: f32) -> Self {
+        let mut rng = crate::utils::rng();
         
         // Choose tuning and scale based on subgenre
         let tuning = subgenre.default_tuning();
@@ -271,14 +925,7 @@ impl MetalSongGenerator {
         
         let sample_rate = crate::utils::get_sample_rate();
         
-        // Determine playability mode based on subgenre
-        let playability_mode = match subgenre {
-            MetalSubgenre::ProgressiveMetal => PlayabilityMode::Aggressive,
-            MetalSubgenre::DeathMetal => PlayabilityMode::Aggressive,
-            _ => PlayabilityMode::Standard,
-        };
-        
-        let pathfinder = FretboardPathfinder::with_mode(tuning, playability_mode);
+        let pathfinder = FretboardPathfinder::with_mode(tuning.clone(), subgenre.playability_mode());
         
         MetalSongGenerator {
             subgenre,
@@ -298,19 +945,176 @@ impl MetalSongGenerator {
                 MetalSubgenre::DeathMetal => 0.8,
                 _ => 0.5,
             },
+            min_playability: min_playability.clamp(0.0, 1.0),
+            // Twin-guitar thirds are a heavy metal staple
+            solo_harmony: match subgenre {
+                MetalSubgenre::HeavyMetal => Some(Interval::Third),
+                _ => None,
+            },
+            // Doom opens quietly before the distortion hits
+            clean_intro: subgenre == MetalSubgenre::DoomMetal,
+            // Melodic choruses voice-lead instead of sliding root-position shapes around
+            chord_inversions: subgenre == MetalSubgenre::HeavyMetal,
+            progression: Vec::new(),
+            palm_mute_ratios: HashMap::new(),
+        }
+    }
+
+    /// Key a section's riff is built in: the song key, or the section's chord from the progression
+    fn section_key(&self, section: MetalSection) -> Key {
+        if self.progression.is_empty() {
+            return self.key;
+        }
+        let index = match section {
+            MetalSection::Intro | MetalSection::Verse | MetalSection::PreChorus | MetalSection::Outro => 0,
+            MetalSection::Chorus => 1,
+            MetalSection::Breakdown => 2,
+            MetalSection::Solo => 3,
+        };
+        self.chord_key(&self.progression[index % self.progression.len()])
+    }
+
+    /// Chord root moved into the lowest octave of the tuning, with the chord's scale
+    /// (sus and power chords keep the subgenre's scale)
+    fn chord_key(&self, chord: &Chord) -> Key {
+        let lowest = self.tuning.lowest_note();
+        let root = lowest + (chord.root % 12 + 12 - lowest % 12) % 12;
+        Key {
+            root,
+            scale_type: chord.chord_type.scale().unwrap_or_else(|| self.subgenre.default_scale()),
         }
     }
 
     /// Generate a complete metal riff for a section
-    /// Now varies based on section intensity and type
+    /// Riffs below `min_playability` are regenerated (up to MAX_PLAYABILITY_ATTEMPTS),
+    /// then the best candidate is run through the fretboard optimizer. Riffs that are
+    /// mostly rests are regenerated (up to MAX_SILENT_RIFF_ATTEMPTS), then filled in.
+    /// Solos get a harmony voice when `solo_harmony` is set; intros are clean when `clean_intro` is.
     pub fn generate_riff(&self, section: MetalSection) -> MetalRiff {
-        let mut rng = rand::thread_rng();
+        // Heavy rest probabilities can leave a riff with hardly a note in it
+        let mut riff = self.generate_playable_riff(section);
+        for _ in 1..MAX_SILENT_RIFF_ATTEMPTS {
+            if riff.is_audible() {
+                break;
+            }
+            riff = self.generate_playable_riff(section);
+        }
+        riff.fill_rests();
+        riff.clean = section == MetalSection::Intro && self.clean_intro;
+
+        if section == MetalSection::Solo {
+            // Neoclassical sweeps for heavy and progressive solos
+            if matches!(self.subgenre, MetalSubgenre::HeavyMetal | MetalSubgenre::ProgressiveMetal) {
+                self.insert_sweep(&mut riff);
+            }
+
+            if let Some(interval) = self.solo_harmony {
+                riff.harmony_notes = Some(
+                    riff.notes.iter().map(|&note| self.section_key(section).harmonize(note, interval)).collect(),
+                );
+            }
+        }
+
+        riff
+    }
+
+    /// Replace a bar in the second half of a solo with a sweep-picked arpeggio of the tonic
+    /// triad, laid out one note per string. Tries two octaves before one, and skips the
+    /// sweep if no shape fits the tuning.
+    fn insert_sweep(&self, riff: &mut MetalRiff) {
+        let intervals = self.key.scale_type.intervals();
+        let quality = if intervals.contains(&4) && !intervals.contains(&3) {
+            ArpeggioChord::Major
+        } else {
+            ArpeggioChord::Minor
+        };
+
+        let pathfinder = FretboardPathfinder::new(self.tuning.clone());
+        let sweep = [(2, 12), (2, 24), (1, 12), (1, 24)]
+            .into_iter()
+            .find_map(|(octaves, offset)| {
+                let notes = generate_sweep_arpeggio(self.key.root + offset, quality, SweepDirection::UpDown, octaves);
+                pathfinder.find_sweep_path(&notes).map(|path| (notes, path))
+            });
+        let Some((notes, path)) = sweep else {
+            return;
+        };
+
+        // Start on a bar boundary (8 notes) in the second half
+        let start = (riff.notes.len() / 2) / 8 * 8;
+        let end = start + notes.len();
+        if end > riff.notes.len() {
+            return;
+        }
+
+        let aligned = riff.fret_positions.len() == riff.notes.len();
+        riff.notes.splice(start..end, notes);
+        riff.rhythms.resize(riff.notes.len(), RhythmPattern::SixteenthNote);
+        riff.velocities.resize(riff.notes.len(), SWEEP_VELOCITY);
+        for i in start..end {
+            riff.rhythms[i] = RhythmPattern::ThirtySecondNote;
+            riff.velocities[i] = SWEEP_VELOCITY;
+            riff.chord_types[i] = ChordType::Single;
+            riff.palm_muted[i] = false;
+        }
+        if aligned {
+            riff.fret_positions.splice(start..end, path);
+            riff.playability_score = calculate_playability_score(&riff.fret_positions);
+        }
+    }
+
+    /// Generate a riff that meets `min_playability` (best effort). Above
+    /// `SPITEFUL_CHAOS_THRESHOLD` the awkward fingering is the point, so the first
+    /// candidate stands instead of being regenerated toward comfort.
+    fn generate_playable_riff(&self, section: MetalSection) -> MetalRiff {
+        let mut best = self.generate_riff_candidate(section);
+        if self.chaos_level > SPITEFUL_CHAOS_THRESHOLD {
+            return best;
+        }
+
+        for _ in 1..MAX_PLAYABILITY_ATTEMPTS {
+            if best.playability_score >= self.min_playability {
+                return best;
+            }
+            let candidate = self.generate_riff_candidate(section);
+            if candidate.playability_score > best.playability_score {
+                best = candidate;
+            }
+        }
+
+        if best.playability_score >= self.min_playability {
+            return best;
+        }
+
+        // Still too hard: snap the notes to the most playable fingering
+        let pathfinder = FretboardPathfinder::new(self.tuning.clone());
+        let (optimized_notes, _) = pathfinder.optimize_riff(&best.notes);
+        if optimized_notes.is_empty() {
+            return best;
+        }
+        let optimized = self.build_riff_from_notes(optimized_notes, section);
+        if optimized.playability_score > best.playability_score {
+            optimized
+        } else {
+            best
+        }
+    }
+
+    /// Generate a single riff candidate for a section
+    /// Varies based on section intensity and type
+    fn generate_riff_candidate(&self, section: MetalSection) -> MetalRiff {
+        let mut rng = crate::utils::rng();
         
         // Use breakdown generator for breakdowns
         if section == MetalSection::Breakdown {
             return self.generate_breakdown_riff();
         }
         
+        // Clean intros ring out an arpeggio instead of chugging
+        if section == MetalSection::Intro && self.clean_intro {
+            return self.generate_intro_arpeggio();
+        }
+
         // Use motif-based generation for some riffs (40% chance)
         if section != MetalSection::Intro && rng.gen_bool(0.4) {
             return self.generate_motif_based_riff(section);
@@ -324,20 +1128,22 @@ impl MetalSongGenerator {
         // Standard generation
         match section {
             MetalSection::Intro => {
-                let root = self.key.root;
-                let scale = self.key.scale_type;
+                let Key { root, scale_type: scale } = self.section_key(section);
                 let notes = self.generate_intro_sequence(root, scale, 16);
                 self.build_riff_from_notes(notes, section)
             },
             MetalSection::Verse => {
-                let root = self.key.root;
-                let scale = self.key.scale_type;
+                let Key { root, scale_type: scale } = self.section_key(section);
                 let notes = self.generate_verse_sequence(root, scale, 16);
                 self.build_riff_from_notes(notes, section)
             },
+            MetalSection::PreChorus => {
+                let Key { root, scale_type: scale } = self.section_key(section);
+                let notes = self.generate_prechorus_sequence(root, scale, 16);
+                self.build_riff_from_notes(notes, section)
+            },
             MetalSection::Chorus => {
-                let root = self.key.root;
-                let scale = self.key.scale_type;
+                let Key { root, scale_type: scale } = self.section_key(section);
                 let notes = self.generate_chorus_sequence(root, scale, 16);
                 self.build_riff_from_notes(notes, section)
             },
@@ -346,14 +1152,12 @@ impl MetalSongGenerator {
                 self.generate_breakdown_riff()
             },
             MetalSection::Solo => {
-                let root = self.key.root;
-                let scale = self.key.scale_type;
+                let Key { root, scale_type: scale } = self.section_key(section);
                 let notes = self.generate_solo_sequence(root, scale, 32);
                 self.build_riff_from_notes(notes, section)
             },
             MetalSection::Outro => {
-                let root = self.key.root;
-                let scale = self.key.scale_type;
+                let Key { root, scale_type: scale } = self.section_key(section);
                 let notes = self.generate_outro_sequence(root, scale, 16);
                 self.build_riff_from_notes(notes, section)
             },
@@ -365,34 +1169,122 @@ impl MetalSongGenerator {
     /// Generate a polymetric riff for progressive metal
     fn generate_polymetric_riff(&self, section: MetalSection) -> MetalRiff {
         // Use PolymetricInterference for prog-metal
-        let polymeter = PolymetricInterference::prog_metal();
+        let mut polymeter = PolymetricInterference::prog_metal();
         
         // Generate guitar pattern in odd meter (5/16)
-        let guitar_positions = polymeter.guitar_pattern(4); // 4 bars
+        let bars = 4;
+        let guitar_positions = self.polymetric_guitar_positions(&mut polymeter, bars);
         
         // Convert positions to notes from scale
-        let scale_notes = self.key.get_scale_notes();
+        let scale_notes = self.section_key(section).get_scale_notes();
         let mut notes = Vec::new();
         
-        for pos in guitar_positions {
+        for &pos in &guitar_positions {
             let note_idx = (pos / polymeter.guitar_meter) % scale_notes.len();
             notes.push(scale_notes[note_idx]);
         }
         
-        // Apply chromatic mutations for complexity
+        // Apply chromatic mutations for complexity (runs may add notes; the grid keeps one per onset)
         let mutated_notes = self.chromatic_mutator.apply_mutations(notes);
+
+        // Each onset lasts until the next one: spans past a quarter are padded with rests
+        let mut riff_notes = Vec::new();
+        let mut rhythms = Vec::new();
+        for (i, (&pos, &note)) in guitar_positions.iter().zip(&mutated_notes).enumerate() {
+            let next = guitar_positions.get(i + 1).copied().unwrap_or(bars * 16);
+            let gap = next.saturating_sub(pos).max(1);
+            riff_notes.push(note);
+            rhythms.push(RhythmPattern::from_sixteenths(gap));
+            for _ in 4..gap {
+                riff_notes.push(note);
+                rhythms.push(RhythmPattern::Rest);
+            }
+        }
         
         // Build riff
-        self.build_riff_from_notes(mutated_notes, section)
+        self.build_riff_with_rhythms(riff_notes, rhythms, section)
     }
 
-    /// Build a MetalRiff from notes with appropriate palm muting, chords, and rhythms
-    fn build_riff_from_notes(&self, notes: Vec<MidiNote>, section: MetalSection) -> MetalRiff {
-        let root = self.key.root;
-        let mut rng = rand::thread_rng();
-        
-        // Generate rhythm patterns based on section and subgenre
-        let rhythms = self.generate_rhythm_patterns(notes.len(), section);
+    /// Guitar onsets (16th steps) for a polymetric riff: the plain odd-meter grid, or at high
+    /// chaos the sabotaged one (the meter may drop mid-riff, so read it back afterwards)
+    fn polymetric_guitar_positions(&self, polymeter: &mut PolymetricInterference, bars: usize) -> Vec<usize> {
+        if self.chaos_level > CHAOTIC_METER_THRESHOLD {
+            polymeter.chaotic_guitar_pattern(bars)
+        } else {
+            polymeter.guitar_pattern(bars)
+        }
+    }
+
+    /// Clean intro: the progression's triads broken into open, ringing eighth notes an octave
+    /// above the key root - no palm muting, no power chords
+    fn generate_intro_arpeggio(&self) -> MetalRiff {
+        let notes: Vec<MidiNote> = INTRO_ARPEGGIO_DEGREES
+            .iter()
+            .flat_map(|&degree| {
+                let mut tones = self.diatonic_triad(degree).get_notes();
+                tones.truncate(3);
+                tones.push(tones[0] + 12);
+                INTRO_ARPEGGIO_PATTERN.iter().map(move |&i| tones[i])
+            })
+            .collect();
+
+        let len = notes.len();
+        let chord_types = vec![ChordType::Single; len];
+        let palm_muted = vec![false; len];
+        let velocities = (0..len)
+            .map(|i| Self::note_velocity(i, ChordType::Single, false, SectionIntensity::Low))
+            .collect();
+        let fret_positions = FretboardPathfinder::new(self.tuning.clone()).find_playable_path(&notes);
+        let playability_score = calculate_playability_score(&fret_positions);
+
+        MetalRiff {
+            notes,
+            chord_types,
+            palm_muted,
+            rhythms: vec![RhythmPattern::EighthNote; len],
+            velocities,
+            fret_positions,
+            harmony_notes: None,
+            playability_score,
+            clean: false,
+        }
+    }
+
+    /// Triad stacked in thirds on a scale degree, an octave above the key root
+    fn diatonic_triad(&self, degree: usize) -> Chord {
+        let scale = self.key.get_scale_notes();
+        let note_at = |step: usize| {
+            let index = degree + step;
+            scale[index % scale.len()] + 12 * (1 + index / scale.len()) as MidiNote
+        };
+        let root = note_at(0);
+
+        let chord_type = match (note_at(2) - root, note_at(4) - root) {
+            (3, 6) => ArpeggioChord::Diminished,
+            (3, _) => ArpeggioChord::Minor,
+            (4, 8) => ArpeggioChord::Augmented,
+            (4, _) => ArpeggioChord::Major,
+            (2, _) => ArpeggioChord::Sus2,
+            (5, _) => ArpeggioChord::Sus4,
+            _ => ArpeggioChord::Power5,
+        };
+
+        Chord { root, chord_type }
+    }
+
+    /// Build a MetalRiff from notes with appropriate palm muting, chords, and rhythms
+    fn build_riff_from_notes(&self, notes: Vec<MidiNote>, section: MetalSection) -> MetalRiff {
+        // Generate rhythm patterns based on section and subgenre
+        let rhythms = self.generate_rhythm_patterns(notes.len(), section);
+        self.build_riff_with_rhythms(notes, rhythms, section)
+    }
+
+    /// Build a riff from notes whose rhythms are already fixed (one per note)
+    fn build_riff_with_rhythms(&self, notes: Vec<MidiNote>, mut rhythms: Vec<RhythmPattern>, section: MetalSection) -> MetalRiff {
+        let root = self.key.root;
+        let mut rng = crate::utils::rng();
+        // Notes off the fretboard move by octaves, so every note keeps its rhythm and fret position
+        let notes = FretboardPathfinder::new(self.tuning.clone()).fit_to_range(&notes);
         
         // Determine palm muting and chords based on section and intensity
         let mut palm_muted = Vec::new();
@@ -460,6 +1352,11 @@ impl MetalSongGenerator {
                         chord_types.push(ChordType::Power);
                     }
                 },
+                MetalSection::PreChorus => {
+                    // Chugs open up halfway through as the build heads for the chorus
+                    palm_muted.push(i < notes.len() / 2);
+                    chord_types.push(ChordType::Power);
+                },
                 MetalSection::Chorus => {
                     palm_muted.push(false);
                     if is_strong_beat {
@@ -497,46 +1394,130 @@ impl MetalSongGenerator {
             }
         }
 
+        self.apply_palm_mute_ratio(section, &notes, &rhythms, &mut palm_muted);
+
+        // Tremolo picking: sustained high-register single notes in death metal verses
+        if self.subgenre == MetalSubgenre::DeathMetal && section == MetalSection::Verse {
+            let rate_hz = self.tremolo_rate_hz();
+            for (i, &note) in notes.iter().enumerate() {
+                if rhythms[i] != RhythmPattern::Rest
+                    && note >= root + TREMOLO_REGISTER_OFFSET
+                    && rng.gen_bool(0.5)
+                {
+                    rhythms[i] = RhythmPattern::Tremolo { rate_hz };
+                    chord_types[i] = ChordType::Single;
+                    palm_muted[i] = false;
+                }
+            }
+        }
+
         // Validate playability
-        let pathfinder = FretboardPathfinder::new(self.tuning);
-        let fret_positions = pathfinder.find_playable_path(&notes);
+        // High chaos: spiteful path with dramatic position jumps instead of the comfortable one
+        let mut fret_positions = if self.chaos_level > SPITEFUL_CHAOS_THRESHOLD {
+            self.aggressive_pathfinder.find_spiteful_path(&notes, self.chaos_level)
+        } else {
+            FretboardPathfinder::new(self.tuning.clone()).find_playable_path(&notes)
+        };
+        if self.chord_inversions {
+            self.choose_inversions(&notes, &mut fret_positions, &mut chord_types);
+        }
         let playability_score = calculate_playability_score(&fret_positions);
 
+        let intensity = section.intensity();
+        let velocities = (0..notes.len())
+            .map(|i| Self::note_velocity(i, chord_types[i], palm_muted[i], intensity))
+            .collect();
+
         MetalRiff {
             notes,
             chord_types,
             palm_muted,
             rhythms,
+            velocities,
+            fret_positions,
+            harmony_notes: None,
             playability_score,
+            clean: false,
+        }
+    }
+
+    /// Re-voice minor chords with the third or fifth in the bass when that bass note can be
+    /// fretted closer to the previous note's position than the root (ties keep the root).
+    /// A re-voiced chord's fret position moves to where its new bass note is fretted.
+    fn choose_inversions(&self, notes: &[MidiNote], fret_positions: &mut [FretPosition], chord_types: &mut [ChordType]) {
+        let pathfinder = FretboardPathfinder::new(self.tuning.clone());
+        for i in 1..notes.len().min(fret_positions.len()).min(chord_types.len()) {
+            if chord_types[i] != ChordType::Minor {
+                continue;
+            }
+            let previous = fret_positions[i - 1];
+            let reach = |interval: u8| {
+                pathfinder
+                    .get_positions_for_note(notes[i].saturating_add(interval))
+                    .into_iter()
+                    .map(|position| (previous.movement_cost(&position), position))
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+            };
+            let root_cost = reach(0).map_or(f32::INFINITY, |(cost, _)| cost);
+            let best = [Inversion::First, Inversion::Second]
+                .into_iter()
+                .filter_map(|inversion| reach(inversion.bass_interval()).map(|(cost, position)| (cost, inversion, position)))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((_, inversion, position)) = best.filter(|&(cost, _, _)| cost < root_cost) {
+                chord_types[i] = ChordType::MinorInversion(inversion);
+                fret_positions[i] = position;
+            }
         }
     }
 
+    /// Re-voice a section to its `palm_mute_ratio`, if one is set: that share of the
+    /// picked notes is muted, pedal notes first, then off-beats, then strong beats
+    fn apply_palm_mute_ratio(&self, section: MetalSection, notes: &[MidiNote], rhythms: &[RhythmPattern], palm_muted: &mut [bool]) {
+        let Some(&ratio) = self.palm_mute_ratios.get(&section) else {
+            return;
+        };
+        let pedal = self.section_key(section).root % 12;
+        let mut picked: Vec<usize> = (0..notes.len().min(palm_muted.len()))
+            .filter(|&i| rhythms.get(i) != Some(&RhythmPattern::Rest))
+            .collect();
+        picked.sort_by_key(|&i| match (notes[i] % 12 == pedal, i.is_multiple_of(4)) {
+            (true, _) => 0,
+            (false, false) => 1,
+            (false, true) => 2,
+        });
+
+        let muted = (ratio * picked.len() as f32).round() as usize;
+        for (rank, &i) in picked.iter().enumerate() {
+            palm_muted[i] = rank < muted;
+        }
+    }
+
+    /// Picking dynamics for one note: downbeats and open chords dig in,
+    /// off-beat passing notes are lighter. Louder sections raise the whole curve.
+    fn note_velocity(index: usize, chord_type: ChordType, palm_muted: bool, intensity: SectionIntensity) -> f32 {
+        let base: f32 = match intensity {
+            SectionIntensity::Low => 0.65,
+            SectionIntensity::Medium => 0.72,
+            SectionIntensity::High => 0.78,
+            SectionIntensity::Extreme => 0.82,
+        };
+        let accent = match index % 4 {
+            0 => 0.12,  // Downbeat
+            2 => 0.04,  // Other eighth
+            _ => -0.06, // Off-beat sixteenth
+        };
+        let open_chord = if !palm_muted && chord_type != ChordType::Single { 0.06 } else { 0.0 };
+        (base + accent + open_chord).clamp(0.0, 1.0)
+    }
+
+    /// Tremolo picking rate locked to sixteenth notes at the song tempo
+    fn tremolo_rate_hz(&self) -> u16 {
+        (self.tempo * 4 / 60).max(8)
+    }
+
     /// Generate rhythm patterns for a riff based on section and subgenre
     fn generate_rhythm_patterns(&self, length: usize, section: MetalSection) -> Vec<RhythmPattern> {
-        match section {
-            MetalSection::Breakdown => {
-                // BREAKDOWN: Sparse, quarter notes. NOT 16th notes.
-                // This prevents the "noisy" machine gun effect
-                let mut rhythms = Vec::new();
-                for i in 0..length {
-                    if i % 2 == 0 {
-                         // Quarter note feel
-                         rhythms.push(RhythmPattern::QuarterNote);
-                    } else {
-                        // Space
-                        rhythms.push(RhythmPattern::Rest);
-                    }
-                }
-                rhythms
-            },
-            _ => match self.subgenre {
-                MetalSubgenre::ThrashMetal => self.generate_thrash_rhythms(length, section),
-                MetalSubgenre::DeathMetal => self.generate_death_rhythms(length, section),
-                MetalSubgenre::DoomMetal => self.generate_doom_rhythms(length, section),
-                MetalSubgenre::HeavyMetal => self.generate_heavy_rhythms(length, section),
-                MetalSubgenre::ProgressiveMetal => self.generate_progressive_rhythms(length, section),
-            },
-        }
+        RhythmEngine::generate(self.subgenre, section, length, &mut crate::utils::rng())
     }
 
     /// Generate intro sequence (low intensity, sparse)
@@ -549,6 +1530,16 @@ impl MetalSongGenerator {
         self.generate_markov_sequence_with_pedal(root, scale, length, 0.50)
     }
 
+    /// Generate pre-chorus sequence (pedal riff climbing a scale degree each quarter)
+    fn generate_prechorus_sequence(&self, root: MidiNote, scale: ScaleType, length: usize) -> Vec<MidiNote> {
+        let key = Key { root, scale_type: scale };
+        self.generate_markov_sequence_with_pedal(root, scale, length, 0.60)
+            .into_iter()
+            .enumerate()
+            .map(|(i, note)| key.step_degrees(note, i * 4 / length.max(1)))
+            .collect()
+    }
+
     /// Generate chorus sequence (open power chords, melodic)
     fn generate_chorus_sequence(&self, root: MidiNote, scale: ScaleType, length: usize) -> Vec<MidiNote> {
         self.generate_markov_sequence_with_pedal(root, scale, length, 0.30)
@@ -564,22 +1555,182 @@ impl MetalSongGenerator {
         self.generate_markov_sequence_with_pedal(root, scale, length, 0.80)
     }
 
+    /// Generate `count` songs, seeding song i with `base_seed + i` so the batch is reproducible
+    pub fn generate_batch(subgenre: MetalSubgenre, count: usize, base_seed: u64) -> Vec<MetalSong> {
+        (0..count)
+            .map(|i| {
+                crate::utils::seed_rng(base_seed.wrapping_add(i as u64));
+                MetalSongGenerator::new(subgenre).generate_song()
+            })
+            .collect()
+    }
+
     /// Generate a complete metal song structure
     pub fn generate_song(&self) -> MetalSong {
-        let mut sections = Vec::new();
-
-        sections.push((MetalSection::Intro, self.generate_riff(MetalSection::Intro)));
-        sections.push((MetalSection::Verse, self.generate_riff(MetalSection::Verse)));
-        sections.push((MetalSection::Chorus, self.generate_riff(MetalSection::Chorus)));
-        sections.push((MetalSection::Verse, self.generate_riff(MetalSection::Verse)));
-        sections.push((MetalSection::Chorus, self.generate_riff(MetalSection::Chorus)));
-        sections.push((MetalSection::Verse, self.generate_riff(MetalSection::Verse)));
-        sections.push((MetalSection::Breakdown, self.generate_riff(MetalSection::Breakdown)));
-        sections.push((MetalSection::Solo, self.generate_riff(MetalSection::Solo)));
-        sections.push((MetalSection::Chorus, self.generate_riff(MetalSection::Chorus)));
-        sections.push((MetalSection::Breakdown, self.generate_riff(MetalSection::Breakdown)));
-        sections.push((MetalSection::Chorus, self.generate_riff(MetalSection::Chorus)));
-        sections.push((MetalSection::Outro, self.generate_riff(MetalSection::Outro)));
+        self.build_song(&STANDARD_LAYOUT)
+    }
+
+    /// Generate a song sized to `target_seconds`, repeating verse/pre-chorus/chorus groups as needed.
+    /// Always keeps exactly one Intro and one Outro.
+    pub fn generate_song_for_duration(&self, target_seconds: f32, seconds_per_section: f32) -> MetalSong {
+        self.build_song(&Self::layout_for_duration(target_seconds, seconds_per_section))
+    }
+
+    /// Section layout for a target duration: Intro, verse/pre-chorus/chorus groups (with a
+    /// breakdown + solo bridge once there's room), Outro. A song too short for a
+    /// pre-chorus falls back to a single verse/chorus pair.
+    pub fn layout_for_duration(target_seconds: f32, seconds_per_section: f32) -> Vec<MetalSection> {
+        let total = if seconds_per_section > 0.0 {
+            (target_seconds / seconds_per_section).round().max(0.0) as usize
+        } else {
+            0
+        };
+
+        let body = total.saturating_sub(2);
+        if body < 3 {
+            return vec![MetalSection::Intro, MetalSection::Verse, MetalSection::Chorus, MetalSection::Outro];
+        }
+
+        let has_bridge = body >= 8;
+        let rest = if has_bridge { body - 2 } else { body };
+        let groups = ((rest as f32 / 3.0).round() as usize).max(1); // Whole verse/pre-chorus/chorus groups
+
+        let mut layout = vec![MetalSection::Intro];
+        let bridge_at = groups.div_ceil(2);
+        for i in 0..groups {
+            if has_bridge && i == bridge_at {
+                layout.push(MetalSection::Breakdown);
+                layout.push(MetalSection::Solo);
+            }
+            layout.extend([MetalSection::Verse, MetalSection::PreChorus, MetalSection::Chorus]);
+        }
+        layout.push(MetalSection::Outro);
+        layout
+    }
+
+    /// Generate the standard song, but restate each verse and chorus riff on its
+    /// repeats (with a varied last bar) instead of writing a new riff every time.
+    /// The first chorus is a development of the verse's opening motif.
+    pub fn generate_song_with_memory(&mut self) -> MetalSong {
+        self.bar_memory.forget_sections();
+        let sections = STANDARD_LAYOUT
+            .iter()
+            .map(|&section| (section, self.remembered_riff(section)))
+            .collect();
+        self.assemble_song(sections)
+    }
+
+    /// Verse/pre-chorus/chorus riff from memory if the section has been heard, otherwise a new one
+    fn remembered_riff(&mut self, section: MetalSection) -> MetalRiff {
+        if !matches!(section, MetalSection::Verse | MetalSection::PreChorus | MetalSection::Chorus) {
+            return self.generate_riff(section);
+        }
+
+        if let Some(theme) = self.bar_memory.recall_section(section).cloned() {
+            return self.vary_riff_ending(theme);
+        }
+
+        // The first chorus grows out of the verse instead of starting from scratch
+        let developed = match (section, self.bar_memory.recall_section(MetalSection::Verse)) {
+            (MetalSection::Chorus, Some(verse)) => Some(self.develop_chorus(verse)),
+            _ => None,
+        };
+        let riff = developed.unwrap_or_else(|| self.generate_riff(section));
+        self.bar_memory.remember_section(section, &riff);
+        riff
+    }
+
+    /// Chorus built from the verse's opening cell: transposed up a fourth, inverted,
+    /// played backwards or augmented, then sequenced to fill the verse's length
+    fn develop_chorus(&self, verse: &MetalRiff) -> MetalRiff {
+        let mut rng = crate::utils::rng();
+        // With a progression the cell moves from the verse's chord to the chorus's
+        let verse_root = self.section_key(MetalSection::Verse).root;
+        let root = self.section_key(MetalSection::Chorus).root;
+        let cell_len = CHORUS_CELL_NOTES.min(verse.notes.len());
+        let cell = RiffMotif::from_notes("Verse cell", &verse.notes[..cell_len], verse_root, 0.5);
+
+        let developed = match rng.gen_range(0..4) {
+            0 => cell.transpose(5),
+            1 => cell.invert(),
+            2 => cell.retrograde(),
+            _ => cell.augment(),
+        };
+        let augmented = developed.rhythm_density < cell.rhythm_density;
+
+        // Augmented notes last twice as long, so half as many fill the same time
+        let length = if augmented { verse.notes.len().div_ceil(2) } else { verse.notes.len() };
+        let lowest = root as i16;
+        let notes: Vec<MidiNote> = developed
+            .apply(root)
+            .into_iter()
+            .cycle()
+            .take(length.max(1))
+            .map(|note| {
+                // Inversions can dip below the open string: lift them back by octaves
+                let mut note = note as i16;
+                while note < lowest {
+                    note += 12;
+                }
+                note.min(127) as MidiNote
+            })
+            .collect();
+
+        let mut riff = self.build_riff_from_notes(notes, MetalSection::Chorus);
+        if augmented {
+            for rhythm in riff.rhythms.iter_mut() {
+                *rhythm = rhythm.augmented();
+            }
+        }
+        riff.fill_rests();
+        riff
+    }
+
+    /// Keep the start of a riff and mutate its last bar, so a repeat is recognisable
+    /// but not a copy. The mutated notes are folded back into the riff's register.
+    fn vary_riff_ending(&mut self, mut riff: MetalRiff) -> MetalRiff {
+        let tail_len = MOTIF_VARIATION_NOTES.min(riff.notes.len() / 2);
+        if tail_len < 2 || riff.rhythms.len() != riff.notes.len() {
+            return riff;
+        }
+        let start = riff.notes.len() - tail_len;
+
+        self.bar_memory.reset();
+        self.bar_memory.store_bar(&riff.notes[start..], &riff.rhythms[start..]);
+        let (notes, rhythms, _) = self.bar_memory.mutate_bar(riff.notes[start]);
+
+        let low = *riff.notes.iter().min().unwrap_or(&self.key.root) as i16;
+        let high = *riff.notes.iter().max().unwrap_or(&self.key.root) as i16;
+        for (slot, note) in riff.notes[start..].iter_mut().zip(notes) {
+            let mut note = note as i16;
+            while note > high {
+                note -= 12;
+            }
+            while note < low {
+                note += 12;
+            }
+            *slot = note.min(high) as MidiNote;
+        }
+        if rhythms.len() == tail_len {
+            riff.rhythms[start..].copy_from_slice(&rhythms);
+        }
+
+        riff.fret_positions = FretboardPathfinder::new(self.tuning.clone()).find_playable_path(&riff.notes);
+        riff.playability_score = calculate_playability_score(&riff.fret_positions);
+        riff
+    }
+
+    /// Generate riffs for each section of a layout and assemble the song
+    fn build_song(&self, layout: &[MetalSection]) -> MetalSong {
+        let sections = layout
+            .iter()
+            .map(|&section| (section, self.generate_riff(section)))
+            .collect();
+        self.assemble_song(sections)
+    }
+
+    /// Wrap generated sections with the song-level settings
+    fn assemble_song(&self, sections: Vec<(MetalSection, MetalRiff)>) -> MetalSong {
 
         let drum_humanizer = match self.subgenre {
             MetalSubgenre::HeavyMetal => DrumHumanizer::new(),
@@ -593,7 +1744,7 @@ impl MetalSongGenerator {
             subgenre: self.subgenre,
             key: self.key,
             tempo: self.tempo,
-            tuning: self.tuning,
+            tuning: self.tuning.clone(),
             sections,
             drum_humanizer,
         }
@@ -631,6 +1782,10 @@ impl MetalSongGenerator {
         } else {
             // For lower pedal probability, use Markov chain for more melodic movement
             let mut markov = match self.subgenre {
+                // Exotic scales get lines built around their augmented second
+                MetalSubgenre::HeavyMetal | MetalSubgenre::ProgressiveMetal if scale.has_augmented_second() => {
+                    MetalMarkovPresets::neoclassical(&key)
+                },
                 MetalSubgenre::HeavyMetal => MetalMarkovPresets::heavy_metal(&key),
                 MetalSubgenre::ThrashMetal => MetalMarkovPresets::heavy_metal(&key),
                 MetalSubgenre::DeathMetal => MetalMarkovPresets::death_metal(&key),
@@ -646,10 +1801,158 @@ impl MetalSongGenerator {
         }
     }
 
+    /// Generate drums for a section (Legacy support method)
+    pub fn generate_drums(&self, section: MetalSection, humanizer: &DrumHumanizer) -> Vec<(u8, i32)> {
+        let mut drum_hits = Vec::new();
+        let subdivisions = match section {
+            MetalSection::Breakdown => 8,
+            _ => 16,
+        };
+
+        let use_blast = matches!(
+            (self.subgenre, section),
+            (MetalSubgenre::DeathMetal, MetalSection::Verse) |
+            (MetalSubgenre::DeathMetal, MetalSection::Chorus)
+        );
+
+        if use_blast {
+            let blast = generate_varied_blast(1, subdivisions, DEFAULT_BLAST_VARIATION);
+            
+            for i in 0..subdivisions {
+                if blast.kicks[i] || blast.snares[i] {
+                    let base_velocity = blast_beat_velocity(100, blast.accents[i]);
+                    let (velocity, timing) = humanizer.humanize_hit(base_velocity, blast.accents[i]);
+                    drum_hits.push((velocity, timing));
+                }
+            }
+        } else {
+            for i in 0..subdivisions {
+                let is_accent = i % 4 == 0;
+                let base_velocity = if is_accent { 110 } else { 95 };
+                let (velocity, timing) = humanizer.humanize_hit(base_velocity, is_accent);
+                drum_hits.push((velocity, timing));
+            }
+        }
+
+        drum_hits
+    }
+
+    /// Generate a motif-based riff with chromatic mutations
+    fn generate_motif_based_riff(&self, section: MetalSection) -> MetalRiff {
+        let mut rng = crate::utils::rng();
+        let root = self.section_key(section).root;
+        
+        // Select random motif
+        let motif = self.motif_library.random_motif();
+        
+        // Apply motif to root note
+        let base_notes = motif.apply(root);
+        
+        // Apply chromatic mutations for dissonance
+        let mutated_notes = self.chromatic_mutator.apply_mutations(base_notes);
+        
+        // Use aggressive pathfinding if available
+        let _fret_positions = if matches!(self.subgenre, MetalSubgenre::ProgressiveMetal) {
+            self.aggressive_pathfinder.find_aggressive_path(&mutated_notes)
+        } else {
+            self.aggressive_pathfinder.find_playable_path(&mutated_notes)
+        };
+        
+        // Build riff from mutated notes
+        self.build_riff_from_notes(mutated_notes, section)
+    }
+
+    /// Generate a breakdown riff with syncopated silences and dotted-eighth stabs
+    fn generate_breakdown_riff(&self) -> MetalRiff {
+        let root = self.section_key(MetalSection::Breakdown).root;
+        
+        // Generate breakdown pattern with syncopated silences
+        let pattern = self.breakdown_generator.generate_breakdown_pattern(root, 2);
+        
+        let mut notes = Vec::new();
+        let mut rhythms = Vec::new();
+        let mut palm_muted = Vec::new();
+        let mut chord_types = Vec::new();
+        
+        for (_pos, note, _duration_mult, is_silent) in pattern {
+            if !is_silent {
+                notes.push(note);
+                rhythms.push(RhythmPattern::DottedEighth); // Dotted eighth stabs
+                palm_muted.push(true); // Heavy palm muting
+                chord_types.push(ChordType::Power); // Power chords
+            }
+        }
+        
+        // Ensure we have at least some notes
+        if notes.is_empty() {
+            notes = vec![root; 4];
+            rhythms = vec![RhythmPattern::QuarterNote; 4];
+            palm_muted = vec![true; 4];
+            chord_types = vec![ChordType::Power; 4];
+        }
+        
+        self.apply_palm_mute_ratio(MetalSection::Breakdown, &notes, &rhythms, &mut palm_muted);
+
+        let fret_positions = FretboardPathfinder::new(self.tuning.clone()).find_playable_path(&notes);
+        let velocities = (0..notes.len())
+            .map(|i| Self::note_velocity(i, chord_types[i], palm_muted[i], MetalSection::Breakdown.intensity()))
+            .collect();
+        
+        MetalRiff {
+            notes,
+            palm_muted,
+            chord_types,
+            rhythms,
+            velocities,
+            fret_positions,
+            harmony_notes: None,
+            playability_score: 0.8,
+            clean: false,
+        }
+    }
+}
+
+/// Per-subgenre rhythm patterns for riffs, usable without a whole `MetalSongGenerator`
+/// (pass `crate::utils::rng()` to follow the song seed)
+pub struct RhythmEngine;
+
+impl RhythmEngine {
+    /// Rhythm patterns for `length` notes of a section; breakdowns are sparse quarter
+    /// notes whatever the subgenre
+    pub fn generate<R: Rng + ?Sized>(
+        subgenre: MetalSubgenre,
+        section: MetalSection,
+        length: usize,
+        rng: &mut R,
+    ) -> Vec<RhythmPattern> {
+        match section {
+            MetalSection::Breakdown => {
+                // BREAKDOWN: Sparse, quarter notes. NOT 16th notes.
+                // This prevents the "noisy" machine gun effect
+                let mut rhythms = Vec::new();
+                for i in 0..length {
+                    if i % 2 == 0 {
+                         // Quarter note feel
+                         rhythms.push(RhythmPattern::QuarterNote);
+                    } else {
+                        // Space
+                        rhythms.push(RhythmPattern::Rest);
+                    }
+                }
+                rhythms
+            },
+            _ => match subgenre {
+                MetalSubgenre::ThrashMetal => Self::thrash(length, section, rng),
+                MetalSubgenre::DeathMetal => Self::death(length, section, rng),
+                MetalSubgenre::DoomMetal => Self::doom(length, section, rng),
+                MetalSubgenre::HeavyMetal => Self::heavy(length, section, rng),
+                MetalSubgenre::ProgressiveMetal => Self::progressive(length, section, rng),
+            },
+        }
+    }
+
     /// Generate thrash metal rhythms
-    fn generate_thrash_rhythms(&self, length: usize, section: MetalSection) -> Vec<RhythmPattern> {
-        let _ = section;
-        let mut rng = rand::thread_rng();
+    fn thrash<R: Rng + ?Sized>(length: usize, section: MetalSection, rng: &mut R) -> Vec<RhythmPattern> {
         let mut rhythms = Vec::with_capacity(length);
         let mut i = 0;
         
@@ -678,8 +1981,7 @@ impl MetalSongGenerator {
     }
 
     /// Generate death metal rhythms
-    fn generate_death_rhythms(&self, length: usize, _section: MetalSection) -> Vec<RhythmPattern> {
-        let mut rng = rand::thread_rng();
+    fn death<R: Rng + ?Sized>(length: usize, _section: MetalSection, rng: &mut R) -> Vec<RhythmPattern> {
         let mut rhythms = Vec::with_capacity(length);
         let mut i = 0;
         
@@ -707,8 +2009,7 @@ impl MetalSongGenerator {
     }
 
     /// Generate doom metal rhythms
-    fn generate_doom_rhythms(&self, length: usize, _section: MetalSection) -> Vec<RhythmPattern> {
-        let mut rng = rand::thread_rng();
+    fn doom<R: Rng + ?Sized>(length: usize, _section: MetalSection, rng: &mut R) -> Vec<RhythmPattern> {
         let mut rhythms = Vec::with_capacity(length);
         let mut i = 0;
         
@@ -729,8 +2030,7 @@ impl MetalSongGenerator {
     }
 
     /// Generate heavy metal rhythms
-    fn generate_heavy_rhythms(&self, length: usize, section: MetalSection) -> Vec<RhythmPattern> {
-        let mut rng = rand::thread_rng();
+    fn heavy<R: Rng + ?Sized>(length: usize, section: MetalSection, rng: &mut R) -> Vec<RhythmPattern> {
         let mut rhythms = Vec::with_capacity(length);
         let rest_prob = match section {
             MetalSection::Verse => 0.35,
@@ -754,9 +2054,7 @@ impl MetalSongGenerator {
 
     /// Generate progressive metal rhythms using Euclidean rhythms
     /// Research: Polymetric and Euclidean patterns are essential for Djent/Progressive metal
-    fn generate_progressive_rhythms(&self, length: usize, _section: MetalSection) -> Vec<RhythmPattern> {
-        let mut rng = rand::thread_rng();
-        
+    fn progressive<R: Rng + ?Sized>(length: usize, _section: MetalSection, rng: &mut R) -> Vec<RhythmPattern> {
         // Use Euclidean rhythm with prime numbers for interesting syncopation
         let pulses = if length >= 16 { 7 } else { 5 }; // Prime numbers create better patterns
         let euclidean_pattern = euclidean_rhythm(length, pulses);
@@ -781,103 +2079,511 @@ impl MetalSongGenerator {
         
         rhythms
     }
+}
 
-    /// Generate drums for a section (Legacy support method)
-    pub fn generate_drums(&self, section: MetalSection, humanizer: &DrumHumanizer) -> Vec<(u8, i32)> {
-        let mut drum_hits = Vec::new();
-        let subdivisions = match section {
-            MetalSection::Breakdown => 8,
-            _ => 16,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song_notes(song: &MetalSong) -> Vec<MidiNote> {
+        song.sections.iter().flat_map(|(_, riff)| riff.notes.clone()).collect()
+    }
+
+    #[test]
+    fn test_generate_batch_is_reproducible() {
+        let first = MetalSongGenerator::generate_batch(MetalSubgenre::ThrashMetal, 2, 42);
+        let second = MetalSongGenerator::generate_batch(MetalSubgenre::ThrashMetal, 2, 42);
+
+        assert_eq!(first.len(), 2);
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.tempo, b.tempo);
+            assert_eq!(song_notes(a), song_notes(b));
+        }
+
+        // Consecutive seeds give distinct songs
+        assert_ne!(song_notes(&first[0]), song_notes(&first[1]));
+    }
+
+    #[test]
+    fn test_song_with_memory_restates_verse_motif() {
+        crate::utils::seed_rng(7);
+        let mut generator = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        let song = generator.generate_song_with_memory();
+
+        let verses: Vec<&MetalRiff> = song.sections.iter()
+            .filter(|(section, _)| *section == MetalSection::Verse)
+            .map(|(_, riff)| riff)
+            .collect();
+        assert!(verses.len() >= 2);
+
+        // Every verse opens with the first verse's motif; only the last bar may differ
+        let first = verses[0];
+        for verse in &verses[1..] {
+            assert_eq!(verse.notes.len(), first.notes.len());
+            let kept = first.notes.len() - MOTIF_VARIATION_NOTES.min(first.notes.len() / 2);
+            assert_eq!(verse.notes[..kept], first.notes[..kept]);
+            assert_eq!(verse.fret_positions.len(), verse.notes.len());
+        }
+    }
+
+    #[test]
+    fn test_chorus_develops_verse_motif() {
+        let pitch_classes = |notes: &[MidiNote]| notes.iter().map(|&n| n % 12).collect::<Vec<_>>();
+
+        for seed in 0..8 {
+            crate::utils::seed_rng(seed);
+            let mut generator = MetalSongGenerator::new(MetalSubgenre::ThrashMetal);
+            let song = generator.generate_song_with_memory();
+            let riff_for = |kind: MetalSection| &song.sections.iter().find(|(s, _)| *s == kind).unwrap().1;
+            let (verse, chorus) = (riff_for(MetalSection::Verse), riff_for(MetalSection::Chorus));
+
+            let cell_len = CHORUS_CELL_NOTES.min(verse.notes.len()).min(chorus.notes.len());
+            let cell = RiffMotif::from_notes("cell", &verse.notes[..cell_len], song.key.root, 0.5);
+            let candidates = [cell.clone(), cell.transpose(5), cell.invert(), cell.retrograde()];
+
+            let opening = pitch_classes(&chorus.notes[..cell_len]);
+            assert!(
+                candidates.iter().any(|m| pitch_classes(&m.apply(song.key.root)) == opening),
+                "seed {}: chorus {:?} is not a development of verse {:?}", seed, opening, cell.intervals,
+            );
+        }
+    }
+
+    #[test]
+    fn test_note_velocity_accents_downbeats() {
+        let velocity = |i, chord, muted| MetalSongGenerator::note_velocity(i, chord, muted, SectionIntensity::Medium);
+
+        // Downbeat > other eighth > off-beat sixteenth
+        assert!(velocity(0, ChordType::Single, true) > velocity(2, ChordType::Single, true));
+        assert!(velocity(2, ChordType::Single, true) > velocity(3, ChordType::Single, true));
+        // Open chords ring out louder than muted ones
+        assert!(velocity(0, ChordType::Power, false) > velocity(0, ChordType::Power, true));
+        // Loudest case stays in range; intensity raises the curve
+        assert!(MetalSongGenerator::note_velocity(0, ChordType::Power, false, SectionIntensity::Extreme) <= 1.0);
+        assert!(
+            MetalSongGenerator::note_velocity(1, ChordType::Single, true, SectionIntensity::High)
+                > MetalSongGenerator::note_velocity(1, ChordType::Single, true, SectionIntensity::Low)
+        );
+
+        // Every generated riff carries one velocity per note
+        let generator = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        for section in [MetalSection::Verse, MetalSection::Breakdown, MetalSection::Solo] {
+            let riff = generator.generate_riff(section);
+            assert_eq!(riff.velocities.len(), riff.notes.len(), "{:?}", section);
+        }
+    }
+
+    #[test]
+    fn test_builder_overrides_subgenre_defaults() {
+        let generator = MetalSongGenerator::builder()
+            .subgenre(MetalSubgenre::DoomMetal)
+            .tuning(GuitarTuning::DropA7)
+            .tempo(72)
+            .chaos_level(1.5)
+            .playability_mode(PlayabilityMode::Conservative)
+            .build();
+        let song = generator.generate_song();
+        assert_eq!(song.subgenre, MetalSubgenre::DoomMetal);
+        assert_eq!(song.tuning, GuitarTuning::DropA7);
+        assert_eq!(song.key.root, GuitarTuning::DropA7.lowest_note());
+        assert!(matches!(song.key.scale_type, ScaleType::Dorian));
+        assert_eq!(song.tempo, 72);
+        assert_eq!(generator.chaos_level, 1.0);
+
+        // An explicit key wins over the tuning's root
+        let key = Key { root: 45, scale_type: ScaleType::Phrygian };
+        let generator = MetalSongGenerator::builder().tuning(GuitarTuning::DropA7).key(key).build();
+        assert_eq!(generator.key.root, 45);
+
+        // The same seed builds the same song
+        let seeded = || {
+            let song = MetalSongGenerator::builder().subgenre(MetalSubgenre::ThrashMetal).seed(99).build().generate_song();
+            (song.tempo, song.sections.iter().flat_map(|(_, riff)| riff.notes.clone()).collect::<Vec<_>>())
         };
+        assert_eq!(seeded(), seeded());
+    }
 
-        let use_blast = matches!(
-            (self.subgenre, section),
-            (MetalSubgenre::DeathMetal, MetalSection::Verse) |
-            (MetalSubgenre::DeathMetal, MetalSection::Chorus)
+    #[test]
+    fn test_high_chaos_sabotages_polymetric_grid() {
+        let mut generator = MetalSongGenerator::new(MetalSubgenre::ProgressiveMetal);
+        let bars = 32;
+
+        generator.chaos_level = 0.3;
+        let regular = generator.polymetric_guitar_positions(&mut PolymetricInterference::prog_metal(), bars);
+        assert_eq!(regular, PolymetricInterference::prog_metal().guitar_pattern(bars));
+
+        crate::utils::seed_rng(11);
+        generator.chaos_level = 0.9;
+        let chaotic = generator.polymetric_guitar_positions(&mut PolymetricInterference::prog_metal(), bars);
+        assert_ne!(chaotic, regular);
+        assert!(chaotic.windows(2).all(|w| w[0] < w[1]), "positions stay sorted and unique");
+    }
+
+    #[test]
+    fn test_spiteful_riffs_skip_the_playability_retry() {
+        // An unreachable threshold would normally regenerate and re-finger the riff
+        let mut generator = MetalSongGenerator::with_min_playability(MetalSubgenre::ThrashMetal, 1.0);
+        generator.chaos_level = 0.9;
+
+        crate::utils::seed_rng(21);
+        let first = generator.generate_riff_candidate(MetalSection::Verse);
+        crate::utils::seed_rng(21);
+        let kept = generator.generate_playable_riff(MetalSection::Verse);
+        assert_eq!(kept.notes, first.notes);
+        assert_eq!(kept.fret_positions, first.fret_positions);
+    }
+
+    #[test]
+    fn test_polymetric_rhythms_follow_the_onsets() {
+        crate::utils::seed_rng(4);
+        let mut generator = MetalSongGenerator::new(MetalSubgenre::ProgressiveMetal);
+        generator.chaos_level = 0.3;
+        let riff = generator.generate_polymetric_riff(MetalSection::Verse);
+
+        // 5/16 onsets: a quarter plus a sixteenth rest, the bar-end remainder cut short
+        assert_eq!(riff.notes.len(), riff.rhythms.len());
+        assert_eq!(&riff.rhythms[..2], &[RhythmPattern::QuarterNote, RhythmPattern::Rest]);
+        let sixteenths: f32 = riff.rhythms.iter()
+            .map(|&rhythm| if rhythm == RhythmPattern::Rest { 0.25 } else { rhythm.to_beats() })
+            .sum::<f32>() * 4.0;
+        assert_eq!(sixteenths, 64.0);
+    }
+
+    #[test]
+    fn test_doom_intro_is_marked_clean() {
+        let song = MetalSongGenerator::new(MetalSubgenre::DoomMetal).generate_song_with_memory();
+        for (section, riff) in &song.sections {
+            assert_eq!(riff.clean, *section == MetalSection::Intro, "{:?}", section);
+        }
+
+        let heavy = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        assert!(!heavy.clean_intro);
+        assert!(!heavy.generate_riff(MetalSection::Intro).clean);
+    }
+
+    #[test]
+    fn test_chromatic_intensity_adds_out_of_scale_notes() {
+        use crate::composition::riff_analysis::analyze_riff;
+
+        let average_chromatic = |intensity: f32| {
+            crate::utils::seed_rng(21);
+            let mut generator = MetalSongGenerator::with_key(
+                MetalSubgenre::HeavyMetal,
+                Key { root: 40, scale_type: ScaleType::Minor },
+            );
+            generator.set_chromatic_intensity(intensity);
+            let riffs = 20;
+            (0..riffs)
+                .map(|_| analyze_riff(&generator.generate_riff(MetalSection::Verse), &generator.key).chromatic_ratio)
+                .sum::<f32>()
+                / riffs as f32
+        };
+
+        let tame = average_chromatic(0.0);
+        let cranked = average_chromatic(1.0);
+        assert!(cranked > tame + 0.05, "chromatic share {} at 1.0 vs {} at 0.0", cranked, tame);
+    }
+
+    #[test]
+    fn test_clean_intro_arpeggiates_chord_tones() {
+        let mut generator = MetalSongGenerator::new(MetalSubgenre::DoomMetal);
+        generator.key = Key { root: 40, scale_type: ScaleType::Minor };
+        let riff = generator.generate_riff(MetalSection::Intro);
+
+        assert!(riff.clean);
+        assert!(riff.palm_muted.iter().all(|&muted| !muted));
+        assert!(riff.chord_types.iter().all(|&chord| chord == ChordType::Single));
+        assert!(riff.rhythms.iter().all(|&rhythm| rhythm == RhythmPattern::EighthNote));
+
+        // E minor: i (E G B), VI (C E G), iv (A C E), v (B D F#) as root-fifth-octave-third
+        assert_eq!(
+            riff.notes,
+            vec![52, 59, 64, 55, 60, 67, 72, 64, 57, 64, 69, 60, 59, 66, 71, 62]
         );
+    }
 
-        if use_blast {
-            let (kicks, snares) = generate_blast_beat(BlastBeatStyle::Traditional, subdivisions);
-            
-            for i in 0..subdivisions {
-                if kicks[i] || snares[i] {
-                    let base_velocity = blast_beat_velocity(100, i == 0);
-                    let (velocity, timing) = humanizer.humanize_hit(base_velocity, i == 0);
-                    drum_hits.push((velocity, timing));
-                }
+    #[test]
+    fn test_from_progression_pedals_on_each_sections_chord() {
+        use crate::composition::music_theory::ChordType as ChordQuality;
+
+        let chord = |root, chord_type| Chord { root, chord_type };
+        // Em - C - G - D
+        let progression = [
+            chord(64, ChordQuality::Minor),
+            chord(60, ChordQuality::Major),
+            chord(67, ChordQuality::Major),
+            chord(62, ChordQuality::Major),
+        ];
+        let generator = MetalSongGenerator::from_progression(MetalSubgenre::ThrashMetal, &progression);
+        let lowest = generator.tuning.lowest_note();
+
+        // The first chord sets the key, dropped into the guitar's lowest octave
+        assert_eq!(generator.key.root % 12, 4);
+        assert!(matches!(generator.key.scale_type, ScaleType::Minor));
+        assert!((lowest..lowest + 12).contains(&generator.key.root));
+
+        let key = |section| generator.section_key(section);
+        assert_eq!(key(MetalSection::Verse).root, generator.key.root);
+        assert_eq!(key(MetalSection::Chorus).root % 12, 0);
+        assert!(matches!(key(MetalSection::Chorus).scale_type, ScaleType::Major));
+        assert_eq!(key(MetalSection::Breakdown).root % 12, 7);
+        assert_eq!(key(MetalSection::Solo).root % 12, 2);
+        assert_eq!(key(MetalSection::Outro).root, generator.key.root);
+
+        // Breakdown stabs chug on their chord's root
+        let breakdown = generator.generate_riff(MetalSection::Breakdown);
+        assert!(breakdown.notes.iter().any(|&note| note % 12 == 7), "{:?}", breakdown.notes);
+
+        // Without a progression every section stays on the key
+        let plain = MetalSongGenerator::new(MetalSubgenre::ThrashMetal);
+        assert_eq!(plain.section_key(MetalSection::Solo).root, plain.key.root);
+    }
+
+    #[test]
+    fn test_minor_chords_invert_toward_the_fretting_hand() {
+        let generator = MetalSongGenerator::builder().tuning(GuitarTuning::EStandard).build();
+        let notes = [57, 40, 40];
+
+        // From A on the D string's 7th fret, E minor is closest with B (its fifth) in the bass;
+        // the inverted chord is fretted where its B is, so the next E minor stays on it
+        let mut positions = [FretPosition::new(2, 7), FretPosition::new(0, 0), FretPosition::new(0, 0)];
+        let mut chord_types = [ChordType::Single, ChordType::Minor, ChordType::Minor];
+        generator.choose_inversions(&notes, &mut positions, &mut chord_types);
+        assert_eq!(chord_types[1], ChordType::MinorInversion(Inversion::Second));
+        let b_positions = FretboardPathfinder::new(GuitarTuning::EStandard).get_positions_for_note(47);
+        assert!(b_positions.contains(&positions[1]), "{:?}", positions[1]);
+        assert_eq!(chord_types[2], ChordType::MinorInversion(Inversion::Second));
+        assert_eq!(positions[2], positions[1]);
+
+        // Right after the open E the root position needs no move at all
+        let mut positions = [FretPosition::new(0, 0), FretPosition::new(0, 0)];
+        let mut chord_types = [ChordType::Single, ChordType::Minor];
+        generator.choose_inversions(&notes[1..], &mut positions, &mut chord_types);
+        assert_eq!(chord_types[1], ChordType::Minor);
+        assert_eq!(positions[1], FretPosition::new(0, 0));
+
+        // Power chords and single notes are never inverted
+        let mut positions = [FretPosition::new(2, 7), FretPosition::new(0, 0), FretPosition::new(0, 0)];
+        let mut chord_types = [ChordType::Single, ChordType::Power, ChordType::Single];
+        generator.choose_inversions(&notes, &mut positions, &mut chord_types);
+        assert_eq!(chord_types, [ChordType::Single, ChordType::Power, ChordType::Single]);
+        assert_eq!(positions[1], FretPosition::new(0, 0));
+    }
+
+    #[test]
+    fn test_palm_mute_ratio_sets_share_of_muted_notes() {
+        let mut generator = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        let notes: Vec<MidiNote> = (0..16).map(|i| generator.key.root + [0, 3, 5, 7][i % 4]).collect();
+        let muted_share = |riff: &MetalRiff| {
+            let picked: Vec<bool> = riff
+                .palm_muted
+                .iter()
+                .zip(&riff.rhythms)
+                .filter(|(_, &rhythm)| rhythm != RhythmPattern::Rest)
+                .map(|(&muted, _)| muted)
+                .collect();
+            picked.iter().filter(|&&muted| muted).count() as f32 / picked.len() as f32
+        };
+
+        // Choruses ring open by default; a ratio tightens them up
+        generator.set_palm_mute_ratio(MetalSection::Chorus, Some(1.0));
+        assert_eq!(muted_share(&generator.build_riff_from_notes(notes.clone(), MetalSection::Chorus)), 1.0);
+        generator.set_palm_mute_ratio(MetalSection::Verse, Some(0.0));
+        assert_eq!(muted_share(&generator.build_riff_from_notes(notes.clone(), MetalSection::Verse)), 0.0);
+
+        // Half muted: the pedal notes go first
+        generator.set_palm_mute_ratio(MetalSection::Verse, Some(0.5));
+        let verse = generator.build_riff_from_notes(notes.clone(), MetalSection::Verse);
+        assert!((muted_share(&verse) - 0.5).abs() <= 0.1, "{}", muted_share(&verse));
+        for (i, &note) in notes.iter().enumerate() {
+            if note == generator.key.root && verse.rhythms[i] != RhythmPattern::Rest {
+                assert!(verse.palm_muted[i], "pedal note {} left open", i);
             }
-        } else {
-            for i in 0..subdivisions {
-                let is_accent = i % 4 == 0;
-                let base_velocity = if is_accent { 110 } else { 95 };
-                let (velocity, timing) = humanizer.humanize_hit(base_velocity, is_accent);
-                drum_hits.push((velocity, timing));
+        }
+
+        // Clearing the ratio restores the section's own voicing
+        generator.set_palm_mute_ratio(MetalSection::Chorus, None);
+        assert_eq!(muted_share(&generator.build_riff_from_notes(notes, MetalSection::Chorus)), 0.0);
+    }
+
+    #[test]
+    fn test_describe_lists_every_section_with_tab() {
+        crate::utils::seed_rng(11);
+        let song = MetalSongGenerator::new(MetalSubgenre::DeathMetal).generate_song();
+        let plan = song.describe();
+
+        assert!(plan.starts_with("DeathMetal in "));
+        assert!(plan.contains(song.tuning.display_name()));
+        for (i, (section, riff)) in song.sections.iter().enumerate() {
+            assert!(plan.contains(&format!("{}. {:?} (", i + 1, section)), "missing section {}", i + 1);
+            let tab = riff.tab(&song.tuning);
+            assert!(plan.contains(&tab));
+            // Rhythm row, palm-mute row and one row per string for each block of notes
+            let blocks = riff.notes.len().div_ceil(TAB_NOTES_PER_LINE);
+            assert_eq!(tab.lines().count(), blocks * (2 + song.tuning.string_count()));
+            // The drum chart is the renderer's grid (blasts vary, so only the others are pinned)
+            if section.rhythmic_feel_for(song.subgenre) != RhythmicFeel::Blast {
+                assert!(plan.contains(&song.section_drum_grid(*section, Some(riff), song.subgenre).chart()));
             }
         }
+        // Death metal verses blast; breakdowns hit the snare on beat 3 only
+        assert!(plan.contains("Blast feel"));
+        assert!(plan.contains("   Snare  |........x.......|"));
+    }
 
-        drum_hits
+    #[test]
+    fn test_drum_grid_for_breakdown_is_sparse() {
+        crate::utils::seed_rng(4);
+        let song = MetalSongGenerator::new(MetalSubgenre::DeathMetal).generate_song();
+        let bar = DrumGrid::STEPS_PER_BAR;
+
+        let breakdown = song.drum_patterns_for_section(MetalSection::Breakdown, song.subgenre);
+        assert_eq!(breakdown.bars(), MetalSection::Breakdown.default_bars());
+        assert_eq!(breakdown.steps(), breakdown.bars() * bar);
+        // Half time: one snare per bar on beat 3, and a crash on every downbeat
+        assert_eq!(breakdown.hit_steps(&breakdown.snare), (0..breakdown.bars()).map(|b| b * bar + 8).collect::<Vec<_>>());
+        assert!((0..breakdown.bars()).all(|b| breakdown.cymbal[b * bar]));
+
+        // Death metal verses blast: far busier than the breakdown
+        let verse = song.drum_patterns_for_section(MetalSection::Verse, song.subgenre);
+        assert!(verse.hit_steps(&verse.snare).len() > 4 * breakdown.hit_steps(&breakdown.snare).len());
+        assert!(verse.hit_steps(&verse.kick).len() > breakdown.hit_steps(&breakdown.kick).len());
     }
 
-    /// Generate a motif-based riff with chromatic mutations
-    fn generate_motif_based_riff(&self, section: MetalSection) -> MetalRiff {
-        let mut rng = rand::thread_rng();
-        let root = self.key.root;
-        
-        // Select random motif
-        let motif = self.motif_library.random_motif();
-        
-        // Apply motif to root note
-        let base_notes = motif.apply(root);
-        
-        // Apply chromatic mutations for dissonance
-        let mutated_notes = self.chromatic_mutator.apply_mutations(base_notes);
-        
-        // Use aggressive pathfinding if available
-        let _fret_positions = if matches!(self.subgenre, MetalSubgenre::ProgressiveMetal) {
-            self.aggressive_pathfinder.find_aggressive_path(&mutated_notes)
-        } else {
-            self.aggressive_pathfinder.find_playable_path(&mutated_notes)
-        };
-        
-        // Build riff from mutated notes
-        self.build_riff_from_notes(mutated_notes, section)
+    #[test]
+    fn test_rhythm_engine_without_a_generator() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        for subgenre in MetalSubgenre::ALL {
+            let verse = RhythmEngine::generate(subgenre, MetalSection::Verse, 32, &mut StdRng::seed_from_u64(3));
+            assert_eq!(verse.len(), 32);
+            // Same seed, same rhythm
+            assert_eq!(verse, RhythmEngine::generate(subgenre, MetalSection::Verse, 32, &mut StdRng::seed_from_u64(3)));
+
+            let breakdown = RhythmEngine::generate(subgenre, MetalSection::Breakdown, 8, &mut StdRng::seed_from_u64(3));
+            assert!(breakdown.iter().step_by(2).all(|&r| r == RhythmPattern::QuarterNote));
+            assert!(breakdown.iter().skip(1).step_by(2).all(|&r| r == RhythmPattern::Rest));
+        }
+
+        // Doom never picks faster than eighths; death bursts into 32nds
+        let mut rng = StdRng::seed_from_u64(9);
+        let doom = RhythmEngine::generate(MetalSubgenre::DoomMetal, MetalSection::Verse, 64, &mut rng);
+        assert!(doom.iter().all(|r| matches!(r, RhythmPattern::Rest | RhythmPattern::QuarterNote | RhythmPattern::EighthNote)));
+        let death = RhythmEngine::generate(MetalSubgenre::DeathMetal, MetalSection::Verse, 64, &mut rng);
+        assert!(death.contains(&RhythmPattern::ThirtySecondNote));
     }
 
-    /// Generate a breakdown riff with syncopated silences and dotted-eighth stabs
-    fn generate_breakdown_riff(&self) -> MetalRiff {
-        let root = self.key.root;
-        
-        // Generate breakdown pattern with syncopated silences
-        let pattern = self.breakdown_generator.generate_breakdown_pattern(root, 2);
-        
-        let mut notes = Vec::new();
-        let mut rhythms = Vec::new();
-        let mut palm_muted = Vec::new();
-        let mut chord_types = Vec::new();
-        
-        for (_pos, note, _duration_mult, is_silent) in pattern {
-            if !is_silent {
-                notes.push(note);
-                rhythms.push(RhythmPattern::DottedEighth); // Dotted eighth stabs
-                palm_muted.push(true); // Heavy palm muting
-                chord_types.push(ChordType::Power); // Power chords
+    #[test]
+    fn test_riff_below_the_tuning_stays_aligned() {
+        let generator = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        let lowest = generator.tuning.string_notes()[0];
+        let notes = vec![lowest, lowest - 5, lowest + 7, lowest - 1];
+
+        let riff = generator.build_riff_from_notes(notes, MetalSection::Verse);
+        assert_eq!(riff.notes, vec![lowest, lowest + 7, lowest + 7, lowest + 11]);
+        for len in [riff.rhythms.len(), riff.palm_muted.len(), riff.chord_types.len(), riff.fret_positions.len()] {
+            assert_eq!(len, riff.notes.len());
+        }
+        let string_notes = generator.tuning.string_notes();
+        for (pos, &note) in riff.fret_positions.iter().zip(&riff.notes) {
+            assert_eq!(string_notes[pos.string as usize] + pos.fret, note);
+        }
+    }
+
+    #[test]
+    fn test_no_generated_section_is_silent() {
+        for subgenre in MetalSubgenre::ALL {
+            for seed in 0..8 {
+                crate::utils::seed_rng(seed);
+                let song = MetalSongGenerator::new(subgenre).generate_song_with_memory();
+                for (section, riff) in &song.sections {
+                    assert!(riff.is_audible(), "{:?} {:?} (seed {}): {:?}", subgenre, section, seed, riff.rhythms);
+                }
             }
         }
-        
-        // Ensure we have at least some notes
-        if notes.is_empty() {
-            notes = vec![root; 4];
-            rhythms = vec![RhythmPattern::QuarterNote; 4];
-            palm_muted = vec![true; 4];
-            chord_types = vec![ChordType::Power; 4];
+
+        // A riff of nothing but rests gets its downbeats back
+        let mut riff = MetalSongGenerator::new(MetalSubgenre::DoomMetal).generate_riff(MetalSection::Verse);
+        riff.rhythms = vec![RhythmPattern::Rest; riff.notes.len()];
+        assert!(!riff.is_audible());
+        riff.fill_rests();
+        let required = (riff.notes.len() as f32 * MIN_AUDIBLE_SHARE).ceil() as usize;
+        assert!(riff.is_audible());
+        assert_eq!(riff.audible_notes(), required);
+        assert!(riff.rhythms.iter().step_by(4).take(required).all(|&r| r != RhythmPattern::Rest));
+    }
+
+    #[test]
+    fn test_with_key_keeps_custom_scale() {
+        let key = Key { root: 38, scale_type: ScaleType::Locrian };
+        let song = MetalSongGenerator::with_key(MetalSubgenre::DeathMetal, key).generate_song();
+
+        assert_eq!(song.key.root, 38);
+        assert!(matches!(song.key.scale_type, ScaleType::Locrian));
+        // Subgenre behaviour is untouched
+        let (min_tempo, max_tempo) = MetalSubgenre::DeathMetal.tempo_range();
+        assert!((min_tempo..=max_tempo).contains(&song.tempo));
+        assert_eq!(song.tuning, MetalSubgenre::DeathMetal.default_tuning());
+    }
+
+    #[test]
+    fn test_song_metadata_serializes_sections() {
+        let song = MetalSongGenerator::new(MetalSubgenre::HeavyMetal).generate_song();
+        let metadata = MetalSongMetadata::from_song(&song);
+
+        assert_eq!(metadata.sections.len(), song.sections.len());
+        assert_eq!(metadata.tuning_notes, song.tuning.string_notes());
+        assert_eq!(metadata.tuning_note_names.len(), song.tuning.string_count());
+        for (summary, (_, riff)) in metadata.sections.iter().zip(&song.sections) {
+            assert_eq!(summary.rhythm_histogram.values().sum::<usize>(), riff.rhythms.len());
         }
-        
-        MetalRiff {
-            notes,
-            palm_muted,
-            chord_types,
-            rhythms,
-            playability_score: 0.8,
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["subgenre"], "HeavyMetal");
+        assert_eq!(json["sections"][0]["section"], "Intro");
+        assert!(json["key"]["root"].is_number());
+    }
+
+    #[test]
+    fn test_prechorus_climbs_within_the_scale() {
+        let generator = MetalSongGenerator::new(MetalSubgenre::HeavyMetal);
+        let intervals = ScaleType::Minor.intervals();
+        let notes = generator.generate_prechorus_sequence(45, ScaleType::Minor, 16);
+
+        assert_eq!(notes.len(), 16);
+        for &note in &notes {
+            let pitch_class = (note as i32 - 45).rem_euclid(12) as u8;
+            assert!(intervals.contains(&pitch_class), "{} is outside A minor", note_name(note));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_layout_for_duration_scales_repeats() {
+        let count = |layout: &[MetalSection], kind: MetalSection| layout.iter().filter(|&&s| s == kind).count();
+
+        for target in [60.0, 180.0, 300.0, 600.0] {
+            let layout = MetalSongGenerator::layout_for_duration(target, 20.0);
+            assert_eq!(layout.first(), Some(&MetalSection::Intro));
+            assert_eq!(layout.last(), Some(&MetalSection::Outro));
+            assert_eq!(count(&layout, MetalSection::Intro), 1);
+            assert_eq!(count(&layout, MetalSection::Outro), 1);
+            assert!((layout.len() as f32 * 20.0 - target).abs() <= 20.0, "{} sections for {}s", layout.len(), target);
+        }
+
+        // Longer targets get more verse/chorus repeats
+        let short = MetalSongGenerator::layout_for_duration(180.0, 20.0);
+        let long = MetalSongGenerator::layout_for_duration(600.0, 20.0);
+        assert!(count(&long, MetalSection::Chorus) > count(&short, MetalSection::Chorus));
+
+        // Every chorus is set up by a pre-chorus
+        for layout in [&short, &long] {
+            assert!(layout.windows(2).all(|w| (w[1] == MetalSection::Chorus) == (w[0] == MetalSection::PreChorus)));
+        }
+
+        // Too short for anything but the minimum song
+        assert_eq!(
+            MetalSongGenerator::layout_for_duration(10.0, 20.0),
+            vec![MetalSection::Intro, MetalSection::Verse, MetalSection::Chorus, MetalSection::Outro]
+        );
+    }
+}
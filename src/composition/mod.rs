@@ -5,12 +5,15 @@ pub mod tuning;         // Guitar tunings for metal
 pub mod rhythm;         // Euclidean and polymetric rhythms
 pub mod riff_generator; // Markov chains and pedal point riff generation
 pub mod riff_motifs;    // Riff motif system for asymmetric patterns
+pub mod riff_analysis;  // Scale/dissonance/pedal analysis of generated riffs
 pub mod fretboard;      // Fretboard pathfinding for playable riffs
 pub mod drum_humanizer; // Drum humanization for realistic metal drums
+pub mod guitar_humanizer; // Onset jitter and note-length feel for rhythm guitar
 pub mod drum_articulations; // Drum articulation patterns (ghost notes, flams, etc.)
 pub mod breakdown_generator;
 pub mod bar_memory;
 pub mod phrase_drums;
+pub mod drum_patterns;   // Section kick/snare/cymbal grids per rhythmic feel
 pub mod metal_song_generator; // Complete metal song generation
 pub mod bass_generator; // Bass line generation for metal
 
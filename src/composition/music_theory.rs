@@ -1,17 +1,18 @@
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 /// Musical note represented as MIDI number (C4 = 60)
 pub type MidiNote = u8;
 
 /// Represents a musical key
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct Key {
     pub root: MidiNote,
     pub scale_type: ScaleType,
 }
 
 /// Types of musical scales
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum ScaleType {
     Major,
     Minor,
@@ -31,6 +32,25 @@ pub enum ScaleType {
     DoubleHarmonicMajor, // Byzantine scale - METAL DJENT/PROG
 }
 
+/// Diatonic harmony interval for doubled lead lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Third, // Twin-guitar harmony (Iron Maiden style)
+    Fifth, // Power-chord style doubling
+    Sixth, // Sweeter, inverted third
+}
+
+impl Interval {
+    /// Number of scale degrees above the melody note
+    pub fn scale_steps(&self) -> usize {
+        match self {
+            Interval::Third => 2,
+            Interval::Fifth => 4,
+            Interval::Sixth => 5,
+        }
+    }
+}
+
 /// Represents a chord
 #[derive(Debug, Clone)]
 pub struct Chord {
@@ -91,12 +111,19 @@ impl ScaleType {
             ScaleType::DoubleHarmonicMajor => vec![0, 1, 4, 5, 7, 8, 11], // 1, b2, 3, 4, 5, b6, 7
         }
     }
+
+    /// Seven-note scale with an augmented second between neighbouring degrees
+    /// (harmonic minor, Phrygian dominant, double harmonic)
+    pub fn has_augmented_second(&self) -> bool {
+        let intervals = self.intervals();
+        intervals.len() == 7 && intervals.windows(2).any(|pair| pair[1] - pair[0] == 3)
+    }
 }
 
 impl Key {
     /// Create a key from a specific scale type
     pub fn from_scale(scale_type: ScaleType) -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let roots = vec![36, 38, 40, 41, 43, 45, 46]; // C, D, E, F, G, A, Bb
         Key {
             root: roots[rng.gen_range(0..roots.len())],
@@ -106,7 +133,7 @@ impl Key {
 
     /// Create a random key suitable for funk/jazz
     pub fn random_funky() -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
 
         // Funky keys often use flats and tend toward minor/dorian
         let roots = vec![
@@ -177,6 +204,32 @@ impl Key {
         interval == 1 || interval == 6  // Minor second (b2) or tritone (b5)
     }
 
+    /// Harmonize a note diatonically: move it up `interval` scale degrees within this key.
+    /// Notes outside the scale keep their chromatic offset from the scale note below them.
+    pub fn harmonize(&self, note: MidiNote, interval: Interval) -> MidiNote {
+        self.step_degrees(note, interval.scale_steps())
+    }
+
+    /// Move a note up `steps` scale degrees within this key (see `harmonize`)
+    pub fn step_degrees(&self, note: MidiNote, steps: usize) -> MidiNote {
+        let intervals = self.scale_type.intervals();
+        let degrees = intervals.len() as i32;
+        let relative = note as i32 - self.root as i32;
+        let octave = relative.div_euclid(12);
+        let pitch_class = relative.rem_euclid(12) as u8;
+
+        // Scale degree at or below the note
+        let degree = intervals.iter().rposition(|&i| i <= pitch_class).unwrap_or(0) as i32;
+        let chromatic_offset = pitch_class as i32 - intervals[degree as usize] as i32;
+
+        let target = degree + steps as i32;
+        let target_octave = octave + target.div_euclid(degrees);
+        let target_interval = intervals[target.rem_euclid(degrees) as usize] as i32;
+
+        let harmonized = self.root as i32 + target_octave * 12 + target_interval + chromatic_offset;
+        harmonized.clamp(0, 127) as MidiNote
+    }
+
     /// Get dissonance weight for metal riff generation (higher = more dissonant = more metal)
     pub fn get_dissonance_weight(interval: u8) -> f32 {
         match interval {
@@ -192,6 +245,26 @@ impl Key {
     }
 }
 
+impl ChordType {
+    /// Scale a riff over this chord stays in (chord-scale), or None when the chord
+    /// has no third to decide between major and minor (sus, power chords)
+    pub fn scale(&self) -> Option<ScaleType> {
+        match self {
+            ChordType::Major | ChordType::Major7 | ChordType::Major9 | ChordType::Major11 | ChordType::Major13
+            | ChordType::Add9 | ChordType::Sixth => Some(ScaleType::Major),
+            ChordType::Minor | ChordType::Minor7 | ChordType::Minor9 | ChordType::Minor11 | ChordType::Minor6 => {
+                Some(ScaleType::Minor)
+            },
+            ChordType::MinorMajor7 => Some(ScaleType::HarmonicMinor),
+            ChordType::Dominant7 | ChordType::Dominant9 | ChordType::Dominant13 | ChordType::Dominant7Sharp9
+            | ChordType::Dominant7Flat9 => Some(ScaleType::Mixolydian),
+            ChordType::Diminished | ChordType::HalfDiminished7 => Some(ScaleType::Locrian),
+            ChordType::Augmented => Some(ScaleType::WholeTone),
+            ChordType::Sus4 | ChordType::Sus2 | ChordType::Power5 => None,
+        }
+    }
+}
+
 impl Chord {
     /// Get the notes that make up this chord
     pub fn get_notes(&self) -> Vec<MidiNote> {
@@ -243,12 +316,12 @@ pub fn generate_chord_progression_with_types(
     length: usize,
     preferred_types: Option<&[ChordType]>,
 ) -> Vec<Chord> {
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
     let scale_notes = key.get_scale_notes();
 
     // Helper to select chord type, preferring preferred types if available
     let select_chord_type =
-        |rng: &mut rand::rngs::ThreadRng, _degree: usize, default: ChordType| -> ChordType {
+        |rng: &mut crate::utils::SongRng, _degree: usize, default: ChordType| -> ChordType {
             if let Some(preferred) = preferred_types {
                 if !preferred.is_empty() {
                     // 70% chance to use preferred type, 30% chance for default
@@ -351,9 +424,84 @@ pub fn generate_chord_progression_with_types(
     progression
 }
 
-/// Convert MIDI note to frequency in Hz
+/// Pitch-class names, sharps only (index = MIDI note % 12)
+pub const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Pitch-class name of a MIDI note, without the octave ("E", "F#")
+pub fn note_name(midi_note: MidiNote) -> &'static str {
+    NOTE_NAMES[midi_note as usize % 12]
+}
+
+/// Concert pitch everything is tuned to unless a `PitchStandard` says otherwise
+pub const DEFAULT_A4: f32 = 440.0;
+
+/// How the twelve notes of the octave are spaced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Temperament {
+    /// Twelve identical semitones (every key sounds the same)
+    #[default]
+    Equal,
+    /// 5-limit just intonation from the tonic: pure thirds and fifths in the home key
+    Just,
+    /// Quarter-comma meantone from the tonic: pure major thirds, narrow fifths
+    Meantone,
+}
+
+impl Temperament {
+    /// Deviation from equal temperament in cents, per semitone above the tonic
+    fn cents(&self) -> [f32; 12] {
+        match self {
+            Temperament::Equal => [0.0; 12],
+            // 1, 16/15, 9/8, 6/5, 5/4, 4/3, 45/32, 3/2, 8/5, 5/3, 9/5, 15/8
+            Temperament::Just => [0.0, 11.73, 3.91, 15.64, -13.69, -1.96, -9.78, 1.96, 13.69, -15.64, 17.6, -11.73],
+            Temperament::Meantone => [0.0, -24.04, -6.84, 10.26, -13.69, 3.42, -20.53, -3.42, -27.37, -10.26, 6.84, -17.11],
+        }
+    }
+}
+
+/// Reference pitch and temperament used to turn MIDI notes into frequencies.
+/// A4 always sounds at `a4`; unequal temperaments are laid out from `tonic`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitchStandard {
+    pub a4: f32,
+    pub temperament: Temperament,
+    pub tonic: MidiNote, // Only the pitch class matters
+}
+
+impl PitchStandard {
+    /// Equal temperament at the given concert pitch (e.g. 432.0)
+    pub fn new(a4: f32) -> Self {
+        Self { a4, temperament: Temperament::Equal, tonic: 69 }
+    }
+
+    /// Temper the octave from `tonic` (usually the song's key root)
+    pub fn with_temperament(mut self, temperament: Temperament, tonic: MidiNote) -> Self {
+        self.temperament = temperament;
+        self.tonic = tonic;
+        self
+    }
+
+    /// Frequency of a MIDI note in Hz
+    pub fn frequency(&self, midi_note: MidiNote) -> f32 {
+        let cents = self.temperament.cents();
+        let degree = |note: MidiNote| (note as usize + 12 - self.tonic as usize % 12) % 12;
+        // Shift so A stays on the reference pitch whatever the tonic
+        let offset = cents[degree(midi_note)] - cents[degree(69)];
+        self.a4 * 2.0_f32.powf((midi_note as f32 - 69.0 + offset / 100.0) / 12.0)
+    }
+}
+
+impl Default for PitchStandard {
+    fn default() -> Self {
+        Self::new(DEFAULT_A4)
+    }
+}
+
+/// Convert MIDI note to frequency in Hz (A4 = 440 Hz, equal temperament;
+/// see `PitchStandard` for other tunings)
 pub fn midi_to_freq(midi_note: MidiNote) -> f32 {
-    440.0 * 2.0_f32.powf((midi_note as f32 - 69.0) / 12.0)
+    PitchStandard::default().frequency(midi_note)
 }
 
 /// Tempo in BPM
@@ -371,7 +519,7 @@ impl Tempo {
 
     /// Generate a random tempo within a specific range
     pub fn random_funky_range(min_bpm: f32, max_bpm: f32) -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         Tempo {
             bpm: rng.gen_range(min_bpm..max_bpm),
         }
@@ -411,9 +559,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_harmonize_diatonic_thirds() {
+        let a_minor = Key { root: 45, scale_type: ScaleType::Minor };
+        assert_eq!(a_minor.harmonize(45, Interval::Third), 48); // A -> C (minor third)
+        assert_eq!(a_minor.harmonize(48, Interval::Third), 52); // C -> E (major third)
+        assert_eq!(a_minor.harmonize(55, Interval::Third), 59); // G -> B, wraps past the octave
+        assert_eq!(a_minor.harmonize(45, Interval::Fifth), 52); // A -> E
+        assert_eq!(a_minor.harmonize(57, Interval::Third), 60); // Octave above keeps shape
+    }
+
     #[test]
     fn test_midi_to_freq() {
         assert!((midi_to_freq(69) - 440.0).abs() < 0.01);
         assert!((midi_to_freq(60) - 261.63).abs() < 0.01);
     }
+
+    #[test]
+    fn test_pitch_standards() {
+        let a432 = PitchStandard::new(432.0);
+        assert!((a432.frequency(69) - 432.0).abs() < 0.01);
+        assert!((a432.frequency(57) - 216.0).abs() < 0.01);
+
+        // Just intonation in E: the fifth is a pure 3:2, the major third a pure 5:4,
+        // and A stays on the reference
+        let just = PitchStandard::default().with_temperament(Temperament::Just, 40);
+        assert!((just.frequency(47) / just.frequency(40) - 1.5).abs() < 1e-4);
+        assert!((just.frequency(44) / just.frequency(40) - 1.25).abs() < 1e-4);
+        assert!((just.frequency(69) - 440.0).abs() < 0.01);
+
+        // Meantone: pure major thirds, fifths slightly narrow
+        let meantone = PitchStandard::default().with_temperament(Temperament::Meantone, 60);
+        assert!((meantone.frequency(64) / meantone.frequency(60) - 1.25).abs() < 1e-4);
+        assert!(meantone.frequency(67) / meantone.frequency(60) < 1.5);
+    }
 }
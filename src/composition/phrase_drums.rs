@@ -19,13 +19,14 @@ pub struct DrumHit {
     pub is_accent: bool,
 }
 
-/// Guitar context extracted from a riff
-#[derive(Debug, Clone)]
+/// Guitar context extracted from a riff (the default is no guitar at all)
+#[derive(Debug, Clone, Default)]
 pub struct GuitarContext {
     pub palm_mute_density: f32,      // 0.0 to 1.0
     pub riff_contour: Vec<i8>,       // Pitch deltas between notes
     pub interval_stress: Vec<bool>,  // True for dissonant intervals
     pub bar_accents: Vec<usize>,     // Strong beat positions
+    pub accent_steps: Vec<usize>,    // 16th-grid onsets of palm-muted chugs
     pub note_count: usize,
 }
 
@@ -60,11 +61,21 @@ impl GuitarContext {
             bar_accents.push(i);
         }
 
+        // Place each pick on the 16th grid by its rhythm (gallops count all three);
+        // palm-muted chugs are the accents
+        let accent_steps = riff
+            .onset_beats()
+            .into_iter()
+            .filter(|&(i, _)| riff.palm_muted.get(i).copied().unwrap_or(false))
+            .map(|(_, beats)| (beats * 4.0f32).round() as usize)
+            .collect();
+
         GuitarContext {
             palm_mute_density,
             riff_contour,
             interval_stress,
             bar_accents,
+            accent_steps,
             note_count: riff.notes.len(),
         }
     }
@@ -87,7 +98,7 @@ impl PhraseAwareDrumGenerator {
     /// Sync kick accents with guitar palm-mute chugs
     pub fn accent_with_chugs(&self, context: &GuitarContext) -> Vec<DrumHit> {
         let mut hits = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
 
         // High palm-mute density = more kick accents
         let accent_probability = context.palm_mute_density * 0.8;
@@ -107,10 +118,19 @@ impl PhraseAwareDrumGenerator {
         hits
     }
 
+    /// Kick grid of `steps` 16ths with a kick under every guitar accent (rhythmic unison)
+    pub fn kick_unison(&self, context: &GuitarContext, steps: usize) -> Vec<bool> {
+        let mut kicks = vec![false; steps];
+        for &step in context.accent_steps.iter().filter(|&&step| step < steps) {
+            kicks[step] = true;
+        }
+        kicks
+    }
+
     /// Generate fill before riff transition
     pub fn fill_before_transition(&self, next_bar_intensity: f32) -> Vec<DrumHit> {
         let mut hits = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
 
         // Fill density based on next bar intensity
         let fill_notes = (4.0 + next_bar_intensity * 8.0) as usize;
@@ -165,7 +185,7 @@ impl PhraseAwareDrumGenerator {
     /// Generate complete drum pattern reacting to guitar phrase
     pub fn generate_reactive_pattern(&self, context: &GuitarContext, intensity: f32) -> Vec<DrumHit> {
         let mut pattern = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
 
         // Base kick pattern synced with palm mutes
         let kick_hits = self.accent_with_chugs(context);
@@ -220,7 +240,11 @@ mod tests {
             palm_muted: vec![true, true, false, false],
             chord_types: vec![ChordType::Power; 4],
             rhythms: vec![RhythmPattern::QuarterNote; 4],
+            velocities: Vec::new(),
+            fret_positions: Vec::new(),
+            harmony_notes: None,
             playability_score: 0.8,
+            clean: false,
         };
 
         let context = GuitarContext::from_riff(&riff);
@@ -248,6 +272,7 @@ mod tests {
             riff_contour: vec![],
             interval_stress: vec![],
             bar_accents: vec![0, 4, 8, 12],
+            accent_steps: vec![0, 2, 8],
             note_count: 16,
         };
 
@@ -11,40 +11,25 @@ pub fn euclidean_rhythm(steps: usize, pulses: usize) -> Vec<bool> {
     }
 
     // Bjorklund's algorithm: distribute pulses as evenly as possible
-    // Start with pulses as [1] and rests as [0]
-    let mut groups: Vec<Vec<bool>> = Vec::new();
-    
-    // Initialize with 'pulses' groups of [true] and 'steps - pulses' groups of [false]
-    for _ in 0..pulses {
-        groups.push(vec![true]);
-    }
-    for _ in 0..(steps - pulses) {
-        groups.push(vec![false]);
-    }
-
-    // Bjorklund's pairing algorithm
-    let mut count = pulses.min(steps - pulses);
-    
-    while count > 1 {
-        let mut i = 0;
-        
-        // Pair groups: take from the end and append to the beginning
-        for _ in 0..count {
-            if i + count < groups.len() {
-                let mut combined = groups[i].clone();
-                combined.extend(&groups[i + count]);
-                groups[i] = combined;
-            }
-            i += 1;
+    // Start with 'pulses' groups of [true] and 'steps - pulses' groups of [false]
+    let mut groups: Vec<Vec<bool>> = vec![vec![true]; pulses];
+    let mut remainder: Vec<Vec<bool>> = vec![vec![false]; steps - pulses];
+
+    // Bjorklund's pairing algorithm: append one remainder group to each group
+    // until at most one remainder group is left
+    while remainder.len() > 1 {
+        let count = groups.len().min(remainder.len());
+        let leftover = if groups.len() > count {
+            groups.split_off(count)
+        } else {
+            remainder.split_off(count)
+        };
+        for (group, tail) in groups.iter_mut().zip(&remainder) {
+            group.extend(tail);
         }
-        
-        // Remove the paired groups from the end
-        groups.truncate(groups.len() - count);
-        
-        // Calculate new count for next iteration
-        let remainder = groups.len() - count;
-        count = count.min(remainder);
+        remainder = leftover;
     }
+    groups.extend(remainder);
 
     // Flatten the result
     groups.into_iter().flatten().collect()
@@ -368,10 +353,17 @@ impl PolymetricInterference {
             && position % self.snare_meter == 0
     }
 
+    /// Every 16th-step index in `bars` where guitar, kick and snare realign
+    /// (e.g. for placing crashes on the resolution)
+    pub fn alignment_points(&self, bars: usize) -> Vec<usize> {
+        // All three meters divide a position exactly when their LCM does
+        (0..bars * 16).step_by(self.resolution_point().max(1)).collect()
+    }
+
     /// CHAOS: Partial reset - reset guitar meter but keep drums going
     pub fn partial_reset(&mut self, bar_num: usize) -> bool {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         // Every 4-8 bars, 30% chance of partial reset
         if bar_num % rng.gen_range(4..=8) == 0 && rng.gen_bool(0.3) {
@@ -405,7 +397,7 @@ impl PolymetricInterference {
     /// CHAOS: Sudden meter drop (7 → 3, 5 → 2)
     pub fn sudden_drop(&mut self) -> usize {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         let old_meter = self.guitar_meter;
         
@@ -422,7 +414,7 @@ impl PolymetricInterference {
     /// CHAOS: Generate a bar that completely ignores meter
     pub fn chaos_bar(&self) -> Vec<usize> {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut positions = Vec::new();
         
         // Random number of hits (3-11)
@@ -441,7 +433,7 @@ impl PolymetricInterference {
     /// Generate chaotic guitar pattern with controlled sabotage
     pub fn chaotic_guitar_pattern(&mut self, bars: usize) -> Vec<usize> {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut all_positions = Vec::new();
         
         for bar in 0..bars {
@@ -491,6 +483,14 @@ mod tests {
         let rhythm = euclidean_rhythm(16, 5);
         assert_eq!(rhythm.len(), 16);
         assert_eq!(rhythm.iter().filter(|&&x| x).count(), 5);
+
+        // Longer patterns keep their length (the prog riff rhythms rely on it)
+        for (steps, pulses) in [(32, 7), (64, 7), (24, 5), (13, 5)] {
+            let rhythm = euclidean_rhythm(steps, pulses);
+            assert_eq!(rhythm.len(), steps);
+            assert_eq!(rhythm.iter().filter(|&&x| x).count(), pulses);
+        }
+        assert_eq!(euclidean_rhythm(8, 3), vec![true, false, false, true, false, false, true, false]);
     }
 
     #[test]
@@ -523,6 +523,17 @@ mod tests {
         assert_eq!(poly.bars_to_resolution(), 5);
     }
 
+    #[test]
+    fn test_alignment_points_match_is_aligned() {
+        let poly = PolymetricInterference::prog_metal();
+        let bars = poly.bars_to_resolution() * 3;
+        let points = poly.alignment_points(bars);
+
+        let expected: Vec<usize> = (0..bars * 16).filter(|&pos| poly.is_aligned(pos)).collect();
+        assert_eq!(points, expected);
+        assert_eq!(points[..3], [0, 20, 40]);
+    }
+
     #[test]
     fn test_lcm_gcd() {
         assert_eq!(gcd(12, 8), 4);
@@ -50,8 +50,29 @@ pub fn generate_euclidean_pattern(steps: usize, pulses: usize) -> Vec<bool> {
     pattern
 }
 
+/// Continuous 16th-note double bass with `gaps` rests per bar for phrasing.
+/// Each rest falls on the last 16th of an evenly spaced segment, so the feet
+/// breathe just before a beat (1 gap = before the next downbeat, 2 = before beats 3 and 1).
+pub fn generate_double_bass_pattern(steps: usize, gaps: usize) -> Vec<bool> {
+    let mut pattern = vec![true; steps];
+    let gaps = gaps.min(16);
+    if gaps == 0 {
+        return pattern;
+    }
+
+    let segment = 16 / gaps;
+    for (i, hit) in pattern.iter_mut().enumerate() {
+        let pos = i % 16;
+        if pos % segment == segment - 1 && pos / segment < gaps {
+            *hit = false;
+        }
+    }
+
+    pattern
+}
+
 /// Generate double bass drum pattern (kick on every 16th note or every 8th)
-pub fn generate_double_bass_pattern(steps: usize, intensity: f32) -> Vec<bool> {
+pub fn generate_double_bass_by_intensity(steps: usize, intensity: f32) -> Vec<bool> {
     let mut pattern = vec![false; steps];
     
     if intensity > 0.8 {
@@ -0,0 +1,94 @@
+//! Read-only analysis of a generated riff against its key: how much of it is in scale,
+//! how dissonant its moves are and how often it returns to the pedal tone.
+//! Useful for seeing what a given `ChromaticMutator` intensity actually produced.
+
+use crate::composition::metal_song_generator::{MetalRiff, RhythmPattern};
+use crate::composition::music_theory::{Key, MidiNote};
+use serde::Serialize;
+
+/// Summary of a riff's pitch content (rests are ignored)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiffAnalysis {
+    pub note_count: usize,
+    /// Moves between consecutive notes, by interval class (index = semitones mod 12)
+    pub interval_histogram: [usize; 12],
+    /// Share of notes in the key's scale (0.0-1.0)
+    pub in_scale_ratio: f32,
+    /// Share of notes outside it (0.0-1.0)
+    pub chromatic_ratio: f32,
+    /// Share of moves that are a minor second or tritone (`Key::is_dissonant`)
+    pub dissonance: f32,
+    /// Share of notes on the key's root, in any octave
+    pub pedal_ratio: f32,
+}
+
+/// Analyze `riff` against `key`
+pub fn analyze_riff(riff: &MetalRiff, key: &Key) -> RiffAnalysis {
+    let notes: Vec<MidiNote> = riff
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| riff.rhythms.get(i) != Some(&RhythmPattern::Rest))
+        .map(|(_, &note)| note)
+        .collect();
+
+    let intervals = key.scale_type.intervals();
+    let pitch_class = |note: MidiNote| (note as i16 - key.root as i16).rem_euclid(12) as u8;
+    let share = |count: usize, total: usize| if total == 0 { 0.0 } else { count as f32 / total as f32 };
+
+    let mut interval_histogram = [0; 12];
+    for pair in notes.windows(2) {
+        interval_histogram[Key::calculate_interval(pair[0], pair[1]) as usize] += 1;
+    }
+    let moves = notes.len().saturating_sub(1);
+    let dissonant = notes.windows(2).filter(|pair| Key::is_dissonant(pair[0], pair[1])).count();
+    let in_scale = notes.iter().filter(|&&note| intervals.contains(&pitch_class(note))).count();
+    let pedal = notes.iter().filter(|&&note| pitch_class(note) == 0).count();
+
+    let in_scale_ratio = share(in_scale, notes.len());
+    RiffAnalysis {
+        note_count: notes.len(),
+        interval_histogram,
+        in_scale_ratio,
+        chromatic_ratio: if notes.is_empty() { 0.0 } else { 1.0 - in_scale_ratio },
+        dissonance: share(dissonant, moves),
+        pedal_ratio: share(pedal, notes.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composition::metal_song_generator::ChordType;
+    use crate::composition::music_theory::ScaleType;
+
+    #[test]
+    fn test_analyze_riff_counts_pedal_and_chromatic_notes() {
+        // E minor pedal riff with a b2 (F) and a tritone (Bb) thrown in
+        let notes = vec![40, 40, 41, 40, 46, 40, 43, 40, 0];
+        let mut rhythms = vec![RhythmPattern::SixteenthNote; 8];
+        rhythms.push(RhythmPattern::Rest);
+        let riff = MetalRiff {
+            chord_types: vec![ChordType::Single; notes.len()],
+            palm_muted: vec![true; notes.len()],
+            notes,
+            rhythms,
+            velocities: Vec::new(),
+            fret_positions: Vec::new(),
+            harmony_notes: None,
+            playability_score: 1.0,
+            clean: false,
+        };
+        let key = Key { root: 40, scale_type: ScaleType::Minor };
+
+        let analysis = analyze_riff(&riff, &key);
+        assert_eq!(analysis.note_count, 8);
+        assert_eq!(analysis.in_scale_ratio, 0.75);
+        assert_eq!(analysis.chromatic_ratio, 0.25);
+        assert_eq!(analysis.pedal_ratio, 0.625);
+        assert_eq!(analysis.interval_histogram[1], 2);
+        assert_eq!(analysis.interval_histogram[6], 2);
+        assert_eq!(analysis.interval_histogram[3], 2);
+        assert!((analysis.dissonance - 4.0 / 7.0).abs() < 1e-6);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::composition::music_theory::{Key, MidiNote};
+use crate::composition::music_theory::{Chord, ChordType, Key, MidiNote};
+use rand::rngs::StdRng;
 use rand::Rng;
 use std::collections::HashMap;
 
@@ -31,7 +32,7 @@ impl MarkovChain {
 
     /// Get the next note based on transition probabilities
     pub fn next_note(&mut self) -> u8 {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         
         if let Some(transitions) = self.transitions.get(&self.current_state) {
             // Normalize probabilities
@@ -149,6 +150,45 @@ impl MetalMarkovPresets {
         
         chain
     }
+
+    /// Create a neoclassical transition matrix for exotic scales
+    /// (harmonic minor, Phrygian dominant, double harmonic)
+    /// Weights moves by their size in semitones rather than by scale degree,
+    /// so the augmented second (e.g. b2 -> 3) and half-step resolutions dominate
+    pub fn neoclassical(key: &Key) -> MarkovChain {
+        let root = key.root;
+        let scale = key.get_scale_notes();
+
+        let mut chain = MarkovChain::new(root);
+
+        for (i, &note) in scale.iter().enumerate() {
+            // Return to the tonic, but less than the chugging presets
+            if note != root {
+                chain.add_transition(note, root, 0.2);
+            }
+
+            // Neighbouring degrees, weighted by the step they make
+            let neighbours = [i.checked_sub(1), Some(i + 1).filter(|&j| j < scale.len())];
+            for j in neighbours.into_iter().flatten() {
+                let step = (scale[j] as i16 - note as i16).abs();
+                let weight = match step {
+                    3 => 0.35, // Augmented second: the signature sound
+                    1 => 0.25, // Half-step (b2 -> 1, 7 -> 8 pull)
+                    _ => 0.1,
+                };
+                chain.add_transition(note, scale[j], weight);
+            }
+
+            // Leap to the dominant for harmonic-minor cadences
+            if let Some(&fifth) = scale.iter().find(|&&n| n == root + 7) {
+                if fifth != note {
+                    chain.add_transition(note, fifth, 0.1);
+                }
+            }
+        }
+
+        chain
+    }
 }
 
 /// Chromatic mutation system for breaking free from scale-locking
@@ -172,25 +212,33 @@ impl ChromaticMutator {
     }
 
     /// Apply all mutations to a note sequence
-    pub fn apply_mutations(&self, mut notes: Vec<MidiNote>) -> Vec<MidiNote> {
+    pub fn apply_mutations(&self, notes: Vec<MidiNote>) -> Vec<MidiNote> {
+        self.apply_mutations_with(notes, &mut crate::utils::rng())
+    }
+
+    /// Apply all mutations using a caller-owned RNG (same seed + same notes = same result)
+    pub fn apply_mutations_seeded(&self, notes: Vec<MidiNote>, rng: &mut StdRng) -> Vec<MidiNote> {
+        self.apply_mutations_with(notes, rng)
+    }
+
+    fn apply_mutations_with<R: Rng>(&self, mut notes: Vec<MidiNote>, rng: &mut R) -> Vec<MidiNote> {
         // Apply bends
-        notes = self.bend_notes(notes);
+        notes = self.bend_notes(notes, rng);
         
         // Apply tritone substitutions
-        notes = self.tritone_substitute(notes);
+        notes = self.tritone_substitute(notes, rng);
         
         // Insert chromatic runs
-        notes = self.insert_chromatic_runs(notes);
+        notes = self.insert_chromatic_runs(notes, rng);
         
         // Enforce dissonant intervals
-        notes = self.enforce_intervals(notes);
+        notes = self.enforce_intervals(notes, rng);
         
         notes
     }
 
     /// Bend notes ±1 semitone with probability
-    fn bend_notes(&self, notes: Vec<MidiNote>) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+    fn bend_notes<R: Rng>(&self, notes: Vec<MidiNote>, rng: &mut R) -> Vec<MidiNote> {
         notes.iter()
             .map(|&note| {
                 if rng.gen_bool(self.bend_probability as f64) {
@@ -204,8 +252,7 @@ impl ChromaticMutator {
     }
 
     /// Replace notes with tritone (+6 semitones) with probability
-    fn tritone_substitute(&self, notes: Vec<MidiNote>) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+    fn tritone_substitute<R: Rng>(&self, notes: Vec<MidiNote>, rng: &mut R) -> Vec<MidiNote> {
         notes.iter()
             .map(|&note| {
                 if rng.gen_bool(self.tritone_probability as f64) {
@@ -218,8 +265,7 @@ impl ChromaticMutator {
     }
 
     /// Insert 2-4 note chromatic passages every 4-8 notes
-    fn insert_chromatic_runs(&self, notes: Vec<MidiNote>) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+    fn insert_chromatic_runs<R: Rng>(&self, notes: Vec<MidiNote>, rng: &mut R) -> Vec<MidiNote> {
         let mut result = Vec::new();
         let mut i = 0;
 
@@ -245,8 +291,7 @@ impl ChromaticMutator {
     }
 
     /// Enforce dissonant intervals (m2, m3, tritone) with probability
-    fn enforce_intervals(&self, notes: Vec<MidiNote>) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+    fn enforce_intervals<R: Rng>(&self, notes: Vec<MidiNote>, rng: &mut R) -> Vec<MidiNote> {
         let mut result = Vec::new();
 
         for (i, &note) in notes.iter().enumerate() {
@@ -302,7 +347,7 @@ impl PedalPointGenerator {
 
     /// Generate a sequence of notes with pedal point technique
     pub fn generate_sequence(&self, length: usize) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut sequence = Vec::with_capacity(length);
         let mut on_pedal = true;
         
@@ -385,6 +430,57 @@ impl RiffStructure {
     }
 }
 
+/// Pick direction for a sweep arpeggio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepDirection {
+    Ascending,  // Low string to high string
+    Descending, // High string to low string
+    UpDown,     // Ascend then descend (peak played once)
+}
+
+/// Generate a sweep-picked arpeggio: the chord's tones stacked over `octaves`,
+/// ending on the root, one note per string when laid out by
+/// `FretboardPathfinder::find_sweep_path`
+pub fn generate_sweep_arpeggio(
+    root: MidiNote,
+    chord_type: ChordType,
+    direction: SweepDirection,
+    octaves: usize,
+) -> Vec<MidiNote> {
+    // Chord tones folded into one octave (so extended chords still sweep cleanly)
+    let mut tones: Vec<u8> = Chord { root: 0, chord_type }
+        .get_notes()
+        .iter()
+        .map(|&n| n % 12)
+        .collect();
+    tones.sort_unstable();
+    tones.dedup();
+
+    let mut ascending = Vec::new();
+    for octave in 0..octaves.max(1) {
+        for &tone in &tones {
+            let note = root as usize + octave * 12 + tone as usize;
+            if note <= 127 {
+                ascending.push(note as MidiNote);
+            }
+        }
+    }
+    let top = root as usize + octaves.max(1) * 12;
+    if top <= 127 {
+        ascending.push(top as MidiNote);
+    }
+
+    match direction {
+        SweepDirection::Ascending => ascending,
+        SweepDirection::Descending => ascending.into_iter().rev().collect(),
+        SweepDirection::UpDown => {
+            let descent: Vec<MidiNote> = ascending.iter().rev().skip(1).copied().collect();
+            ascending.extend(descent);
+            ascending
+        }
+    }
+}
+
 /// Metal subgenre styles for riff generation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MetalStyle {
@@ -398,6 +494,65 @@ mod tests {
     use super::*;
     use crate::composition::music_theory::ScaleType;
 
+    #[test]
+    fn test_neoclassical_favours_augmented_seconds() {
+        crate::utils::seed_rng(1);
+        let key = Key { root: 40, scale_type: ScaleType::PhrygianDominant };
+        let b2_to_3 = |chain: &mut MarkovChain| {
+            let mut count = 0;
+            let mut previous = chain.next_note();
+            for _ in 0..2000 {
+                let next = chain.next_note();
+                if (previous == 41 && next == 44) || (previous == 44 && next == 41) {
+                    count += 1;
+                }
+                previous = next;
+            }
+            count
+        };
+
+        let neoclassical = b2_to_3(&mut MetalMarkovPresets::neoclassical(&key));
+        let heavy = b2_to_3(&mut MetalMarkovPresets::heavy_metal(&key));
+        assert!(neoclassical > heavy * 2, "neoclassical {} vs heavy {}", neoclassical, heavy);
+
+        assert!(ScaleType::PhrygianDominant.has_augmented_second());
+        assert!(ScaleType::HarmonicMinor.has_augmented_second());
+        assert!(!ScaleType::MinorPentatonic.has_augmented_second());
+        assert!(!ScaleType::Phrygian.has_augmented_second());
+    }
+
+    #[test]
+    fn test_seeded_mutations_are_deterministic() {
+        use rand::SeedableRng;
+
+        let mutator = ChromaticMutator::new(1.0);
+        let notes: Vec<MidiNote> = vec![40, 47, 52, 40, 45, 52, 40, 43, 47, 40, 50, 52];
+
+        let first = mutator.apply_mutations_seeded(notes.clone(), &mut StdRng::seed_from_u64(7));
+        let second = mutator.apply_mutations_seeded(notes.clone(), &mut StdRng::seed_from_u64(7));
+        assert_eq!(first, second);
+
+        // Full intensity over several seeds mutates something
+        let changed = (0..10u64).any(|seed| {
+            mutator.apply_mutations_seeded(notes.clone(), &mut StdRng::seed_from_u64(seed)) != notes
+        });
+        assert!(changed);
+    }
+
+    #[test]
+    fn test_sweep_arpeggio_tones() {
+        // A minor, one octave: A C E A
+        let up = generate_sweep_arpeggio(45, ChordType::Minor, SweepDirection::Ascending, 1);
+        assert_eq!(up, vec![45, 48, 52, 57]);
+
+        let down = generate_sweep_arpeggio(45, ChordType::Minor, SweepDirection::Descending, 1);
+        assert_eq!(down, vec![57, 52, 48, 45]);
+
+        // Up and back down without repeating the peak
+        let up_down = generate_sweep_arpeggio(45, ChordType::Minor, SweepDirection::UpDown, 1);
+        assert_eq!(up_down, vec![45, 48, 52, 57, 52, 48, 45]);
+    }
+
     #[test]
     fn test_markov_chain_creation() {
         let mut chain = MarkovChain::new(60);
@@ -19,6 +19,55 @@ impl RiffMotif {
         }
     }
 
+    /// Capture a played phrase as a motif (intervals relative to `root`)
+    pub fn from_notes(name: &str, notes: &[MidiNote], root: MidiNote, rhythm_density: f32) -> Self {
+        let intervals = notes
+            .iter()
+            .map(|&note| (note as i16 - root as i16).clamp(i8::MIN as i16, i8::MAX as i16) as i8)
+            .collect();
+        Self::new(name, intervals, rhythm_density)
+    }
+
+    /// Shift the whole motif by `semitones`
+    pub fn transpose(&self, semitones: i8) -> Self {
+        RiffMotif {
+            name: format!("{} (transposed {:+})", self.name, semitones),
+            intervals: self.intervals.iter().map(|&i| i.saturating_add(semitones)).collect(),
+            rhythm_density: self.rhythm_density,
+        }
+    }
+
+    /// Mirror the contour around the first note: rising steps become falling ones
+    pub fn invert(&self) -> Self {
+        let axis = self.intervals.first().copied().unwrap_or(0) as i16;
+        RiffMotif {
+            name: format!("{} (inverted)", self.name),
+            intervals: self.intervals
+                .iter()
+                .map(|&i| (2 * axis - i as i16).clamp(i8::MIN as i16, i8::MAX as i16) as i8)
+                .collect(),
+            rhythm_density: self.rhythm_density,
+        }
+    }
+
+    /// Play the motif backwards
+    pub fn retrograde(&self) -> Self {
+        RiffMotif {
+            name: format!("{} (retrograde)", self.name),
+            intervals: self.intervals.iter().rev().copied().collect(),
+            rhythm_density: self.rhythm_density,
+        }
+    }
+
+    /// Same pitches at double the note lengths (half the rhythmic density)
+    pub fn augment(&self) -> Self {
+        RiffMotif {
+            name: format!("{} (augmented)", self.name),
+            intervals: self.intervals.clone(),
+            rhythm_density: self.rhythm_density * 0.5,
+        }
+    }
+
     /// Apply this motif starting from a root note
     pub fn apply(&self, root: MidiNote) -> Vec<MidiNote> {
         self.intervals
@@ -152,7 +201,7 @@ impl MotifLibrary {
 
     /// Get a random motif
     pub fn random_motif(&self) -> &RiffMotif {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let idx = rng.gen_range(0..self.motifs.len());
         &self.motifs[idx]
     }
@@ -196,7 +245,7 @@ impl MotifRecombinator {
     /// Generate a riff by recombining motifs
     /// Returns a sequence of notes
     pub fn generate_riff(&self, key: &Key, num_motifs: usize, prefer_fast: bool) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut notes = Vec::new();
 
         let motif_pool = if prefer_fast {
@@ -237,7 +286,7 @@ impl MotifRecombinator {
 
     /// Fallback: use all motifs if pool is empty
     fn generate_riff_fallback(&self, key: &Key, num_motifs: usize) -> Vec<MidiNote> {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let mut notes = Vec::new();
 
         for _ in 0..num_motifs {
@@ -289,6 +338,25 @@ mod tests {
         assert_eq!(notes, vec![44, 42, 40]); // Reversed
     }
 
+    #[test]
+    fn test_motif_development() {
+        let motif = RiffMotif::new("Cell", vec![0, 3, 1, 6], 0.8);
+
+        assert_eq!(motif.transpose(5).intervals, vec![5, 8, 6, 11]);
+        assert_eq!(motif.invert().intervals, vec![0, -3, -1, -6]);
+        assert_eq!(motif.retrograde().intervals, vec![6, 1, 3, 0]);
+
+        let augmented = motif.augment();
+        assert_eq!(augmented.intervals, motif.intervals);
+        assert_eq!(augmented.rhythm_density, 0.4);
+
+        // Inversion mirrors around the first note, wherever it starts
+        assert_eq!(motif.transpose(2).invert().intervals, vec![2, -1, 1, -4]);
+
+        let captured = RiffMotif::from_notes("Verse", &[40, 43, 41, 46], 40, 0.5);
+        assert_eq!(captured.intervals, motif.intervals);
+    }
+
     #[test]
     fn test_library_creation() {
         let library = MotifLibrary::new();
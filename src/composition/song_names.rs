@@ -2,7 +2,7 @@ use rand::Rng;
 
 /// Generates funky, jazzy, groovy song names with personality
 pub fn generate_song_name() -> String {
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
 
     let adjectives = vec![
         "Brutal", "Dark", "Heavy", "Sludge", "Doom", "Black", "Rotting", "Vile",
@@ -63,7 +63,7 @@ use crate::composition::Genre;
 
 /// Generates a genre/style tag for the song based on the actual genre
 pub fn generate_genre_tags(genre: Genre) -> Vec<String> {
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
     let mut tags = Vec::new();
 
     // Add primary genre name
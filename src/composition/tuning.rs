@@ -1,7 +1,11 @@
-use crate::composition::music_theory::MidiNote;
+use crate::composition::music_theory::{note_name, MidiNote};
+use serde::Serialize;
+
+/// Highest open string a custom tuning may use: leaves room for 24 frets within MIDI 127
+pub const MAX_OPEN_STRING: MidiNote = 103;
 
 /// Guitar tunings for metal
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum GuitarTuning {
     EStandard,      // E2 (MIDI 40) - Thrash, Heavy Metal, Power Metal
     DropD,          // D2 (MIDI 38) - Metalcore, Alt-Metal, Nu-Metal
@@ -12,9 +16,33 @@ pub enum GuitarTuning {
     DropA7,         // A1 (MIDI 33) - 7-string Deathcore, Djent
     FSharpStandard8,// F#1 (MIDI 30) - 8-string Djent, Prog Metal
     DropE8,         // E1 (MIDI 28) - 8-string Extreme Djent/Thall
+    Custom(Vec<MidiNote>), // Arbitrary open strings, low to high (e.g. bass VI)
 }
 
 impl GuitarTuning {
+    /// Build a custom tuning, rejecting empty or out-of-range string lists
+    pub fn custom(strings: Vec<MidiNote>) -> Result<Self, String> {
+        let tuning = GuitarTuning::Custom(strings);
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    /// Check that a custom tuning has at least one string and every open string fits on the fretboard
+    pub fn validate(&self) -> Result<(), String> {
+        if let GuitarTuning::Custom(strings) = self {
+            if strings.is_empty() {
+                return Err("custom tuning needs at least one string".to_string());
+            }
+            if let Some(&note) = strings.iter().find(|&&note| note > MAX_OPEN_STRING) {
+                return Err(format!(
+                    "custom tuning open strings must be at most MIDI {} (got {})",
+                    MAX_OPEN_STRING, note
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Get the lowest note (MIDI number) for this tuning
     pub fn lowest_note(&self) -> MidiNote {
         match self {
@@ -27,6 +55,7 @@ impl GuitarTuning {
             GuitarTuning::DropA7 => 33,         // A1
             GuitarTuning::FSharpStandard8 => 30,// F#1
             GuitarTuning::DropE8 => 28,         // E1
+            GuitarTuning::Custom(strings) => strings.iter().copied().min().unwrap_or(40),
         }
     }
 
@@ -47,23 +76,41 @@ impl GuitarTuning {
             GuitarTuning::DropA7 => vec![33, 40, 45, 50, 55, 59, 64],     // A E A D G B E
             GuitarTuning::FSharpStandard8 => vec![30, 35, 40, 45, 50, 55, 59, 64], // F# B E A D G B E
             GuitarTuning::DropE8 => vec![28, 35, 40, 45, 50, 55, 59, 64], // E B E A D G B E
+            GuitarTuning::Custom(strings) => strings.clone(),
+        }
+    }
+
+    /// Human-readable name ("Drop C", "8-string F# Standard")
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            GuitarTuning::EStandard => "E Standard",
+            GuitarTuning::DropD => "Drop D",
+            GuitarTuning::DStandard => "D Standard",
+            GuitarTuning::CStandard => "C Standard",
+            GuitarTuning::DropC => "Drop C",
+            GuitarTuning::BStandard7 => "7-string B Standard",
+            GuitarTuning::DropA7 => "7-string Drop A",
+            GuitarTuning::FSharpStandard8 => "8-string F# Standard",
+            GuitarTuning::DropE8 => "8-string Drop E",
+            GuitarTuning::Custom(_) => "Custom",
         }
     }
 
+    /// Number of strings
+    pub fn string_count(&self) -> usize {
+        self.string_notes().len()
+    }
+
+    /// Open-string note names, low to high (e.g. ["D", "A", "D", "G", "B", "E"])
+    pub fn note_names(&self) -> Vec<String> {
+        self.string_notes().into_iter().map(|note| note_name(note).to_string()).collect()
+    }
+
     /// Check if bass should play in unison mode (tuning too low for octave down)
     pub fn bass_should_use_unison(&self) -> bool {
         // If lowest note is below B1 (MIDI 35), bass octave down would be too low
         self.lowest_note() < 35
     }
-
-    /// Get the recommended bass offset in semitones
-    pub fn bass_offset(&self) -> i8 {
-        if self.bass_should_use_unison() {
-            0  // Unison mode
-        } else {
-            -12  // Octave down
-        }
-    }
 }
 
 #[cfg(test)]
@@ -91,4 +138,35 @@ mod tests {
         assert_eq!(strings.len(), 6);
         assert_eq!(strings[0], 40); // Low E
     }
+
+    #[test]
+    fn test_tuning_metadata() {
+        assert_eq!(GuitarTuning::DropD.display_name(), "Drop D");
+        assert_eq!(GuitarTuning::DropD.note_names(), vec!["D", "A", "D", "G", "B", "E"]);
+        assert_eq!(GuitarTuning::DropA7.string_count(), 7);
+        assert_eq!(GuitarTuning::FSharpStandard8.note_names()[0], "F#");
+
+        let custom = GuitarTuning::Custom(vec![28, 33, 38]);
+        assert_eq!(custom.display_name(), "Custom");
+        assert_eq!(custom.string_count(), 3);
+        assert_eq!(custom.note_names(), vec!["E", "A", "D"]);
+    }
+
+    #[test]
+    fn test_custom_tuning() {
+        // Bass VI: a guitar an octave down
+        let bass_six = GuitarTuning::Custom(vec![28, 33, 38, 43, 47, 52]);
+        assert_eq!(bass_six.lowest_note(), 28);
+        assert_eq!(bass_six.string_notes().len(), 6);
+        assert!(bass_six.bass_should_use_unison());
+        assert!(!GuitarTuning::Custom(vec![38, 45, 50, 55, 59, 64]).bass_should_use_unison());
+    }
+
+    #[test]
+    fn test_custom_tuning_validation() {
+        assert!(GuitarTuning::custom(vec![28, 33, 38]).is_ok());
+        assert!(GuitarTuning::custom(vec![]).is_err());
+        assert!(GuitarTuning::custom(vec![40, 250]).is_err());
+        assert!(GuitarTuning::DropC.validate().is_ok());
+    }
 }
@@ -1,3 +1,7 @@
+use crate::audio::voice::VoicePlacement;
+use crate::audio::wav::BitDepth;
+use crate::composition::metal_song_generator::{MetalSection, MetalSubgenre, SectionIntensity};
+use crate::composition::music_theory::{Temperament, DEFAULT_A4};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -10,12 +14,29 @@ pub struct Config {
     pub generation: GenerationConfig,
     #[serde(default = "default_voice_config")]
     pub voice: VoiceConfig,
+    #[serde(default)]
+    pub metal: MetalConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioConfig {
     pub sample_rate: u32,
     pub bit_depth: u16,
+    #[serde(default = "default_reference_pitch")]
+    pub reference_pitch: f32, // A4 in Hz (440 concert pitch, 432, 415 baroque, ...)
+    #[serde(default)]
+    pub temperament: Temperament, // "equal", "just" or "meantone" (tempered from the song's key)
+}
+
+impl AudioConfig {
+    /// The bit depth must be one we can write and the reference pitch a plausible A4
+    pub fn validate(&self) -> Result<(), String> {
+        BitDepth::from_bits(self.bit_depth).map_err(|e| format!("audio.bit_depth: {}", e))?;
+        if !(300.0..=600.0).contains(&self.reference_pitch) {
+            return Err(format!("audio.reference_pitch must be between 300 and 600 Hz (got {})", self.reference_pitch));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +51,8 @@ pub struct CompositionConfig {
     pub structure: String, // "short" or "standard"
     pub min_tempo: f32,
     pub max_tempo: f32,
+    #[serde(default = "default_min_playability")]
+    pub min_playability: f32, // 0.8 = conservative, 0.2 = chaos
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +61,16 @@ pub struct GenerationConfig {
     pub write_metadata_json: bool,
     #[serde(default = "default_encode_mp3")]
     pub encode_mp3: bool,
+    #[serde(default)]
+    pub encode_flac: bool, // Lossless archival copy
+    #[serde(default)]
+    pub encode_ogg: bool, // Requires `oggenc` (vorbis-tools) on PATH
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String, // e.g. "{subgenre}/{date}_{title}" (see utils::FILENAME_FIELDS)
+    #[serde(default)]
+    pub export_stems: bool, // Also write dry _guitar/_bass/_drums WAVs for mixing in a DAW
+    #[serde(default)]
+    pub export_timeline: bool, // Also write section markers (_timeline.json) for a DAW
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,16 +79,273 @@ pub struct VoiceConfig {
     pub wisdom_file: String,
     #[serde(skip)]
     pub language: String, // Detected from wisdom_file name
-    pub placement: String, // "intro" | "intro_outro" | "bridge" | "intro_bridge" | "distributed"
+    pub placement: String, // "intro" | "intro_outro" | "bridge" | "intro_bridge" | "distributed" | "every_<n>_bars"
     pub volume: f32,
     pub duck_music_db: f32,
     pub segments_per_minute: f32,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetalConfig {
+    #[serde(default)]
+    pub mix: MixConfig,
+    #[serde(default)]
+    pub palm_mute: PalmMuteConfig,
+    /// Chance (0.0-1.0) that a blast bar switches style, shifts its accent or ends in a
+    /// fill (`None` = the renderer's default)
+    #[serde(default)]
+    pub blast_variation: Option<f32>,
+    /// Mastering EQ on the finished song (`None` = per-subgenre curve)
+    #[serde(default)]
+    pub master_eq: Option<MasterEqConfig>,
+    /// Master-bus saturation drive, 1.0-4.0 (0.0 = off, `None` = the renderer's subtle default)
+    #[serde(default)]
+    pub saturation_drive: Option<f32>,
+    /// Seconds of silence before the song (before the count-in, if any)
+    #[serde(default)]
+    pub lead_in_silence: f32,
+    /// One bar counted in before the first section: "none", "clicks" or "crash"
+    #[serde(default)]
+    pub count_in: CountIn,
+}
+
+/// How the song is counted in before its first section
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountIn {
+    /// Start straight on the first section
+    #[default]
+    None,
+    /// Four metronome clicks, one per beat, accented on the first
+    Clicks,
+    /// A reversed-crash swell over one bar, cutting off on the first downbeat
+    Crash,
+}
+
+/// Longest leading silence a song may start with, in seconds
+pub const MAX_LEAD_IN_SILENCE: f32 = 30.0;
+
+/// Highest drive the master saturation accepts
+pub const MAX_SATURATION_DRIVE: f32 = 4.0;
+
+impl MetalConfig {
+    /// `blast_variation` must be within 0.0-1.0, `saturation_drive` 0.0 or 1.0-4.0,
+    /// `lead_in_silence` 0-30 seconds and the master EQ bands in range
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=MAX_LEAD_IN_SILENCE).contains(&self.lead_in_silence) {
+            return Err(format!(
+                "metal.lead_in_silence must be between 0.0 and {} seconds (got {})",
+                MAX_LEAD_IN_SILENCE, self.lead_in_silence
+            ));
+        }
+        match self.blast_variation {
+            Some(variation) if !(0.0..=1.0).contains(&variation) => {
+                return Err(format!("metal.blast_variation must be between 0.0 and 1.0 (got {})", variation));
+            }
+            _ => {}
+        }
+        match self.saturation_drive {
+            Some(drive) if drive != 0.0 && !(1.0..=MAX_SATURATION_DRIVE).contains(&drive) => {
+                return Err(format!(
+                    "metal.saturation_drive must be 0.0 (off) or between 1.0 and {} (got {})",
+                    MAX_SATURATION_DRIVE, drive
+                ));
+            }
+            _ => {}
+        }
+        self.master_eq.map_or(Ok(()), |eq| eq.validate())
+    }
+}
+
+/// Largest boost or cut a master EQ band may apply, in dB
+pub const MASTER_EQ_MAX_DB: f32 = 12.0;
+
+/// Three-band mastering EQ (`[metal.master_eq]`): low shelf, mid peak, high shelf.
+/// Gains are in dB (0.0 = flat); omitted fields keep these flat defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MasterEqConfig {
+    pub low_hz: f32,
+    pub low_db: f32,
+    pub mid_hz: f32,
+    pub mid_db: f32,
+    pub mid_q: f32,
+    pub high_hz: f32,
+    pub high_db: f32,
+}
+
+impl Default for MasterEqConfig {
+    fn default() -> Self {
+        MasterEqConfig {
+            low_hz: 100.0,
+            low_db: 0.0,
+            mid_hz: 800.0,
+            mid_db: 0.0,
+            mid_q: 1.0,
+            high_hz: 5000.0,
+            high_db: 0.0,
+        }
+    }
+}
+
+impl MasterEqConfig {
+    /// Mastering curve per subgenre: scooped mids for thrash and death, presence for prog,
+    /// a dark, mid-heavy doom
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        let (low_db, mid_hz, mid_db, high_hz, high_db) = match subgenre {
+            MetalSubgenre::HeavyMetal => (1.5, 800.0, -2.0, 4000.0, 2.0),
+            MetalSubgenre::ThrashMetal => (1.0, 750.0, -4.0, 5000.0, 2.0),
+            MetalSubgenre::DeathMetal => (2.0, 600.0, -3.0, 5000.0, 3.0),
+            MetalSubgenre::DoomMetal => (3.0, 700.0, 1.0, 5000.0, -1.0),
+            MetalSubgenre::ProgressiveMetal => (1.0, 1000.0, -1.0, 3500.0, 3.0),
+        };
+        MasterEqConfig { low_db, mid_hz, mid_db, high_hz, high_db, ..MasterEqConfig::default() }
+    }
+
+    /// Frequencies within 20 Hz-20 kHz, a positive Q, gains within ±`MASTER_EQ_MAX_DB`
+    pub fn validate(&self) -> Result<(), String> {
+        for (band, hz) in [("low_hz", self.low_hz), ("mid_hz", self.mid_hz), ("high_hz", self.high_hz)] {
+            if !(20.0..=20000.0).contains(&hz) {
+                return Err(format!("metal.master_eq.{} must be between 20 and 20000 Hz (got {})", band, hz));
+            }
+        }
+        for (band, db) in [("low_db", self.low_db), ("mid_db", self.mid_db), ("high_db", self.high_db)] {
+            if !(-MASTER_EQ_MAX_DB..=MASTER_EQ_MAX_DB).contains(&db) {
+                return Err(format!(
+                    "metal.master_eq.{} must be between -{} and {} dB (got {})",
+                    band, MASTER_EQ_MAX_DB, MASTER_EQ_MAX_DB, db
+                ));
+            }
+        }
+        if !(self.mid_q.is_finite() && self.mid_q > 0.0) {
+            return Err(format!("metal.master_eq.mid_q must be positive (got {})", self.mid_q));
+        }
+        Ok(())
+    }
+}
+
+/// Share of palm-muted notes per section (`[metal.palm_mute]`, 0.0 = all open,
+/// 1.0 = all muted). Sections left out keep the generator's own voicing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PalmMuteConfig {
+    pub intro: Option<f32>,
+    pub verse: Option<f32>,
+    pub pre_chorus: Option<f32>,
+    pub chorus: Option<f32>,
+    pub breakdown: Option<f32>,
+    pub solo: Option<f32>,
+    pub outro: Option<f32>,
+}
+
+impl PalmMuteConfig {
+    /// Sections with a ratio set, and their ratio
+    pub fn ratios(&self) -> Vec<(MetalSection, f32)> {
+        [
+            (MetalSection::Intro, self.intro),
+            (MetalSection::Verse, self.verse),
+            (MetalSection::PreChorus, self.pre_chorus),
+            (MetalSection::Chorus, self.chorus),
+            (MetalSection::Breakdown, self.breakdown),
+            (MetalSection::Solo, self.solo),
+            (MetalSection::Outro, self.outro),
+        ]
+        .into_iter()
+        .filter_map(|(section, ratio)| ratio.map(|ratio| (section, ratio)))
+        .collect()
+    }
+
+    /// Every ratio must be within 0.0-1.0
+    pub fn validate(&self) -> Result<(), String> {
+        for (section, ratio) in self.ratios() {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!(
+                    "metal.palm_mute.{} must be between 0.0 and 1.0 (got {})",
+                    section.config_key(),
+                    ratio
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Track gains for one section intensity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MixLevels {
+    pub guitar: f32,
+    pub bass: f32,
+    pub drums: f32,
+}
+
+impl MixLevels {
+    pub const fn new(guitar: f32, bass: f32, drums: f32) -> Self {
+        MixLevels { guitar, bass, drums }
+    }
+}
+
+/// Per-intensity track gains used when mixing each section (`[metal.mix]`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MixConfig {
+    pub low: MixLevels,
+    pub medium: MixLevels,
+    pub high: MixLevels,
+    pub extreme: MixLevels,
+}
+
+impl Default for MixConfig {
+    fn default() -> Self {
+        MixConfig {
+            low: MixLevels::new(0.35, 0.40, 0.50),
+            medium: MixLevels::new(0.40, 0.45, 0.60),
+            high: MixLevels::new(0.45, 0.50, 0.65),
+            extreme: MixLevels::new(0.50, 0.55, 0.70), // Louder, but safe
+        }
+    }
+}
+
+impl MixConfig {
+    /// Gains for a section of the given intensity
+    pub fn levels(&self, intensity: SectionIntensity) -> MixLevels {
+        match intensity {
+            SectionIntensity::Low => self.low,
+            SectionIntensity::Medium => self.medium,
+            SectionIntensity::High => self.high,
+            SectionIntensity::Extreme => self.extreme,
+        }
+    }
+
+    /// Every gain must be a finite, non-negative number
+    pub fn validate(&self) -> Result<(), String> {
+        let tiers = [("low", self.low), ("medium", self.medium), ("high", self.high), ("extreme", self.extreme)];
+        for (tier, levels) in tiers {
+            for (track, gain) in [("guitar", levels.guitar), ("bass", levels.bass), ("drums", levels.drums)] {
+                if !gain.is_finite() || gain < 0.0 {
+                    return Err(format!("metal.mix.{}.{} must be a finite, non-negative gain (got {})", tier, track, gain));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_reference_pitch() -> f32 {
+    DEFAULT_A4
+}
+
 fn default_encode_mp3() -> bool {
     true
 }
 
+fn default_filename_template() -> String {
+    crate::utils::DEFAULT_FILENAME_TEMPLATE.to_string()
+}
+
+fn default_min_playability() -> f32 {
+    crate::composition::metal_song_generator::DEFAULT_MIN_PLAYABILITY
+}
+
 fn default_voice_config() -> VoiceConfig {
     VoiceConfig {
         enabled: false,
@@ -69,6 +359,11 @@ fn default_voice_config() -> VoiceConfig {
 }
 
 impl VoiceConfig {
+    /// Parsed `placement` (unknown names are an error, not a silent default)
+    pub fn placement(&self) -> Result<VoicePlacement, String> {
+        self.placement.parse()
+    }
+
     /// Detect language from wisdom file name
     /// - "wisdom.json" -> "en"
     /// - "wisdom-bg.json" -> "bg"
@@ -82,6 +377,14 @@ impl VoiceConfig {
     }
 }
 
+impl GenerationConfig {
+    /// The filename template must name at least one known field
+    pub fn validate(&self) -> Result<(), String> {
+        crate::utils::validate_filename_template(&self.filename_template)
+            .map_err(|e| format!("generation.filename_template: {}", e))
+    }
+}
+
 impl Config {
     /// Load configuration from a TOML file
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
@@ -89,6 +392,12 @@ impl Config {
         let mut config: Config = toml::from_str(&contents)?;
         // Detect language from wisdom file name
         config.voice.language = VoiceConfig::detect_language_from_filename(&config.voice.wisdom_file);
+        config.audio.validate()?;
+        config.metal.mix.validate()?;
+        config.metal.palm_mute.validate()?;
+        config.metal.validate()?;
+        config.generation.validate()?;
+        config.voice.placement()?;
         Ok(config)
     }
 
@@ -103,6 +412,8 @@ impl Config {
             audio: AudioConfig {
                 sample_rate: 44100,
                 bit_depth: 16,
+                reference_pitch: default_reference_pitch(),
+                temperament: Temperament::Equal,
             },
             metadata: MetadataConfig {
                 artist: "Petar Zarkov".to_string(),
@@ -113,17 +424,24 @@ impl Config {
                 structure: "standard".to_string(),
                 min_tempo: 80.0,
                 max_tempo: 250.0,
+                min_playability: default_min_playability(),
             },
             generation: GenerationConfig {
                 output_dir: "output".to_string(),
                 write_metadata_json: true,
                 encode_mp3: true,
+                encode_flac: false,
+                encode_ogg: false,
+                filename_template: default_filename_template(),
+                export_stems: false,
+                export_timeline: false,
             },
             voice: {
                 let mut voice = default_voice_config();
                 voice.language = VoiceConfig::detect_language_from_filename(&voice.wisdom_file);
                 voice
             },
+            metal: MetalConfig::default(),
         }
     }
 }
@@ -138,4 +456,91 @@ mod tests {
         assert_eq!(config.audio.sample_rate, 44100);
         assert_eq!(config.metadata.artist, "Petar Zarkov");
     }
+
+    #[test]
+    fn test_mix_config_parses_and_validates() {
+        let mix: MixConfig = toml::from_str("extreme = { guitar = 0.3, bass = 0.8, drums = 0.7 }").unwrap();
+        assert_eq!(mix.extreme, MixLevels::new(0.3, 0.8, 0.7));
+        // Tiers left out keep the stock mix
+        assert_eq!(mix.medium, MixConfig::default().medium);
+        assert!(mix.validate().is_ok());
+
+        let negative = MixConfig { low: MixLevels::new(0.35, -0.1, 0.5), ..MixConfig::default() };
+        assert!(negative.validate().unwrap_err().contains("low.bass"));
+
+        let nan = MixConfig { high: MixLevels::new(f32::NAN, 0.5, 0.65), ..MixConfig::default() };
+        assert!(nan.validate().is_err());
+    }
+
+    #[test]
+    fn test_audio_pitch_config() {
+        let audio: AudioConfig = toml::from_str("sample_rate = 48000\nbit_depth = 24").unwrap();
+        assert_eq!((audio.reference_pitch, audio.temperament), (440.0, Temperament::Equal));
+
+        let audio: AudioConfig =
+            toml::from_str("sample_rate = 48000\nbit_depth = 24\nreference_pitch = 432.0\ntemperament = \"just\"").unwrap();
+        assert_eq!((audio.reference_pitch, audio.temperament), (432.0, Temperament::Just));
+        assert!(audio.validate().is_ok());
+
+        assert!(AudioConfig { reference_pitch: 44.0, ..audio.clone() }.validate().is_err());
+        assert!(AudioConfig { reference_pitch: f32::NAN, ..audio.clone() }.validate().is_err());
+        assert!(AudioConfig { bit_depth: 20, ..audio }.validate().is_err());
+        assert!(toml::from_str::<AudioConfig>("sample_rate = 1\nbit_depth = 16\ntemperament = \"pythagorean\"").is_err());
+    }
+
+    #[test]
+    fn test_palm_mute_config_parses_and_validates() {
+        let metal: MetalConfig = toml::from_str("[palm_mute]\nverse = 0.9\nchorus = 0.1").unwrap();
+        assert_eq!(metal.palm_mute.ratios(), vec![(MetalSection::Verse, 0.9), (MetalSection::Chorus, 0.1)]);
+        assert!(metal.palm_mute.validate().is_ok());
+        assert!(MetalConfig::default().palm_mute.ratios().is_empty());
+
+        let too_high = PalmMuteConfig { solo: Some(1.5), ..PalmMuteConfig::default() };
+        assert!(too_high.validate().unwrap_err().contains("palm_mute.solo"));
+        let nan = PalmMuteConfig { intro: Some(f32::NAN), ..PalmMuteConfig::default() };
+        assert!(nan.validate().is_err());
+
+        let metal: MetalConfig = toml::from_str("blast_variation = 0.6").unwrap();
+        assert_eq!(metal.blast_variation, Some(0.6));
+        assert!(metal.validate().is_ok());
+        assert!(MetalConfig { blast_variation: Some(1.2), ..metal }.validate().is_err());
+
+        let metal: MetalConfig = toml::from_str("saturation_drive = 0.0").unwrap();
+        assert!(metal.validate().is_ok());
+        assert!(MetalConfig { saturation_drive: Some(2.0), ..metal }.validate().is_ok());
+        assert!(MetalConfig { saturation_drive: Some(0.5), ..metal }.validate().is_err());
+        assert!(MetalConfig { saturation_drive: Some(f32::NAN), ..metal }.validate().is_err());
+    }
+
+    #[test]
+    fn test_lead_in_defaults_off_and_validates() {
+        let metal = MetalConfig::default();
+        assert_eq!((metal.lead_in_silence, metal.count_in), (0.0, CountIn::None));
+
+        let metal: MetalConfig = toml::from_str("lead_in_silence = 1.5\ncount_in = \"crash\"").unwrap();
+        assert_eq!((metal.lead_in_silence, metal.count_in), (1.5, CountIn::Crash));
+        assert!(metal.validate().is_ok());
+        assert!(MetalConfig { lead_in_silence: -1.0, ..metal }.validate().unwrap_err().contains("lead_in_silence"));
+        assert!(MetalConfig { lead_in_silence: f32::NAN, ..metal }.validate().is_err());
+        assert!(toml::from_str::<MetalConfig>("count_in = \"cowbell\"").is_err());
+    }
+
+    #[test]
+    fn test_master_eq_config_parses_and_validates() {
+        assert_eq!(MetalConfig::default().master_eq, None);
+        let metal: MetalConfig = toml::from_str("[master_eq]\nmid_hz = 650.0\nmid_db = -4.5").unwrap();
+        let eq = metal.master_eq.unwrap();
+        assert_eq!((eq.mid_hz, eq.mid_db), (650.0, -4.5));
+        assert_eq!((eq.low_db, eq.high_db), (0.0, 0.0)); // Omitted bands stay flat
+        assert!(metal.validate().is_ok());
+
+        for subgenre in MetalSubgenre::ALL {
+            assert!(MasterEqConfig::for_subgenre(subgenre).validate().is_ok());
+        }
+        assert!(MasterEqConfig::for_subgenre(MetalSubgenre::ThrashMetal).mid_db < 0.0);
+        assert!(MasterEqConfig { high_db: 18.0, ..eq }.validate().unwrap_err().contains("high_db"));
+        assert!(MasterEqConfig { mid_hz: 5.0, ..eq }.validate().is_err());
+        assert!(MasterEqConfig { mid_q: 0.0, ..eq }.validate().is_err());
+        assert!(MasterEqConfig { low_db: f32::NAN, ..eq }.validate().is_err());
+    }
 }
@@ -0,0 +1,32 @@
+//! Rust Beats - procedural metal song generator
+//!
+//! Library API behind the `rust-beats` binary: compose a song structure with
+//! [`MetalSongGenerator`], then render it to mono samples with [`MetalAudioRenderer`].
+//!
+//! ```no_run
+//! use rust_beats::{init_sample_rate, MetalAudioRenderer, MetalSongGenerator, MetalSubgenre, SongRenderOptions};
+//!
+//! init_sample_rate(44100);
+//! let song = MetalSongGenerator::new(MetalSubgenre::ThrashMetal).generate_song();
+//! let rendered = MetalAudioRenderer::new().render_song(&song, &SongRenderOptions::default());
+//! println!("{:.1}s", rendered.stats.duration_seconds);
+//! ```
+
+pub mod audio;
+pub mod composition;
+pub mod config;
+pub mod synthesis;
+pub mod utils;
+
+// Core exports
+pub use composition::metal_song_generator::{
+    MetalRiff, MetalSection, MetalSong, MetalSongGenerator, MetalSongGeneratorBuilder, MetalSongMetadata,
+    MetalSubgenre, RhythmEngine, RhythmPattern,
+};
+pub use composition::drum_patterns::DrumGrid;
+pub use composition::tuning::GuitarTuning;
+pub use config::{AudioConfig, CompositionConfig, Config, GenerationConfig, MasterEqConfig, MetadataConfig, MixConfig, MixLevels, VoiceConfig};
+pub use synthesis::metal_audio_renderer::{
+    MetalAudioRenderer, RenderStats, RenderedSong, SectionMarker, SongRenderOptions, Stems,
+};
+pub use utils::{get_sample_rate, init_sample_rate, RenderContext};
@@ -1,20 +1,52 @@
-mod audio;
-mod composition;
-mod config;
-mod synthesis;
-mod utils;
-
-use audio::encode_to_mp3;
-use composition::{
-    generate_song_name, generate_genre_tags,
-    metal_song_generator::{MetalSongGenerator, MetalSubgenre},
+use rust_beats::audio::{
+    encode_to_flac, encode_to_mp3, encode_to_ogg, save_wav, write_metadata_json, BitDepth, ClipReport, SaveError,
 };
-use config::Config;
-use synthesis::{get_sample_rate, init_sample_rate, metal_audio_renderer::MetalAudioRenderer};
-use utils::{get_current_date, sanitize_filename};
+use rust_beats::composition::{self, generate_genre_tags, generate_song_name, music_theory::{note_name, PitchStandard}};
+use rust_beats::composition::metal_song_generator::{MAX_TEMPO, MIN_TEMPO};
+use rust_beats::config::Config;
+use rust_beats::utils::{format_filename, get_current_date, seed_rng};
+use rust_beats::{get_sample_rate, init_sample_rate, MetalAudioRenderer, MetalSongGenerator, MetalSongMetadata, MetalSubgenre, SongRenderOptions};
+use rand::Rng;
 use std::fs;
+use std::path::Path;
+
+const USAGE: &str = "Usage: rust-beats [--subgenre <name>] [--tempo <bpm>] [--seed <n>] [--out <file.wav>] [--count <n>] [--click] [--plan]";
+
+/// Command-line overrides for the random choices
+#[derive(Debug, Default, PartialEq)]
+struct CliArgs {
+    subgenre: Option<MetalSubgenre>,
+    tempo: Option<u16>,
+    seed: Option<u64>,
+    out: Option<String>,
+    count: Option<usize>,
+    click: bool,
+    plan: bool, // Print the song plan and skip rendering
+    help: bool,
+}
+
+/// One finished track, for the batch summary table
+struct TrackSummary {
+    name: String,
+    subgenre: MetalSubgenre,
+    tempo: u16,
+    duration_seconds: f32,
+    seed: u64,
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    });
+    if cli.help {
+        println!("{}", USAGE);
+        println!("Subgenres: {}", subgenre_names());
+        return;
+    }
+
     println!("🤘 RUST BEATS - METAL MUSIC GENERATOR 🤘");
     println!("=========================================\n");
 
@@ -29,18 +61,51 @@ fn main() {
     init_sample_rate(config.audio.sample_rate);
     
     println!("Artist: {}", config.metadata.artist);
-    println!("Sample Rate: {} Hz\n", config.audio.sample_rate);
-    
-    // Choose a random metal subgenre
-    let subgenres = vec![
-        MetalSubgenre::HeavyMetal,
-        MetalSubgenre::ThrashMetal,
-        MetalSubgenre::DeathMetal,
-        MetalSubgenre::DoomMetal,
-        MetalSubgenre::ProgressiveMetal,
-    ];
-    
-    let subgenre = subgenres[rand::random::<usize>() % subgenres.len()];
+    println!("Sample Rate: {} Hz", config.audio.sample_rate);
+
+    // Song i is seeded with base_seed + i so the whole batch is reproducible
+    let count = cli.count.unwrap_or(1);
+    let base_seed = cli.seed.unwrap_or_else(|| rust_beats::utils::rng().gen());
+    println!("Seed: {}\n", base_seed);
+
+    // One renderer for the whole batch (drum kit and DSP chain are reused)
+    seed_rng(base_seed);
+    let mut renderer = MetalAudioRenderer::new();
+    renderer.set_mix_levels(config.metal.mix);
+    renderer.set_blast_variation(config.metal.blast_variation);
+    renderer.set_master_eq(config.metal.master_eq);
+    renderer.set_saturation_drive(config.metal.saturation_drive);
+    renderer.set_lead_in(config.metal.lead_in_silence, config.metal.count_in);
+
+    let mut summaries = Vec::with_capacity(count);
+    for track in 0..count {
+        let seed = base_seed.wrapping_add(track as u64);
+        seed_rng(seed);
+        if count > 1 {
+            println!("💿 Track {}/{} (seed {})\n", track + 1, count, seed);
+        }
+        if let Some(summary) = generate_track(&config, &cli, &mut renderer, seed, (count > 1).then_some(track + 1)) {
+            summaries.push(summary);
+        }
+    }
+
+    if count > 1 {
+        print_batch_summary(&summaries);
+    }
+}
+
+/// Generate, render and save one song; `track_number` suffixes filenames in batch mode
+fn generate_track(
+    config: &Config,
+    cli: &CliArgs,
+    renderer: &mut MetalAudioRenderer,
+    seed: u64,
+    track_number: Option<usize>,
+) -> Option<TrackSummary> {
+    // Choose a random metal subgenre (unless given on the command line)
+    let subgenre = cli.subgenre.unwrap_or_else(|| {
+        MetalSubgenre::ALL[rust_beats::utils::rng().gen_range(0..MetalSubgenre::ALL.len())]
+    });
     
     println!("🎸 Generating {:?} song...\n", subgenre);
     
@@ -50,8 +115,15 @@ fn main() {
     let genre_tags = generate_genre_tags(genre);
     
     // Generate the song structure
-    let generator = MetalSongGenerator::new(subgenre);
-    let song = generator.generate_song();
+    let mut generator = MetalSongGenerator::with_min_playability(subgenre, config.composition.min_playability);
+    if let Some(tempo) = cli.tempo {
+        generator = generator.with_tempo(tempo);
+    }
+    for (section, ratio) in config.metal.palm_mute.ratios() {
+        generator.set_palm_mute_ratio(section, Some(ratio));
+    }
+    // Verses and choruses come back as the same riff (with a varied last bar)
+    let song = generator.generate_song_with_memory();
     
     println!("📝 Song Details:");
     println!("   Name: {}", song_name);
@@ -59,11 +131,28 @@ fn main() {
     println!("   Subgenre: {:?}", song.subgenre);
     println!("   Key: {:?} {:?}", song.key.root, song.key.scale_type);
     println!("   Tempo: {} BPM", song.tempo);
-    println!("   Tuning: {:?}", song.tuning);
+    println!("   Tuning: {} ({})", song.tuning.display_name(), song.tuning.note_names().join(" "));
     println!("   Sections: {}", song.sections.len());
     println!();
     
-    // Print section breakdown
+    // Each section lasts its default number of bars at the song's tempo
+    let total_duration: f32 = song.sections.iter().map(|(section, _)| section.default_duration(song.tempo)).sum();
+
+    // Dry run: show the composition and stop before the render
+    if cli.plan {
+        println!("📋 Song Plan:");
+        println!("{}", song.describe());
+        println!("   Estimated Duration: {:.1}s ({:.1} min)", total_duration, total_duration / 60.0);
+        println!();
+        return Some(TrackSummary {
+            name: song_name,
+            subgenre,
+            tempo: song.tempo,
+            duration_seconds: total_duration,
+            seed,
+        });
+    }
+
     println!("🎼 Song Structure:");
     for (i, (section, riff)) in song.sections.iter().enumerate() {
         println!("   {}. {:?} - {} notes", i + 1, section, riff.notes.len());
@@ -72,88 +161,134 @@ fn main() {
     
     // Render the audio
     println!("🔊 Rendering audio...");
-    let mut renderer = MetalAudioRenderer::new();
-    
-    // Calculate variable durations for each section
-    let mut total_duration = 0.0;
-    let mut section_durations = Vec::new();
-    
-    for (section, _) in &song.sections {
-        let duration = get_section_duration(*section, song.tempo);
-        section_durations.push(duration);
-        total_duration += duration;
-    }
-    
     println!("   Estimated Duration: {:.1}s ({:.1} min)", total_duration, total_duration / 60.0);
     
-    // Render each section with its specific duration
-    let mut audio_samples = Vec::new();
-    for ((section, riff), duration) in song.sections.iter().zip(section_durations.iter()) {
-        let section_audio = renderer.render_section(*section, riff, *duration, song.tempo, song.subgenre);
-        audio_samples.extend(section_audio);
-    }
-    
-    let duration_seconds = audio_samples.len() as f32 / get_sample_rate() as f32;
+    renderer.set_pitch_standard(
+        PitchStandard::new(config.audio.reference_pitch).with_temperament(config.audio.temperament, song.key.root),
+    );
+    let rendered = renderer.render_song(&song, &SongRenderOptions {
+        progress: Some(&print_progress),
+        click: cli.click,
+        stems: config.generation.export_stems,
+        ..SongRenderOptions::default()
+    });
+    println!();
+    let (audio_samples, stats, rendered_stems) = (rendered.audio, rendered.stats, rendered.stems);
+    let duration_seconds = stats.duration_seconds;
     println!("   Duration: {:.1}s", duration_seconds);
     println!("   Samples: {}", audio_samples.len());
+    println!("   Peak: {:.1} dBFS, RMS: {:.1} dBFS", stats.peak_dbfs(), stats.rms_dbfs());
     println!();
     
     // Save files
     println!("💾 Saving audio...");
     let date = get_current_date();
     
-    // Create sanitized filename
-    let sanitized_artist = sanitize_filename(&config.metadata.artist);
-    let sanitized_song_name = sanitize_filename(&song_name);
-    let filename_base = format!("{}_{}_{}",  date, sanitized_artist, sanitized_song_name);
+    // Filename from the config template (--out overrides both the directory and the name)
+    let (output_dir, mut filename_base) = match &cli.out {
+        Some(out) => split_output_path(out),
+        None => {
+            let key = format!("{} {:?}", note_name(song.key.root), song.key.scale_type).replace('#', "sharp");
+            let fields = [
+                ("date", date.clone()),
+                ("artist", config.metadata.artist.clone()),
+                ("title", song_name.clone()),
+                ("subgenre", song.subgenre.cli_name().to_string()),
+                ("key", key),
+                ("bpm", song.tempo.to_string()),
+                ("seed", seed.to_string()),
+            ];
+            match format_filename(&config.generation.filename_template, &fields) {
+                Ok(filename) => (config.generation.output_dir.clone(), filename),
+                Err(e) => {
+                    eprintln!("❌ Error in filename template: {}", e);
+                    return None;
+                }
+            }
+        }
+    };
+    if let Some(number) = track_number {
+        filename_base = format!("{}_{:02}", filename_base, number);
+    }
     
-    // Create output directory
-    let output_dir = &config.generation.output_dir;
-    if let Err(e) = fs::create_dir_all(output_dir) {
+    // Create output directory (plus any subfolders from the filename template)
+    let output_dir = &output_dir;
+    let base_path = Path::new(output_dir).join(&filename_base);
+    if let Err(e) = fs::create_dir_all(base_path.parent().unwrap_or(Path::new(output_dir))) {
         eprintln!("❌ Error creating output directory: {}", e);
-        return;
+        return None;
     }
     
     let wav_path = format!("{}/{}.wav", output_dir, filename_base);
     let mp3_path = format!("{}/{}.mp3", output_dir, filename_base);
+    let flac_path = format!("{}/{}.flac", output_dir, filename_base);
+    let ogg_path = format!("{}/{}.ogg", output_dir, filename_base);
     let json_path = format!("{}/{}.json", output_dir, filename_base);
     
     // Save WAV file
-    match save_wav(&wav_path, &audio_samples, get_sample_rate()) {
-        Ok(_) => println!("✅ Successfully created: {}", wav_path),
+    let bit_depth = match BitDepth::from_bits(config.audio.bit_depth) {
+        Ok(bit_depth) => bit_depth,
         Err(e) => {
             eprintln!("❌ Error saving WAV file: {}", e);
-            return;
+            return None;
+        }
+    };
+    match save_wav(&wav_path, &audio_samples, get_sample_rate(), bit_depth) {
+        Ok(report) => {
+            warn_on_clipping(&report, bit_depth);
+            println!("✅ Successfully created: {}", wav_path);
+        }
+        Err(e) => {
+            eprintln!("❌ Error saving WAV file: {}", e);
+            return None;
         }
     }
     
+    // Save dry stems (rendered only if enabled in config)
+    if let Some(stems) = &rendered_stems {
+        for (track, samples) in stems.tracks() {
+            let stem_path = format!("{}/{}_{}.wav", output_dir, filename_base, track);
+            let result = save_wav(&stem_path, samples, get_sample_rate(), bit_depth);
+            report_optional_save("stem", &stem_path, result.map(|report| warn_on_clipping(&report, bit_depth)));
+        }
+    }
+
+    // Save section markers (if enabled in config)
+    if config.generation.export_timeline {
+        let timeline_path = format!("{}/{}_timeline.json", output_dir, filename_base);
+        let sections: Vec<_> = song.sections.iter().map(|(section, _)| *section).collect();
+        let timeline = serde_json::to_value(stats.timeline(&sections, song.tempo)).unwrap_or_default();
+        report_optional_save("timeline", &timeline_path, write_metadata_json(&timeline_path, &timeline));
+    }
+
     // Save MP3 file (if enabled in config)
     if config.generation.encode_mp3 {
-        match encode_to_mp3(&audio_samples, &mp3_path, &song_name, &config.metadata.artist) {
-            Ok(_) => println!("✅ Successfully created: {}", mp3_path),
-            Err(e) => eprintln!("⚠️  Warning: Could not create MP3: {}", e),
-        }
+        report_optional_save("MP3", &mp3_path, encode_to_mp3(&audio_samples, &mp3_path, &song_name, &config.metadata.artist));
+    }
+    
+    // Save FLAC file (if enabled in config)
+    if config.generation.encode_flac {
+        report_optional_save("FLAC", &flac_path, encode_to_flac(&audio_samples, &flac_path, &song_name, &config.metadata.artist));
+    }
+    
+    // Save OGG file (if enabled in config)
+    if config.generation.encode_ogg {
+        report_optional_save("OGG", &ogg_path, encode_to_ogg(&audio_samples, &ogg_path, &song_name, &config.metadata.artist));
     }
     
     // Save JSON metadata (if enabled in config)
     if config.generation.write_metadata_json {
-        let metadata = serde_json::json!({
-            "name": song_name,
-            "artist": config.metadata.artist,
-            "genre": genre_tags,
-            "tempo": song.tempo as f32,
-            "duration": duration_seconds,
-            "date": date,
-            "subgenre": format!("{:?}", song.subgenre),
-            "key": format!("{:?} {:?}", song.key.root, song.key.scale_type),
-            "tuning": format!("{:?}", song.tuning),
-            "sections": song.sections.len(),
-        });
-        
-        match fs::write(&json_path, serde_json::to_string_pretty(&metadata).unwrap()) {
-            Ok(_) => println!("✅ Successfully created: {}", json_path),
-            Err(e) => eprintln!("⚠️  Warning: Could not write metadata: {}", e),
+        let mut metadata = serde_json::to_value(MetalSongMetadata::from_song(&song)).unwrap_or_default();
+        if let Some(fields) = metadata.as_object_mut() {
+            fields.insert("name".into(), serde_json::json!(song_name));
+            fields.insert("artist".into(), serde_json::json!(config.metadata.artist));
+            fields.insert("genre".into(), serde_json::json!(genre_tags));
+            fields.insert("date".into(), serde_json::json!(date));
+            fields.insert("duration".into(), serde_json::json!(duration_seconds));
+            fields.insert("render".into(), serde_json::to_value(&stats).unwrap_or_default());
         }
+        
+        report_optional_save("metadata", &json_path, write_metadata_json(&json_path, &metadata));
     }
     
     println!();
@@ -163,65 +298,195 @@ fn main() {
     println!("   Style: {:?}", subgenre);
     println!("   Tempo: {} BPM", song.tempo);
     println!("   Duration: {:.1}s", duration_seconds);
+    println!();
+
+    Some(TrackSummary {
+        name: song_name,
+        subgenre,
+        tempo: song.tempo,
+        duration_seconds,
+        seed,
+    })
 }
 
-/// Save audio samples to a WAV file
-fn save_wav(filename: &str, samples: &[f32], sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs::File;
-    use std::io::Write;
-    
-    let mut file = File::create(filename)?;
-    
-    // WAV header
-    let num_samples = samples.len() as u32;
-    let byte_rate = sample_rate * 2; // 16-bit mono
-    let data_size = num_samples * 2;
-    let file_size = data_size + 36;
-    
-    // RIFF header
-    file.write_all(b"RIFF")?;
-    file.write_all(&file_size.to_le_bytes())?;
-    file.write_all(b"WAVE")?;
-    
-    // fmt chunk
-    file.write_all(b"fmt ")?;
-    file.write_all(&16u32.to_le_bytes())?; // chunk size
-    file.write_all(&1u16.to_le_bytes())?;  // audio format (PCM)
-    file.write_all(&1u16.to_le_bytes())?;  // num channels (mono)
-    file.write_all(&sample_rate.to_le_bytes())?;
-    file.write_all(&byte_rate.to_le_bytes())?;
-    file.write_all(&2u16.to_le_bytes())?;  // block align
-    file.write_all(&16u16.to_le_bytes())?; // bits per sample
-    
-    // data chunk
-    file.write_all(b"data")?;
-    file.write_all(&data_size.to_le_bytes())?;
-    
-    // Write audio data (convert f32 to i16)
-    for &sample in samples {
-        let sample_i16 = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
-        file.write_all(&sample_i16.to_le_bytes())?;
+/// Report samples beyond full scale in a saved WAV: a warning with the limiter
+/// suggestion for 16-bit output, where overs are crushed, otherwise just a note
+fn warn_on_clipping(report: &ClipReport, bit_depth: BitDepth) {
+    if !report.is_clipping() {
+        return;
+    }
+    let overs = format!(
+        "{} samples over full scale (true peak {:+.2} dBFS, worst at {:.2}s)",
+        report.clipped_samples,
+        report.true_peak_dbfs(),
+        report.worst_time(get_sample_rate())
+    );
+    match bit_depth {
+        BitDepth::Int16 => {
+            eprintln!("⚠️  Warning: {}", overs);
+            eprintln!("   Enable the limiter or lower the master level to avoid clipped output");
+        }
+        BitDepth::Int24 => println!("📊 {} (clamped in the 24-bit file)", overs),
+        BitDepth::Float32 => println!("📊 {} (kept as float headroom)", overs),
     }
-    
-    Ok(())
 }
 
-/// Calculate section duration based on bars and tempo
-fn get_section_duration(section: composition::metal_song_generator::MetalSection, tempo: u16) -> f32 {
-    use composition::metal_song_generator::MetalSection;
-    
-    let bars = match section {
-        MetalSection::Intro => 4,
-        MetalSection::Verse => 8,
-        MetalSection::Chorus => 8,
-        MetalSection::Breakdown => 4,
-        MetalSection::Solo => 12,
-        MetalSection::Outro => 4,
-    };
-    
-    // Calculate duration: bars * beats_per_bar * seconds_per_beat
-    let beats_per_bar = 4.0;
-    let seconds_per_beat = 60.0 / tempo as f32;
-    bars as f32 * beats_per_bar * seconds_per_beat
+/// Report an optional output (MP3, FLAC, OGG, metadata): failures warn but don't stop the run
+fn report_optional_save(kind: &str, path: &str, result: Result<(), SaveError>) {
+    match result {
+        Ok(()) => println!("✅ Successfully created: {}", path),
+        Err(SaveError::Encode(msg)) => eprintln!("⚠️  Warning: Could not encode {}: {}", kind, msg),
+        Err(e) => eprintln!("⚠️  Warning: Could not create {}: {}", kind, e),
+    }
+}
+
+/// Print the names, subgenres and durations of every track in a batch
+fn print_batch_summary(summaries: &[TrackSummary]) {
+    println!("📀 Batch Summary:");
+    println!("   {:>3}  {:<32} {:<18} {:>5} {:>8} {:>20}", "#", "Name", "Subgenre", "BPM", "Length", "Seed");
+    for (i, track) in summaries.iter().enumerate() {
+        println!(
+            "   {:>3}  {:<32} {:<18} {:>5} {:>5}:{:02} {:>20}",
+            i + 1,
+            track.name,
+            track.subgenre.cli_name(),
+            track.tempo,
+            (track.duration_seconds / 60.0) as u32,
+            (track.duration_seconds % 60.0) as u32,
+            track.seed,
+        );
+    }
+    let total: f32 = summaries.iter().map(|t| t.duration_seconds).sum();
+    println!("   Total: {} tracks, {:.1} min", summaries.len(), total / 60.0);
+}
+
+/// Parse command-line flags; unknown flags or bad values are errors
+fn parse_args(args: &[String]) -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut iter = args.iter();
+
+    while let Some(flag) = iter.next() {
+        if flag == "-h" || flag == "--help" {
+            cli.help = true;
+            continue;
+        }
+        if flag == "--click" {
+            cli.click = true;
+            continue;
+        }
+        if flag == "--plan" {
+            cli.plan = true;
+            continue;
+        }
+
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--subgenre" => {
+                cli.subgenre = Some(MetalSubgenre::from_name(value).ok_or_else(|| {
+                    format!("Unknown subgenre '{}'. Valid options: {}", value, subgenre_names())
+                })?);
+            }
+            "--tempo" => {
+                let tempo: u16 = value.parse().map_err(|_| format!("Invalid tempo '{}'", value))?;
+                if !(MIN_TEMPO..=MAX_TEMPO).contains(&tempo) {
+                    return Err(format!("--tempo must be between {} and {} BPM (got {})", MIN_TEMPO, MAX_TEMPO, tempo));
+                }
+                cli.tempo = Some(tempo);
+            }
+            "--seed" => {
+                cli.seed = Some(value.parse().map_err(|_| format!("Invalid seed '{}'", value))?);
+            }
+            "--out" => cli.out = Some(value.clone()),
+            "--count" => {
+                let count: usize = value.parse().map_err(|_| format!("Invalid count '{}'", value))?;
+                if count == 0 {
+                    return Err("--count must be at least 1".to_string());
+                }
+                cli.count = Some(count);
+            }
+            _ => return Err(format!("Unknown argument '{}'", flag)),
+        }
+    }
+
+    Ok(cli)
 }
 
+/// Comma-separated list of valid --subgenre values
+fn subgenre_names() -> String {
+    MetalSubgenre::ALL
+        .iter()
+        .map(|s| s.cli_name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Split an --out path into (directory, file stem); other formats share the stem
+fn split_output_path(out: &str) -> (String, String) {
+    let path = Path::new(out);
+    let dir = path
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| ".".to_string());
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "song".to_string());
+    (dir, stem)
+}
+
+/// Print a single-line progress status (overwritten in place)
+fn print_progress(fraction: f32, label: &str) {
+    use std::io::Write;
+
+    print!("\r   [{:>3.0}%] {:<40}", fraction * 100.0, label);
+    let _ = std::io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args_overrides() {
+        let cli = parse_args(&args(&[
+            "--subgenre", "death-metal", "--tempo", "200", "--seed", "42", "--out", "./song.wav",
+        ]))
+        .unwrap();
+        assert_eq!(cli.subgenre, Some(MetalSubgenre::DeathMetal));
+        assert_eq!(cli.tempo, Some(200));
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(split_output_path(cli.out.as_deref().unwrap()), (".".to_string(), "song".to_string()));
+    }
+
+    #[test]
+    fn test_click_flag_takes_no_value() {
+        let cli = parse_args(&args(&["--click", "--seed", "7"])).unwrap();
+        assert!(cli.click);
+        assert_eq!(cli.seed, Some(7));
+        assert!(!parse_args(&args(&[])).unwrap().click);
+
+        let cli = parse_args(&args(&["--plan", "--subgenre", "doom-metal"])).unwrap();
+        assert!(cli.plan && !cli.click);
+        assert_eq!(cli.subgenre, Some(MetalSubgenre::DoomMetal));
+    }
+
+    #[test]
+    fn test_out_of_range_tempo_is_rejected() {
+        assert!(parse_args(&args(&["--tempo", "20"])).unwrap_err().contains("between 40 and 320"));
+        assert!(parse_args(&args(&["--tempo", "400"])).is_err());
+        assert_eq!(parse_args(&args(&["--tempo", "320"])).unwrap().tempo, Some(320));
+    }
+
+    #[test]
+    fn test_unknown_subgenre_lists_options() {
+        let err = parse_args(&args(&["--subgenre", "polka"])).unwrap_err();
+        assert!(err.contains("death-metal") && err.contains("doom-metal"));
+    }
+}
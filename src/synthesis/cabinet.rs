@@ -64,6 +64,13 @@ impl CabinetSimulator {
         )
     }
 
+    /// Clear filter state, as if the speaker had been silent
+    pub fn reset(&mut self) {
+        self.lpf.reset();
+        self.hpf_prev_input = 0.0;
+        self.hpf_prev_output = 0.0;
+    }
+
     /// Process a single sample through the cabinet simulator
     pub fn process(&mut self, input: f32) -> f32 {
         let sample_rate = get_sample_rate() as f32;
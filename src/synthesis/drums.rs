@@ -1,5 +1,7 @@
+use crate::composition::metal_song_generator::MetalSubgenre;
 use crate::utils::get_sample_rate;
 use rand::Rng;
+use std::f32::consts::TAU;
 
 /// Per-song drum sound variation parameters
 #[derive(Clone, Copy)]
@@ -16,7 +18,7 @@ pub struct DrumSoundParams {
 
 impl DrumSoundParams {
     pub fn generate() -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         DrumSoundParams {
             kick_pitch_offset: rng.gen_range(-5.0..5.0),
             kick_decay_offset: rng.gen_range(-0.5..0.5),
@@ -30,17 +32,116 @@ impl DrumSoundParams {
     }
 }
 
+/// Drum kit tone: how the kit is tuned and set up, as opposed to the per-song
+/// variation in `DrumSoundParams`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetalDrumKit {
+    pub kick_pitch: f32,  // Sub frequency the kick settles on (Hz)
+    pub kick_click: f32,  // Beater click (1.0 = stock, higher = triggered)
+    pub kick_decay: f32,  // Kick body decay rate (higher = tighter, lower = open and boomy)
+    pub snare_tuning: f32, // Snare shell frequency (Hz)
+    pub snare_wires: f32, // Snare wire snap (1.0 = stock)
+    pub tom_pitch: f32,   // Floor tom frequency (Hz)
+}
+
+impl MetalDrumKit {
+    /// Stock kit
+    pub fn new() -> Self {
+        MetalDrumKit {
+            kick_pitch: 45.0,
+            kick_click: 1.0,
+            kick_decay: 8.0,
+            snare_tuning: 190.0,
+            snare_wires: 1.0,
+            tom_pitch: 80.0,
+        }
+    }
+
+    /// Triggered, clicky kick and a high, cracking snare that cut through blasts
+    pub fn death_metal() -> Self {
+        MetalDrumKit {
+            kick_pitch: 52.0,
+            kick_click: 1.6,
+            kick_decay: 12.0,
+            snare_tuning: 220.0,
+            snare_wires: 1.1,
+            tom_pitch: 95.0,
+        }
+    }
+
+    /// Punchy, dry kit with a bright snare
+    pub fn thrash() -> Self {
+        MetalDrumKit {
+            kick_pitch: 48.0,
+            kick_click: 1.3,
+            kick_decay: 10.0,
+            snare_tuning: 205.0,
+            snare_wires: 1.2,
+            tom_pitch: 88.0,
+        }
+    }
+
+    /// Big open kick, fat low snare and deep toms
+    pub fn doom() -> Self {
+        MetalDrumKit {
+            kick_pitch: 40.0,
+            kick_click: 0.6,
+            kick_decay: 4.5,
+            snare_tuning: 160.0,
+            snare_wires: 0.8,
+            tom_pitch: 65.0,
+        }
+    }
+
+    /// Kit each subgenre is played on
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        match subgenre {
+            MetalSubgenre::DeathMetal => Self::death_metal(),
+            MetalSubgenre::ThrashMetal => Self::thrash(),
+            MetalSubgenre::DoomMetal => Self::doom(),
+            MetalSubgenre::HeavyMetal | MetalSubgenre::ProgressiveMetal => Self::new(),
+        }
+    }
+}
+
+impl Default for MetalDrumKit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert a MIDI velocity (0-127) to the normalized 0.0-1.0 velocity the drum synths take
+pub fn normalize_velocity(midi_velocity: u8) -> f32 {
+    (midi_velocity.min(127) as f32 / 127.0).clamp(0.0, 1.0)
+}
+
+/// Gains of the (transient, body) layers for a normalized velocity: the body only
+/// fills in as the hit gets harder, so a soft hit is a bodyless tick and a full
+/// hit cracks with its whole weight behind it
+pub fn layer_balance(velocity: f32) -> (f32, f32) {
+    let velocity = velocity.clamp(0.0, 1.0);
+    (0.5 + 0.5 * velocity, velocity * velocity)
+}
+
 /// Generate a Metal Kick: Massive click, sub weight, aggressive compression
-pub fn generate_kick(amplitude: f32) -> Vec<f32> {
-    generate_kick_with_params(amplitude, None)
+/// `velocity` is normalized (0.0-1.0): harder hits are louder, clickier and more saturated
+pub fn generate_kick(velocity: f32) -> Vec<f32> {
+    generate_kick_with_params(velocity, None)
+}
+
+pub fn generate_kick_with_params(velocity: f32, params: Option<&DrumSoundParams>) -> Vec<f32> {
+    generate_kick_with_kit(velocity, params, &MetalDrumKit::new())
 }
 
-pub fn generate_kick_with_params(amplitude: f32, params: Option<&DrumSoundParams>) -> Vec<f32> {
-    let mut rng = rand::thread_rng();
+/// Kick on a specific kit
+pub fn generate_kick_with_kit(velocity: f32, params: Option<&DrumSoundParams>, kit: &MetalDrumKit) -> Vec<f32> {
+    let velocity = velocity.clamp(0.0, 1.0);
+    let mut rng = crate::utils::rng();
     let duration = 0.4; // Slightly shorter for tighter sound
     let base_pitch = 60.0; // Higher base for more click
     
     let start_pitch = if let Some(p) = params { base_pitch + p.kick_pitch_offset } else { base_pitch };
+    let (transient_gain, body_gain) = layer_balance(velocity);
     
     let num_samples = (duration * get_sample_rate() as f32) as usize;
     let mut samples = Vec::with_capacity(num_samples);
@@ -48,45 +149,54 @@ pub fn generate_kick_with_params(amplitude: f32, params: Option<&DrumSoundParams
     for i in 0..num_samples {
         let time = i as f32 / get_sample_rate() as f32;
 
-        // AGGRESSIVE pitch envelope: Start at 220Hz (beater attack), drop to 45Hz (sub)
+        // AGGRESSIVE pitch envelope: Start ~175Hz above the sub (beater attack), drop to the kit's sub
         let pitch_drop = (-time * 50.0).exp(); // Faster drop
-        let pitch = 45.0 + (175.0 * pitch_drop);
+        let pitch = kit.kick_pitch + (175.0 * pitch_drop);
 
         // SHARPER amplitude envelope for punch
-        let amp_env = (-time * 8.0).exp();
+        let amp_env = (-time * kit.kick_decay).exp();
 
-        // Main Body (Sine + Triangle blend for weight)
+        // BODY LAYER (Sine + Triangle blend for weight)
         let phase = 2.0 * std::f32::consts::PI * pitch * time;
         let body = (phase.sin() * 0.7 + (phase * 0.5).sin().signum() * 0.3) * amp_env;
 
-        // AGGRESSIVE CLICK: Sharp beater attack
-        let click_amp = params.map(|p| p.kick_click_amount).unwrap_or(1.2); 
+        // TRANSIENT LAYER: Sharp beater attack
+        let click_amp = params.map(|p| p.kick_click_amount).unwrap_or(1.2) * kit.kick_click;
         let click_env = (-time * 180.0).exp(); // Very fast decay
         let click = (rng.gen_range(-1.0..1.0)) * click_amp * click_env;
 
         // More click in the mix for modern metal
-        let mut sample = body + click * 0.4;
+        let mut sample = body * body_gain + click * 0.4 * transient_gain;
 
-        // HARD saturation for that "basketball" thud
-        sample = (sample * 4.5).tanh(); 
+        // HARD saturation for that "basketball" thud - drive follows velocity
+        let drive = 2.0 + 2.5 * velocity;
+        sample = (sample * drive).tanh(); 
 
-        samples.push(sample * amplitude);
+        samples.push(sample * velocity);
     }
 
     samples
 }
 
 /// Generate a Metal Snare: Gunshot quality
-pub fn generate_snare(amplitude: f32) -> Vec<f32> {
-    generate_snare_with_params(amplitude, None)
+/// `velocity` is normalized (0.0-1.0): ghost notes are darker, full hits crack
+pub fn generate_snare(velocity: f32) -> Vec<f32> {
+    generate_snare_with_params(velocity, None)
 }
 
-pub fn generate_snare_with_params(amplitude: f32, params: Option<&DrumSoundParams>) -> Vec<f32> {
-    let mut rng = rand::thread_rng();
+pub fn generate_snare_with_params(velocity: f32, params: Option<&DrumSoundParams>) -> Vec<f32> {
+    generate_snare_with_kit(velocity, params, &MetalDrumKit::new())
+}
+
+/// Snare on a specific kit
+pub fn generate_snare_with_kit(velocity: f32, params: Option<&DrumSoundParams>, kit: &MetalDrumKit) -> Vec<f32> {
+    let velocity = velocity.clamp(0.0, 1.0);
+    let mut rng = crate::utils::rng();
     let duration = 0.3; // Shorter for tighter sound
-    let base_freq = 190.0; // Slightly higher for more crack
+    let base_freq = kit.snare_tuning;
     
     let freq = if let Some(p) = params { base_freq + p.snare_freq_offset } else { base_freq };
+    let (transient_gain, body_gain) = layer_balance(velocity);
 
     let num_samples = (duration * get_sample_rate() as f32) as usize;
     let mut samples = Vec::with_capacity(num_samples);
@@ -97,37 +207,41 @@ pub fn generate_snare_with_params(amplitude: f32, params: Option<&DrumSoundParam
         // SHARPER envelope for more attack
         let amp_env = (-time * 12.0).exp();
 
-        // Tonal Body (Pitch dive)
+        // BODY LAYER: Tonal shell (Pitch dive)
         let pitch_mod = 1.0 - (-time * 25.0).exp() * 0.3;
         let phase = 2.0 * std::f32::consts::PI * freq * pitch_mod * time;
         let body = phase.sin() * amp_env * 0.35;
 
-        // MORE NOISE for aggressive crack
-        let noise_amp = params.map(|p| p.snare_noise_amount).unwrap_or(1.2);
+        // TRANSIENT LAYER: MORE NOISE for aggressive crack (snare wires)
+        let noise_amp = params.map(|p| p.snare_noise_amount).unwrap_or(1.2) * kit.snare_wires;
         let noise = rng.gen_range(-1.0..1.0) * amp_env * 0.9 * noise_amp;
 
-        let mut sample = body + noise;
+        let mut sample = body * body_gain + noise * transient_gain;
         
         // HARDER clipping for that "gunshot" quality
-        sample = (sample * 3.0).clamp(-0.95, 0.95);
+        let drive = 1.5 + 1.5 * velocity;
+        sample = (sample * drive).clamp(-0.95, 0.95);
 
-        samples.push(sample * amplitude);
+        samples.push(sample * velocity);
     }
 
     samples
 }
 
 // Keep existing Cymbals/Toms/China (China is good)
-pub fn generate_hihat(amplitude: f32, open: bool) -> Vec<f32> {
-    generate_hihat_with_params(amplitude, open, None)
+/// `velocity` is normalized (0.0-1.0): harder hits are brighter
+pub fn generate_hihat(velocity: f32, open: bool) -> Vec<f32> {
+    generate_hihat_with_params(velocity, open, None)
 }
 
-pub fn generate_hihat_with_params(amplitude: f32, open: bool, params: Option<&DrumSoundParams>) -> Vec<f32> {
+pub fn generate_hihat_with_params(velocity: f32, open: bool, params: Option<&DrumSoundParams>) -> Vec<f32> {
+    let velocity = velocity.clamp(0.0, 1.0);
     let duration = if open { 0.5 } else { 0.05 };
-    let _brightness = params.map(|p| p.hihat_brightness).unwrap_or(1.0);
+    let brightness = params.map(|p| p.hihat_brightness).unwrap_or(1.0) * (0.5 + 0.5 * velocity);
     let num_samples = (duration * get_sample_rate() as f32) as usize;
     let mut samples = Vec::with_capacity(num_samples);
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
+    let mut prev_noise = 0.0;
 
     for i in 0..num_samples {
         let time = i as f32 / get_sample_rate() as f32;
@@ -137,32 +251,59 @@ pub fn generate_hihat_with_params(amplitude: f32, open: bool, params: Option<&Dr
         // White noise high-passed
         let noise = rng.gen_range(-1.0..1.0);
         
-        // Simple high-pass effect
-        samples.push(noise * amp_env * amplitude * 0.7);
+        // Simple high-pass effect: first difference, blended by brightness
+        let high = (noise - prev_noise) * 0.5;
+        prev_noise = noise;
+        let tone = high * brightness + noise * (1.0 - brightness);
+        samples.push(tone * amp_env * velocity * 0.7);
     }
     samples
 }
 
 pub fn generate_crash(amplitude: f32) -> Vec<f32> { crate::synthesis::drums::generate_china(amplitude) } // Re-use China logic for Crash for trashier sound
-pub fn generate_ride(amplitude: f32) -> Vec<f32> { crate::synthesis::drums::generate_china(amplitude * 0.6) } // Temporary mapping
-pub fn generate_tom(amplitude: f32) -> Vec<f32> { 
+pub fn generate_tom(amplitude: f32) -> Vec<f32> {
+    generate_tom_with_kit(amplitude, &MetalDrumKit::new())
+}
+
+/// Tom on a specific kit
+pub fn generate_tom_with_kit(amplitude: f32, kit: &MetalDrumKit) -> Vec<f32> {
    // Deep heavy tom
    let duration = 0.6;
    let num_samples = (duration * get_sample_rate() as f32) as usize;
    let mut samples = Vec::with_capacity(num_samples);
    for i in 0..num_samples {
        let time = i as f32 / get_sample_rate() as f32;
-       let pitch = 80.0 * (1.0 - time * 3.0).max(0.5);
+       let pitch = kit.tom_pitch * (1.0 - time * 3.0).max(0.5);
        let val = (time * pitch * 6.28).sin() * (-time * 4.0).exp();
        samples.push((val * 2.0).tanh() * amplitude);
    }
    samples
 }
+
+/// Ride: a pinging bell over a quiet wash, short enough to play on every eighth
+pub fn generate_ride(amplitude: f32) -> Vec<f32> {
+    let duration = 0.6;
+    let num_samples = (duration * get_sample_rate() as f32) as usize;
+    let mut samples = Vec::with_capacity(num_samples);
+    let mut rng = crate::utils::rng();
+    let mut prev_noise = 0.0;
+    for i in 0..num_samples {
+        let time = i as f32 / get_sample_rate() as f32;
+        // Inharmonic partials give the "ping"; they ring longer than the wash
+        let ping = ((time * 3150.0 * TAU).sin() + (time * 4730.0 * TAU).sin() * 0.6) * (-time * 9.0).exp();
+        let noise = rng.gen_range(-1.0..1.0);
+        let wash = (noise - prev_noise) * 0.5 * (-time * 6.0).exp();
+        prev_noise = noise;
+        samples.push((ping * 0.3 + wash * 0.4) * amplitude);
+    }
+    samples
+}
+
 pub fn generate_china(amplitude: f32) -> Vec<f32> {
     let duration = 1.2;
     let num_samples = (duration * get_sample_rate() as f32) as usize;
     let mut samples = Vec::with_capacity(num_samples);
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
     for i in 0..num_samples {
         let time = i as f32 / get_sample_rate() as f32;
         let env = (-time * 5.0).exp();
@@ -176,25 +317,45 @@ pub fn generate_china(amplitude: f32) -> Vec<f32> {
 /// Metal Drums synthesizer
 pub struct MetalDrums {
     params: DrumSoundParams,
+    kit: MetalDrumKit,
 }
 
 impl MetalDrums {
     pub fn new() -> Self {
+        Self::with_kit(MetalDrumKit::new())
+    }
+
+    /// Drums played on a specific kit
+    pub fn with_kit(kit: MetalDrumKit) -> Self {
         Self {
             params: DrumSoundParams::generate(),
+            kit,
         }
     }
 
-    pub fn generate_kick(&self, amplitude: f32) -> Vec<f32> {
-        generate_kick_with_params(amplitude, Some(&self.params))
+    /// Swap the kit (keeps this song's sound variation)
+    pub fn set_kit(&mut self, kit: MetalDrumKit) {
+        self.kit = kit;
+    }
+
+    /// Kit being played
+    pub fn kit(&self) -> MetalDrumKit {
+        self.kit
     }
 
-    pub fn generate_snare(&self, amplitude: f32) -> Vec<f32> {
-        generate_snare_with_params(amplitude, Some(&self.params))
+    /// Kick at normalized velocity (0.0-1.0)
+    pub fn generate_kick(&self, velocity: f32) -> Vec<f32> {
+        generate_kick_with_kit(velocity, Some(&self.params), &self.kit)
     }
 
-    pub fn generate_hihat(&self, amplitude: f32, open: bool) -> Vec<f32> {
-        generate_hihat_with_params(amplitude, open, Some(&self.params))
+    /// Snare at normalized velocity (0.0-1.0)
+    pub fn generate_snare(&self, velocity: f32) -> Vec<f32> {
+        generate_snare_with_kit(velocity, Some(&self.params), &self.kit)
+    }
+
+    /// Hi-hat at normalized velocity (0.0-1.0)
+    pub fn generate_hihat(&self, velocity: f32, open: bool) -> Vec<f32> {
+        generate_hihat_with_params(velocity, open, Some(&self.params))
     }
 
     pub fn generate_crash(&self, amplitude: f32) -> Vec<f32> {
@@ -206,10 +367,73 @@ impl MetalDrums {
     }
 
     pub fn generate_tom(&self, amplitude: f32) -> Vec<f32> {
-        generate_tom(amplitude)
+        generate_tom_with_kit(amplitude, &self.kit)
     }
 
     pub fn generate_china(&self, amplitude: f32) -> Vec<f32> {
         generate_china(amplitude)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sum-of-squares energy of the signal
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    /// Energy of the signal's first difference
+    fn edge(samples: &[f32]) -> f32 {
+        samples.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum()
+    }
+
+    /// Share of the signal's energy in its first-difference (high-frequency content)
+    fn crack(samples: &[f32]) -> f32 {
+        edge(samples) / energy(samples)
+    }
+
+    #[test]
+    fn test_soft_hits_are_bodyless_hard_hits_crack() {
+        assert_eq!(layer_balance(0.0).1, 0.0);
+        let (soft_transient, soft_body) = layer_balance(0.2);
+        let (hard_transient, hard_body) = layer_balance(1.0);
+        assert!(soft_transient / soft_body > hard_transient / hard_body);
+        assert!(hard_transient > soft_transient);
+
+        // The click is gone after ~10ms; what rings on is the body
+        crate::utils::seed_rng(7);
+        let attack = (0.01 * get_sample_rate() as f32) as usize;
+        let soft_kick = generate_kick(0.2);
+        let hard_kick = generate_kick(1.0);
+        let ring = |kick: &[f32]| energy(&kick[3 * attack..]) / energy(&kick[..attack]);
+        assert!(ring(&soft_kick) < ring(&hard_kick));
+        assert!(edge(&hard_kick[..attack]) > edge(&soft_kick[..attack]));
+
+        let soft_snare = generate_snare(0.2);
+        let hard_snare = generate_snare(1.0);
+        assert!(edge(&hard_snare) > edge(&soft_snare));
+    }
+
+    #[test]
+    fn test_kits_sound_distinct() {
+        let attack = (0.01 * get_sample_rate() as f32) as usize;
+
+        crate::utils::seed_rng(11);
+        let death = generate_kick_with_kit(1.0, None, &MetalDrumKit::for_subgenre(MetalSubgenre::DeathMetal));
+        crate::utils::seed_rng(11);
+        let doom = generate_kick_with_kit(1.0, None, &MetalDrumKit::for_subgenre(MetalSubgenre::DoomMetal));
+
+        // Death metal kick is all click; the doom kick keeps booming after it
+        assert!(crack(&death[..attack]) > crack(&doom[..attack]));
+        let tail = death.len() / 2;
+        assert!(energy(&doom[tail..]) > energy(&death[tail..]) * 2.0);
+
+        // The stock kit is the one the plain generators use
+        crate::utils::seed_rng(3);
+        let stock = generate_snare(0.8);
+        crate::utils::seed_rng(3);
+        assert_eq!(generate_snare_with_kit(0.8, None, &MetalDrumKit::default()), stock);
+    }
+}
@@ -15,6 +15,11 @@ impl LowPassFilter {
         }
     }
 
+    /// Clear filter state
+    pub fn reset(&mut self) {
+        self.prev_output = 0.0;
+    }
+
     pub fn process(&mut self, input: f32) -> f32 {
         let resonance_adjust = (1.0 + self.resonance).max(0.1);
         let alpha = (self.cutoff / (self.cutoff + resonance_adjust)).clamp(0.0, 1.0);
@@ -1,14 +1,75 @@
 /// Audio effects and transitions for production polish
+use crate::config::CountIn;
 use crate::utils::get_sample_rate;
 use rand::Rng;
 
+/// Length of the breakdown drop kick in seconds (longer than a normal kick for dramatic effect)
+pub const DROP_KICK_DURATION: f32 = 2.0;
+
+/// Metronome tick: a 20 ms sine blip, higher and louder on the bar's downbeat
+pub fn generate_click(accent: bool) -> Vec<f32> {
+    let (frequency, amplitude) = if accent { (1600.0, 0.6) } else { (1000.0, 0.4) };
+    let sample_rate = get_sample_rate() as f32;
+    let num_samples = (0.02 * sample_rate) as usize;
+
+    (0..num_samples)
+        .map(|i| {
+            let time = i as f32 / sample_rate;
+            (2.0 * std::f32::consts::PI * frequency * time).sin() * (-time * 250.0).exp() * amplitude
+        })
+        .collect()
+}
+
+/// One 4/4 bar of count-in at `tempo` (empty for `CountIn::None`)
+pub fn generate_count_in(count_in: CountIn, tempo: u16) -> Vec<f32> {
+    let sample_rate = get_sample_rate() as f32;
+    let beat_samples = (60.0 / tempo.max(1) as f32 * sample_rate) as usize;
+
+    match count_in {
+        CountIn::None => Vec::new(),
+        CountIn::Clicks => {
+            let mut samples = vec![0.0; beat_samples * 4];
+            for beat in 0..4 {
+                for (sample, tick) in samples[beat * beat_samples..].iter_mut().zip(generate_click(beat == 0)) {
+                    *sample += tick;
+                }
+            }
+            samples
+        }
+        CountIn::Crash => generate_crash_swell((beat_samples * 4) as f32 / sample_rate),
+    }
+}
+
+/// Reversed cymbal: ring-modulated noise that swells over `duration` seconds and
+/// stops dead at its loudest
+pub fn generate_crash_swell(duration: f32) -> Vec<f32> {
+    let sample_rate = get_sample_rate() as f32;
+    let num_samples = (duration.max(0.0) * sample_rate) as usize;
+    let mut samples = Vec::with_capacity(num_samples);
+    let mut rng = crate::utils::rng();
+
+    for i in 0..num_samples {
+        let time = i as f32 / sample_rate;
+        let progress = i as f32 / num_samples as f32;
+
+        // A crash's decay played backwards, faded up from silence so the swell has no click
+        let remaining = (num_samples - i) as f32 / sample_rate;
+        let envelope = (-remaining * 3.0).exp() * progress;
+        let noise = rng.gen_range(-1.0..1.0);
+        let metal = (time * 400.0 * std::f32::consts::TAU).sin() * (time * 340.0 * std::f32::consts::TAU).sin();
+        samples.push((noise + metal * 0.5) * envelope * 0.4);
+    }
+
+    samples
+}
+
 /// Generate a heavy drop kick for breakdowns
 /// This is an extended, aggressive kick drum with massive low-end
 pub fn generate_drop_kick() -> Vec<f32> {
-    let duration = 2.0; // Longer than a normal kick for dramatic effect
+    let duration = DROP_KICK_DURATION;
     let num_samples = (duration * get_sample_rate() as f32) as usize;
     let mut samples = Vec::with_capacity(num_samples);
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
 
     for i in 0..num_samples {
         let time = i as f32 / get_sample_rate() as f32;
@@ -39,3 +100,52 @@ pub fn generate_drop_kick() -> Vec<f32> {
 
     samples
 }
+
+/// Noise riser for build-ups: filtered white noise whose cutoff sweeps up and whose
+/// level swells over `duration` seconds, peaking right at the end
+pub fn generate_riser(duration: f32) -> Vec<f32> {
+    let sample_rate = get_sample_rate() as f32;
+    let num_samples = (duration.max(0.0) * sample_rate) as usize;
+    let mut samples = Vec::with_capacity(num_samples);
+    let mut rng = crate::utils::rng();
+    let mut filtered = 0.0_f32;
+
+    for i in 0..num_samples {
+        let progress = i as f32 / num_samples as f32;
+
+        // One-pole low-pass opening from 200 Hz to 8 kHz
+        let cutoff = 200.0 * 40.0_f32.powf(progress);
+        let coefficient = 1.0 - (-2.0 * std::f32::consts::PI * cutoff / sample_rate).exp();
+        filtered += (rng.gen_range(-1.0..1.0) - filtered) * coefficient;
+
+        samples.push(filtered * progress * progress * 0.5);
+    }
+
+    samples
+}
+
+/// Sub-bass "bomb" under a breakdown hit: a pure sine that lands a few semitones
+/// sharp, settles onto `frequency` and swells out over `duration` seconds
+pub fn generate_sub_drop(frequency: f32, duration: f32) -> Vec<f32> {
+    let sample_rate = get_sample_rate() as f32;
+    let num_samples = (duration.max(0.0) * sample_rate) as usize;
+    let attack_samples = (0.004 * sample_rate).max(1.0);
+    let mut samples = Vec::with_capacity(num_samples);
+    let mut phase = 0.0_f32;
+
+    for i in 0..num_samples {
+        let time = i as f32 / sample_rate;
+
+        // Short downward glide into the root gives the drop its "thump"
+        let pitch = frequency * (1.0 + 0.25 * (-time * 30.0).exp());
+
+        // Fast attack, then a decay that's gone by the end of the note
+        let progress = i as f32 / num_samples as f32;
+        let envelope = (i as f32 / attack_samples).min(1.0) * (1.0 - progress) * (-progress * 2.0).exp();
+
+        samples.push((2.0 * std::f32::consts::PI * phase).sin() * envelope);
+        phase = (phase + pitch / sample_rate).fract();
+    }
+
+    samples
+}
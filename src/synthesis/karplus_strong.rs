@@ -19,6 +19,25 @@ pub enum PlayingTechnique {
     SingleNote,         // Standard single note
 }
 
+/// Lowest string frequency we model (F#1 on an 8-string is ~46 Hz)
+const LOWEST_STRING_HZ: f32 = 40.0;
+
+/// Highest fretted frequency we model (high E around the 17th fret)
+const HIGHEST_STRING_HZ: f32 = 880.0;
+
+/// String damping from pitch: 0.0 = lowest string (rings long and dark),
+/// 1.0 = top of the high E (dies fast, bright)
+pub fn string_damping(frequency: f32) -> f32 {
+    let position = (frequency.max(1.0) / LOWEST_STRING_HZ).log2()
+        / (HIGHEST_STRING_HZ / LOWEST_STRING_HZ).log2();
+    position.clamp(0.0, 1.0)
+}
+
+/// Scale a technique's per-period decay by string damping (more loss on higher strings)
+fn damped_decay(base_decay: f32, damping: f32) -> f32 {
+    1.0 - (1.0 - base_decay) * (0.6 + 0.8 * damping)
+}
+
 /// Karplus-Strong string synthesizer for realistic guitar/bass sounds
 /// Based on research: physical modeling of plucked strings
 #[derive(Debug, Clone)]
@@ -28,16 +47,25 @@ pub struct KarplusStrong {
     decay_factor: f32,
     damping_filter: LowPassFilter,
     technique: PlayingTechnique,
+    damping: f32,
 }
 
 impl KarplusStrong {
     /// Create a new Karplus-Strong synthesizer for a given frequency
+    /// (damping follows the pitch: low strings ring longer and darker)
     pub fn new(frequency: f32, technique: PlayingTechnique) -> Self {
+        Self::with_damping(frequency, technique, string_damping(frequency))
+    }
+
+    /// Create a synthesizer with explicit string damping (0.0 = low string, 1.0 = high string),
+    /// e.g. derived from a `FretPosition.string` instead of the pitch
+    pub fn with_damping(frequency: f32, technique: PlayingTechnique, damping: f32) -> Self {
+        let damping = damping.clamp(0.0, 1.0);
         let sample_rate = get_sample_rate() as f32;
         let buffer_length = (sample_rate / frequency).round() as usize;
         
         // Initialize buffer with white noise (the "pluck")
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::utils::rng();
         let buffer: Vec<f32> = (0..buffer_length)
             .map(|_| rng.gen_range(-1.0..1.0))
             .collect();
@@ -54,12 +82,17 @@ impl KarplusStrong {
             PlayingTechnique::PowerChordOctave | PlayingTechnique::MinorChordThird => (0.97, 6000.0),
         };
 
+        // Per-string tone: wound low strings are darker, plain high strings brighter
+        let decay_factor = damped_decay(decay_factor, damping);
+        let filter_cutoff = filter_cutoff * (0.6 + 0.6 * damping);
+
         KarplusStrong {
             buffer,
             buffer_index: 0,
             decay_factor,
             damping_filter: LowPassFilter::new(filter_cutoff, 0.7),
             technique,
+            damping,
         }
     }
 
@@ -176,15 +209,15 @@ pub fn generate_metal_bass_string(
     let num_samples = (duration * sample_rate) as usize;
     
     let mut synth = KarplusStrong::new(frequency, PlayingTechnique::Open);
-    // Increase decay for bass sustain
-    synth.decay_factor = 0.999; // Longer sustain
+    // Increase decay for bass sustain (still shorter on the higher strings)
+    synth.decay_factor = damped_decay(0.999, synth.damping); // Longer sustain
     
     // MUCH LOWER cutoff filter for bass damping (darker, heavier tone)
     // Research: Bass should be darker and less "plucky" than synth bass
     let mut bass_filter = LowPassFilter::new(600.0, 0.8); // Lower cutoff, higher resonance
     
     let mut buffer = Vec::with_capacity(num_samples);
-    let mut rng = rand::thread_rng();
+    let mut rng = crate::utils::rng();
     
     for i in 0..num_samples {
         let t = i as f32 / num_samples as f32;
@@ -270,6 +303,23 @@ mod tests {
         assert!(muted.decay_factor < open.decay_factor);
     }
 
+    #[test]
+    fn test_low_strings_ring_longer_than_high_strings() {
+        let low_b = KarplusStrong::new(61.7, PlayingTechnique::Open); // B1 (7-string)
+        let high_e = KarplusStrong::new(329.6, PlayingTechnique::Open); // E4
+        assert!(low_b.damping < high_e.damping);
+        assert!(low_b.decay_factor > high_e.decay_factor);
+
+        // A high palm mute dies faster than a low one
+        let low_mute = KarplusStrong::new(61.7, PlayingTechnique::PalmMute);
+        let high_mute = KarplusStrong::new(329.6, PlayingTechnique::PalmMute);
+        assert!(high_mute.decay_factor < low_mute.decay_factor);
+
+        // Explicit damping overrides the pitch estimate
+        let forced = KarplusStrong::with_damping(329.6, PlayingTechnique::Open, 0.0);
+        assert_eq!(forced.damping, 0.0);
+    }
+
     #[test]
     fn test_generate_note() {
         let buffer = KarplusStrong::generate_note(
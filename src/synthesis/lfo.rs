@@ -0,0 +1,98 @@
+//! Low-frequency oscillator shared by modulation effects (chorus, auto-pan, wobble).
+//! Rates can be given in Hz or as a note division of the song tempo, so the
+//! modulation stays locked to the beat.
+
+use std::f32::consts::TAU;
+
+/// LFO waveform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Square,
+}
+
+/// Phase-accumulating LFO; every shape runs -1.0..1.0 and starts at its zero crossing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lfo {
+    pub shape: LfoShape,
+    pub rate: f32,  // Cycles per second
+    pub phase: f32, // Position in the cycle (0.0-1.0)
+}
+
+impl Lfo {
+    pub fn new(shape: LfoShape, rate: f32) -> Self {
+        Lfo {
+            shape,
+            rate: rate.max(0.0),
+            phase: 0.0,
+        }
+    }
+
+    /// Start the cycle at `phase` (0.0-1.0) instead of the zero crossing
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase.rem_euclid(1.0);
+        self
+    }
+
+    /// One cycle per note of `division` at `tempo` BPM (4 = quarter, 8 = eighth, 16 = sixteenth,
+    /// 2 = half note; 12 = eighth-note triplet)
+    pub fn from_note_division(shape: LfoShape, tempo: u16, division: f32) -> Self {
+        let beats_per_second = tempo as f32 / 60.0;
+        Self::new(shape, beats_per_second * division.max(0.0) / 4.0)
+    }
+
+    /// Current value without advancing
+    pub fn value(&self) -> f32 {
+        match self.shape {
+            LfoShape::Sine => (TAU * self.phase).sin(),
+            // Rises 0 -> 1 over the first quarter, falls to -1 by three quarters, back to 0
+            LfoShape::Triangle => 1.0 - 4.0 * ((self.phase + 0.25).fract() - 0.5).abs(),
+            LfoShape::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        }
+    }
+
+    /// Current value, then advance by one sample
+    pub fn next(&mut self, sample_rate: u32) -> f32 {
+        let value = self.value();
+        self.phase = (self.phase + self.rate / sample_rate.max(1) as f32).fract();
+        value
+    }
+
+    /// Current value mapped to 0.0-1.0 (for gains and filter sweeps), then advance
+    pub fn next_unipolar(&mut self, sample_rate: u32) -> f32 {
+        0.5 + 0.5 * self.next(sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfo_locks_to_tempo() {
+        // 120 BPM eighths: 4 cycles a second
+        let lfo = Lfo::from_note_division(LfoShape::Sine, 120, 8.0);
+        assert!((lfo.rate - 4.0).abs() < 1e-6);
+
+        let sample_rate = 1000;
+        for shape in [LfoShape::Sine, LfoShape::Triangle, LfoShape::Square] {
+            let mut lfo = Lfo::from_note_division(shape, 120, 8.0);
+            let cycle: Vec<f32> = (0..250).map(|_| lfo.next(sample_rate)).collect();
+            assert!(cycle.iter().all(|v| (-1.0..=1.0).contains(v)));
+            // Peaks a quarter cycle in, troughs at three quarters
+            assert!((cycle[62] - 1.0).abs() < 0.05 || shape == LfoShape::Square, "{:?}", shape);
+            assert!(cycle[187] < -0.95, "{:?}", shape);
+            // Back where it started after a full cycle
+            assert!(lfo.phase.abs() < 1e-3 || (lfo.phase - 1.0).abs() < 1e-3);
+        }
+
+        assert_eq!(Lfo::new(LfoShape::Square, 1.0).next_unipolar(1000), 1.0);
+    }
+}
@@ -1,22 +1,258 @@
 use crate::composition::{
-    metal_song_generator::{MetalSong, MetalRiff, MetalSection, MetalSubgenre, ChordType, SectionIntensity, RhythmPattern, RhythmicFeel},
-    rhythm_generator,
-    bass_generator::BassMode,
+    metal_song_generator::{MetalSong, MetalRiff, MetalSection, MetalSubgenre, ChordType, Inversion, SectionIntensity, RhythmPattern, RhythmicFeel},
+    bass_generator::{BassMode, BassPitchRelation},
+    drum_humanizer::{DrumHumanizer, DEFAULT_BLAST_VARIATION},
+    drum_patterns::{generate_drum_grid, CymbalStyle, DrumGrid, DrumPatternOptions, TimeKeeper},
+    guitar_humanizer::GuitarHumanizer,
+    drum_articulations::{Articulation, DrumArticulationGenerator},
+    music_theory::{MidiNote, PitchStandard},
+    tuning::GuitarTuning,
+    phrase_drums::GuitarContext,
 };
 use crate::synthesis::{
     karplus_strong::{generate_metal_guitar_note, generate_metal_bass_string, PlayingTechnique},
-    metal_dsp::{MetalDSPChain, TubeDistortion},
+    reese_bass::generate_reese_bass_with_wobble,
+    metal_dsp::{sidechain_compress_at, BassAmp, MetalDSPChain, NoiseGate, SidechainSettings},
     cabinet::CabinetSimulator,
-    drums::MetalDrums,
-    fx::generate_drop_kick,
+    mixing::{MasterEQ, MasterSaturation, MixProfile, Reverb, ReverbSend, DEFAULT_SATURATION_DRIVE},
+    modulation::Chorus,
+    drums::{normalize_velocity, MetalDrumKit, MetalDrums},
+    fx::{
+        generate_click, generate_count_in, generate_drop_kick, generate_riser, generate_sub_drop,
+        DROP_KICK_DURATION,
+    },
 };
-use crate::utils::get_sample_rate;
+use crate::audio::check_clipping;
+use crate::config::{CountIn, MasterEqConfig, MixConfig};
+use crate::utils::{get_sample_rate, RenderContext};
+use rand::Rng;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Humanizer timing offsets are in MIDI ticks at this resolution (pulses per quarter note)
+const TICKS_PER_BEAT: f32 = 480.0;
+
+/// Guitar distortion drive for Medium-intensity sections; other sections scale from it
+const GUITAR_BASE_DRIVE: f32 = 6.0;
+
+/// Gap between the grace stroke and the main stroke of a flam (seconds)
+const FLAM_GAP: f32 = 0.005;
+
+/// Gap between the grace strokes of a drag (seconds)
+const DRAG_GAP: f32 = 0.012;
+
+/// Picking strength for riffs that carry no per-note velocities
+const DEFAULT_GUITAR_VELOCITY: f32 = 0.8;
+
+/// Detune range of the second rhythm guitar take (cents, either direction)
+const DOUBLE_TRACK_MIN_CENTS: f32 = 2.0;
+const DOUBLE_TRACK_MAX_CENTS: f32 = 4.0;
+
+/// Latest the second take may land behind the first (samples)
+const DOUBLE_TRACK_MAX_OFFSET: usize = 12;
+
+/// Overlap between consecutive sections, so a ringing tail doesn't click into the next downbeat
+const SECTION_CROSSFADE: f32 = 0.005;
+
+/// Silence before the breakdown drop kick (seconds)
+const BREAKDOWN_DROP_SILENCE: f32 = 0.5;
+
+/// Lowest bass pitch (E1, ~41 Hz): notes below it are folded up an octave
+pub const BASS_FLOOR: MidiNote = 28;
+
+/// Lowest breakdown sub-drop pitch (C1); lower than this is felt less than it's wasted headroom
+const SUB_DROP_FLOOR: MidiNote = 24;
+
+/// Length of a breakdown sub-drop, in beats
+const SUB_DROP_BEATS: f32 = 2.0;
+
+/// Sub-drop level relative to the bass track
+const SUB_DROP_GAIN: f32 = 0.7;
+
+/// How bass notes are synthesized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BassVoice {
+    /// Plucked, overdriven bass guitar
+    Picked,
+    /// Detuned-saw Reese with its filter wobbling at `wobble_hz`
+    Reese { wobble_hz: f32 },
+}
+
+/// Progress hook for long renders: (fraction complete 0.0-1.0, status label)
+pub type ProgressCallback<'a> = &'a dyn Fn(f32, &str);
+
+/// How `render_song` lays a song out and what it hands back besides the mix
+#[derive(Clone, Copy, Default)]
+pub struct SongRenderOptions<'a> {
+    /// Seconds per section (`None` = `MetalSection::default_duration` at the song's tempo)
+    pub section_seconds: Option<f32>,
+    /// Receives fraction complete (0.0-1.0) and a label such as "Rendering Chorus 3/12"
+    /// before each section and once more when done
+    pub progress: Option<ProgressCallback<'a>>,
+    /// Mix a metronome into each section on its own grid, for checking timing by ear
+    pub click: bool,
+    /// Also return dry guitar/bass/drum stems lined up with the mix
+    pub stems: bool,
+}
+
+/// A finished song: the mastered mix, its measurements and the stems if they were asked for
+#[derive(Debug, Clone)]
+pub struct RenderedSong {
+    pub audio: Vec<f32>,
+    pub stats: RenderStats,
+    pub stems: Option<Stems>,
+}
+
+/// Dry per-track audio for mixing in a DAW: each track at its section mix level,
+/// without the reverb sends. Sections line up sample for sample with the mix
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stems {
+    pub guitar: Vec<f32>,
+    pub bass: Vec<f32>,
+    pub drums: Vec<f32>,
+}
+
+impl Stems {
+    /// Append a section's stems with the same crossfade the mix uses between sections
+    pub fn append_crossfaded(&mut self, section: &Stems, sample_rate: u32) {
+        MetalAudioRenderer::append_crossfaded(&mut self.guitar, &section.guitar, sample_rate);
+        MetalAudioRenderer::append_crossfaded(&mut self.bass, &section.bass, sample_rate);
+        MetalAudioRenderer::append_crossfaded(&mut self.drums, &section.drums, sample_rate);
+    }
+
+    /// Each stem with the file suffix it's saved under ("guitar", "bass", "drums")
+    pub fn tracks(&self) -> [(&'static str, &[f32]); 3] {
+        [("guitar", &self.guitar), ("bass", &self.bass), ("drums", &self.drums)]
+    }
+
+    /// Shift every stem later by `samples` of silence, to line up with a song's lead-in
+    pub fn prepend_silence(&mut self, samples: usize) {
+        for stem in [&mut self.guitar, &mut self.bass, &mut self.drums] {
+            stem.splice(0..0, std::iter::repeat_n(0.0, samples));
+        }
+    }
+}
+
+/// Measurements of a rendered song
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenderStats {
+    pub duration_seconds: f32,
+    pub peak: f32,                   // Highest absolute sample value
+    pub rms: f32,                    // Whole-song RMS level
+    pub clip_count: usize,           // Samples over full scale (including inter-sample peaks)
+    pub section_durations: Vec<f32>, // Rendered length of each section in seconds
+    pub lead_in_seconds: f32,        // Silence and count-in before the first section
+}
+
+impl RenderStats {
+    /// Measure a rendered buffer; `section_lengths` are the per-section sample counts
+    pub fn measure(samples: &[f32], section_lengths: &[usize], sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1) as f32;
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let rms = if samples.is_empty() {
+            0.0
+        } else {
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+
+        RenderStats {
+            duration_seconds: samples.len() as f32 / sample_rate,
+            peak,
+            rms,
+            clip_count: check_clipping(samples).clipped_samples,
+            section_durations: section_lengths.iter().map(|&len| len as f32 / sample_rate).collect(),
+            lead_in_seconds: 0.0,
+        }
+    }
+
+    /// Record that the first section starts `samples` into the buffer
+    pub fn with_lead_in(mut self, samples: usize, sample_rate: u32) -> Self {
+        self.lead_in_seconds = samples as f32 / sample_rate.max(1) as f32;
+        self
+    }
+
+    /// Peak level in dBFS
+    pub fn peak_dbfs(&self) -> f32 {
+        20.0 * self.peak.max(1e-10).log10()
+    }
+
+    /// RMS level in dBFS
+    pub fn rms_dbfs(&self) -> f32 {
+        20.0 * self.rms.max(1e-10).log10()
+    }
+
+    /// Section boundaries for DAW markers; `sections` are the song's sections in render order
+    pub fn timeline(&self, sections: &[MetalSection], tempo: u16) -> Vec<SectionMarker> {
+        let bar_seconds = 4.0 * 60.0 / tempo.max(1) as f32;
+        let mut start = self.lead_in_seconds;
+        sections
+            .iter()
+            .zip(&self.section_durations)
+            .map(|(&section, &duration)| {
+                let marker = SectionMarker {
+                    section,
+                    start_s: start,
+                    end_s: start + duration,
+                    start_bar: 1.0 + start / bar_seconds,
+                    bars: duration / bar_seconds,
+                };
+                start += duration;
+                marker
+            })
+            .collect()
+    }
+}
+
+/// Where a section sits in the rendered song (bars are 4/4 at the song tempo, counted from 1)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SectionMarker {
+    pub section: MetalSection,
+    pub start_s: f32,
+    pub end_s: f32,
+    pub start_bar: f32,
+    pub bars: f32,
+}
 
 pub struct MetalAudioRenderer {
     drums: MetalDrums,
+    drum_kit: Option<MetalDrumKit>,
     dsp_chain: MetalDSPChain,
-    bass_dsp: TubeDistortion,
+    bass_amp: Option<BassAmp>,
+    section_bass_amp: BassAmp, // Bass amp of the section being rendered
+    sub_drop: Option<bool>,
     cabinet: CabinetSimulator,
+    room: Option<Reverb>,
+    room_subgenre: Option<MetalSubgenre>, // Subgenre the room bus was last built for
+    room_reverb: Reverb,
+    room_level: f32, // Room return relative to the stock metal room
+    lead_reverb: Reverb,
+    reverb_send: ReverbSend,
+    sidechain: Option<SidechainSettings>,
+    cymbal_style: Option<CymbalStyle>,
+    blast_variation: Option<f32>,
+    master_eq: Option<MasterEqConfig>,
+    saturation_drive: Option<f32>,
+    lead_in_silence: f32,
+    count_in: CountIn,
+    double_bass: bool,
+    swing: Option<f32>,
+    section_swing: f32, // Shuffle of the section being rendered
+    guitar_humanizer: Option<GuitarHumanizer>,
+    section_guitar_humanizer: GuitarHumanizer, // Picking feel of the section being rendered
+    tuning: GuitarTuning,
+    bass_octave_offset: Option<i8>,
+    bass_relation: Option<BassPitchRelation>,
+    bass_subgenre: MetalSubgenre, // Subgenre of the section being rendered (picks the bass relation)
+    intro_chorus: Option<Chorus>,
+    mix: Box<dyn MixProfile>,
+    double_track: bool,
+    take_detune: f32, // Pitch ratio of the guitar take being rendered (1.0 = in tune)
+    guitar_cache: HashMap<u64, Vec<f32>>, // Finished guitar tracks of sections already rendered
+    capture_stems: bool,
+    section_stems: Option<Stems>, // Stems of the last rendered section, when capturing
+    pitch: PitchStandard,
     sample_rate: u32,
 }
 
@@ -24,27 +260,398 @@ impl MetalAudioRenderer {
     pub fn new() -> Self {
         Self {
             drums: MetalDrums::new(),
+            drum_kit: None,
             // REDUCED DRIVE to prevent noise wall (was higher default)
-            dsp_chain: MetalDSPChain::new(6.0), 
-            bass_dsp: TubeDistortion::new(5.0, 1.0),
+            dsp_chain: MetalDSPChain::new(GUITAR_BASE_DRIVE), 
+            bass_amp: None,
+            section_bass_amp: BassAmp::metal(),
+            sub_drop: None,
             cabinet: CabinetSimulator::metal_4x12(),
+            room: None,
+            room_subgenre: None,
+            room_reverb: Reverb::send_bus(0.5, 0.25), // Short, tight room
+            room_level: 1.0,
+            lead_reverb: Reverb::send_bus(1.2, 0.6),  // Longer tail for solos
+            reverb_send: ReverbSend::default(),
+            sidechain: None,
+            cymbal_style: None,
+            blast_variation: None,
+            master_eq: None,
+            saturation_drive: None,
+            lead_in_silence: 0.0,
+            count_in: CountIn::None,
+            double_bass: true,
+            swing: None,
+            section_swing: 0.0,
+            guitar_humanizer: None,
+            section_guitar_humanizer: GuitarHumanizer::quantized(),
+            tuning: GuitarTuning::EStandard,
+            bass_octave_offset: None,
+            bass_relation: None,
+            bass_subgenre: MetalSubgenre::HeavyMetal,
+            intro_chorus: None,
+            mix: Box::new(MixConfig::default()),
+            double_track: true,
+            take_detune: 1.0,
+            guitar_cache: HashMap::new(),
+            capture_stems: false,
+            section_stems: None,
+            pitch: PitchStandard::default(),
             sample_rate: get_sample_rate(),
         }
     }
 
-    pub fn render_song(&mut self, song: &MetalSong, duration_per_section: f32) -> Vec<f32> {
-        let mut full_audio = Vec::new();
+    /// Renderer for an explicit sample rate instead of the global one. Its renders run inside
+    /// `context.scope`, so concurrent renders on other threads can use other rates; effects
+    /// handed to setters should be built inside the same scope.
+    pub fn with_context(context: RenderContext) -> Self {
+        context.scope(Self::new)
+    }
+
+    /// Sample rate (and any other render settings) this renderer was built for
+    pub fn context(&self) -> RenderContext {
+        RenderContext::new(self.sample_rate)
+    }
+
+    /// Set the per-track reverb send levels used when mixing sections
+    pub fn set_reverb_send(&mut self, send: ReverbSend) {
+        self.reverb_send = send;
+    }
+
+    /// Override the room the rhythm section plays in (`None` = per-subgenre default,
+    /// see `Reverb::for_subgenre`)
+    pub fn set_room_reverb(&mut self, room: Option<Reverb>) {
+        if let Some(reverb) = &room {
+            self.use_room(reverb);
+        }
+        self.room = room;
+        self.room_subgenre = None;
+    }
+
+    /// Send the rhythm section into `room`; its wet/dry mix sets the return level
+    fn use_room(&mut self, room: &Reverb) {
+        self.room_reverb = room.to_send_bus();
+        self.room_level = room.mix() / Reverb::metal().mix();
+    }
+
+    /// Set the per-intensity guitar/bass/drum gains (e.g. from `[metal.mix]`)
+    pub fn set_mix_levels(&mut self, mix: MixConfig) {
+        self.mix = Box::new(mix);
+    }
+
+    /// Replace the per-intensity track balance with a custom profile
+    pub fn set_mix_profile(&mut self, profile: Box<dyn MixProfile>) {
+        self.mix = profile;
+    }
+
+    /// Replace the guitar noise gate (default `NoiseGate::metal()`); quiet or very loud
+    /// material may want a threshold from `NoiseGate::auto_calibrate`
+    pub fn set_noise_gate(&mut self, gate: NoiseGate) {
+        self.dsp_chain.set_noise_gate(gate);
+        self.guitar_cache.clear();
+    }
+
+    /// Record rhythm guitars twice (slightly detuned and late) instead of once
+    pub fn set_double_track(&mut self, double_track: bool) {
+        self.double_track = double_track;
+    }
+
+    /// Override how far the bass ducks under the kick (`None` = per-subgenre default)
+    pub fn set_sidechain(&mut self, sidechain: Option<SidechainSettings>) {
+        self.sidechain = sidechain;
+    }
+
+    /// Override the cymbal density and ride/hi-hat choice for every section
+    /// (`None` = per-section default)
+    pub fn set_cymbal_style(&mut self, style: Option<CymbalStyle>) {
+        self.cymbal_style = style;
+    }
+
+    /// How often blast beats switch style, shift their accent or fill
+    /// (`None` = `DEFAULT_BLAST_VARIATION`; 0.0 = the same bar throughout)
+    pub fn set_blast_variation(&mut self, variation: Option<f32>) {
+        self.blast_variation = variation.map(|variation| variation.clamp(0.0, 1.0));
+    }
+
+    /// Override the mastering EQ on the finished song (`None` = per-subgenre curve,
+    /// see `MasterEqConfig::for_subgenre`)
+    pub fn set_master_eq(&mut self, eq: Option<MasterEqConfig>) {
+        self.master_eq = eq;
+    }
+
+    /// Drive of the master-bus saturation (`None` = `DEFAULT_SATURATION_DRIVE`,
+    /// 0.0 = off; otherwise 1.0-4.0)
+    pub fn set_saturation_drive(&mut self, drive: Option<f32>) {
+        self.saturation_drive = drive;
+    }
+
+    /// Final stages on the whole song, after the limiter: the master EQ shapes the
+    /// tone, then the saturation glues the mix and catches any peaks the EQ raised
+    pub fn apply_master_bus(&self, audio: &mut [f32], subgenre: MetalSubgenre) {
+        let eq = self.master_eq.unwrap_or_else(|| MasterEqConfig::for_subgenre(subgenre));
+        self.context().scope(|| MasterEQ::new(&eq).process_buffer(audio));
+
+        let drive = self.saturation_drive.unwrap_or(DEFAULT_SATURATION_DRIVE);
+        if drive > 0.0 {
+            MasterSaturation::new(drive).process_buffer(audio);
+        }
+    }
+
+    /// Breathing room before the first section: `silence` seconds of nothing, then
+    /// one bar of `count_in` (both off by default, so songs start on the intro riff)
+    pub fn set_lead_in(&mut self, silence: f32, count_in: CountIn) {
+        self.lead_in_silence = silence.max(0.0);
+        self.count_in = count_in;
+    }
+
+    /// The song's leading silence and count-in, ending on the first section's downbeat
+    pub fn render_lead_in(&self, tempo: u16) -> Vec<f32> {
+        let mut lead_in = vec![0.0; (self.lead_in_silence * self.sample_rate as f32) as usize];
+        lead_in.extend(self.context().scope(|| generate_count_in(self.count_in, tempo)));
+        lead_in
+    }
+
+    /// Put the lead-in in front of a rendered song; returns how many samples it added
+    pub fn prepend_lead_in(&self, audio: &mut Vec<f32>, tempo: u16) -> usize {
+        let lead_in = self.render_lead_in(tempo);
+        let length = lead_in.len();
+        audio.splice(0..0, lead_in);
+        length
+    }
+
+    /// Override the drum kit for every subgenre (`None` = per-subgenre kit)
+    pub fn set_drum_kit(&mut self, kit: Option<MetalDrumKit>) {
+        self.drum_kit = kit;
+    }
+
+    /// Let death/thrash verses run continuous 16th-note double bass
+    /// instead of the Euclidean kick pattern
+    pub fn set_double_bass(&mut self, double_bass: bool) {
+        self.double_bass = double_bass;
+    }
+
+    /// Tuning of the song being rendered (decides whether the bass doubles an octave down)
+    pub fn set_tuning(&mut self, tuning: GuitarTuning) {
+        self.tuning = tuning;
+    }
+
+    /// Override the bass amp for every subgenre (`None` = per-subgenre amp:
+    /// subtle for doom, grinding for death/thrash/prog)
+    pub fn set_bass_amp(&mut self, amp: Option<BassAmp>) {
+        self.bass_amp = amp;
+    }
+
+    /// Drop a sub-bass sine under the first kick of every breakdown bar
+    /// (`None` = per-subgenre: only death metal breakdowns)
+    pub fn set_sub_drop(&mut self, sub_drop: Option<bool>) {
+        self.sub_drop = sub_drop;
+    }
+
+    /// Whether a subgenre's breakdowns get the sub-drop by default
+    pub fn sub_drop_for_subgenre(subgenre: MetalSubgenre) -> bool {
+        subgenre == MetalSubgenre::DeathMetal
+    }
+
+    /// Override the bass offset from the guitar in semitones; takes precedence over
+    /// the pitch relation (`None` = use `set_bass_relation`)
+    pub fn set_bass_octave_offset(&mut self, offset: Option<i8>) {
+        self.bass_octave_offset = offset;
+    }
+
+    /// Override the bass pitch against the guitar (`None` = per-subgenre default for the
+    /// tuning, see `BassPitchRelation::for_subgenre`)
+    pub fn set_bass_relation(&mut self, relation: Option<BassPitchRelation>) {
+        self.bass_relation = relation;
+    }
+
+    fn bass_relation(&self) -> BassPitchRelation {
+        self.bass_relation
+            .unwrap_or_else(|| BassPitchRelation::for_subgenre(self.bass_subgenre, &self.tuning))
+    }
+
+    fn bass_offset(&self) -> i8 {
+        self.bass_octave_offset.unwrap_or_else(|| self.bass_relation().offset())
+    }
+
+    /// Bass pitch for a guitar note: shifted by `offset`, then folded up by octaves
+    /// until it sits at or above `BASS_FLOOR`
+    pub fn bass_note(guitar_note: MidiNote, offset: i8) -> MidiNote {
+        let mut note = guitar_note as i16 + offset as i16;
+        while note < BASS_FLOOR as i16 {
+            note += 12;
+        }
+        note.min(127) as MidiNote
+    }
+
+    /// Reference pitch and temperament for guitar and bass notes (default A4 = 440 Hz, equal)
+    pub fn set_pitch_standard(&mut self, pitch: PitchStandard) {
+        if pitch != self.pitch {
+            self.guitar_cache.clear();
+        }
+        self.pitch = pitch;
+    }
+
+    /// Keep each rendered section's dry guitar, bass and drum tracks for `take_section_stems`
+    pub fn set_capture_stems(&mut self, capture: bool) {
+        self.capture_stems = capture;
+        self.section_stems = None;
+    }
+
+    /// Stems of the section rendered last (None unless `set_capture_stems(true)`)
+    pub fn take_section_stems(&mut self) -> Option<Stems> {
+        self.section_stems.take()
+    }
+
+    /// Play intros on the clean channel through a chorus/flanger (`None` = distorted intros)
+    pub fn set_clean_intro(&mut self, chorus: Option<Chorus>) {
+        self.intro_chorus = chorus;
+        self.guitar_cache.clear();
+    }
+
+    /// Override the 16th-note shuffle for every subgenre
+    /// (0.0 = straight, 1.0 = triplet; `None` = per-subgenre default)
+    pub fn set_swing(&mut self, swing: Option<f32>) {
+        self.swing = swing.map(|amount| amount.clamp(0.0, 1.0));
+    }
+
+    /// Override the rhythm guitar's onset jitter and note-length feel for every subgenre
+    /// (`GuitarHumanizer::quantized()` = on the grid; `None` = per-subgenre default)
+    pub fn set_guitar_humanizer(&mut self, humanizer: Option<GuitarHumanizer>) {
+        self.guitar_humanizer = humanizer;
+    }
+
+    /// Drop the cached guitar tracks; call between songs so a reused renderer doesn't
+    /// hold on to every section it has ever rendered
+    pub fn clear_guitar_cache(&mut self) {
+        self.guitar_cache.clear();
+    }
+
+    /// Clear amp, cabinet, reverb and chorus state so a reused renderer starts each song
+    /// as if freshly built (no gate envelope or reverb tail carried over from the last one)
+    pub fn reset_effects(&mut self) {
+        self.dsp_chain.reset();
+        self.cabinet.reset();
+        self.room_reverb.reset();
+        self.lead_reverb.reset();
+        if let Some(chorus) = self.intro_chorus.as_mut() {
+            chorus.reset();
+        }
+    }
+
+    /// Move a straight grid position (in beats) to where it lands with `swing`.
+    /// Offbeat 16ths are pushed toward the last triplet of their eighth;
+    /// eighth-note positions never move.
+    pub fn swing_position(beats: f32, swing: f32) -> f32 {
+        let eighth_start = (beats / 0.5).floor() * 0.5;
+        let within = beats - eighth_start;
+        let delay = swing.clamp(0.0, 1.0) * 0.25 / 3.0;
+        let warped = if within <= 0.25 {
+            within * (0.25 + delay) / 0.25
+        } else {
+            (0.25 + delay) + (within - 0.25) * (0.25 - delay) / 0.25
+        };
+        eighth_start + warped
+    }
+
+    /// Length of a note starting at `start` beats once the grid is swung
+    fn swung_length(&self, start: f32, beats: f32) -> f32 {
+        Self::swing_position(start + beats, self.section_swing) - Self::swing_position(start, self.section_swing)
+    }
+
+    /// Grid settings the renderer plays with (`set_blast_variation`, `set_double_bass`,
+    /// `set_cymbal_style`)
+    fn drum_options(&self) -> DrumPatternOptions {
+        DrumPatternOptions {
+            blast_variation: self.blast_variation.unwrap_or(DEFAULT_BLAST_VARIATION),
+            double_bass: self.double_bass,
+            cymbal_style: self.cymbal_style,
+        }
+    }
+
+    /// Reverb send multiplier per section: tight verses and breakdowns,
+    /// intros, outros and solos left to breathe
+    pub fn section_ambience(section: MetalSection) -> f32 {
+        match section {
+            MetalSection::Breakdown => 0.3,
+            MetalSection::Verse => 0.5,
+            MetalSection::PreChorus => 0.7,
+            MetalSection::Chorus => 1.0,
+            MetalSection::Solo => 1.5,
+            MetalSection::Intro => 2.0,
+            MetalSection::Outro => 2.5,
+        }
+    }
+
+    /// Default bass-to-kick ducking: subtle for doom's long notes, pumping for prog's syncopation
+    pub fn sidechain_for_subgenre(subgenre: MetalSubgenre) -> SidechainSettings {
+        match subgenre {
+            MetalSubgenre::DoomMetal => SidechainSettings::subtle(),
+            MetalSubgenre::ProgressiveMetal => SidechainSettings::pumping(),
+            _ => SidechainSettings::tight(),
+        }
+    }
+
+    /// Render every section of the song into one mastered buffer, starting from clean
+    /// effect state: sections are crossfaded, the lead-in goes in front, then the limiter
+    /// and master bus run over the whole song
+    pub fn render_song(&mut self, song: &MetalSong, options: &SongRenderOptions) -> RenderedSong {
+        self.context().scope(|| self.render_song_in_context(song, options))
+    }
+
+    fn render_song_in_context(&mut self, song: &MetalSong, options: &SongRenderOptions) -> RenderedSong {
+        self.set_tuning(song.tuning.clone());
+        self.clear_guitar_cache();
+        self.reset_effects();
+        let section_seconds =
+            |section: MetalSection| options.section_seconds.unwrap_or_else(|| section.default_duration(song.tempo));
+        let expected_samples: usize = song
+            .sections
+            .iter()
+            .map(|(section_type, _)| self.section_length(*section_type, section_seconds(*section_type)))
+            .sum();
+        let mut full_audio = Vec::with_capacity(expected_samples);
+        let mut section_lengths = Vec::with_capacity(song.sections.len());
+        let total_sections = song.sections.len();
+        let capture_stems = std::mem::replace(&mut self.capture_stems, options.stems);
+        let mut stems = options.stems.then(Stems::default);
+        
+        for (i, (section_type, riff)) in song.sections.iter().enumerate() {
+            if let Some(report) = options.progress {
+                let label = format!("Rendering {:?} {}/{}", section_type, i + 1, total_sections);
+                report(i as f32 / total_sections as f32, &label);
+            }
+            let duration = section_seconds(*section_type);
+            let mut section_audio = self.render_section_in_context(*section_type, riff, duration, song.tempo, song.subgenre, &song.drum_humanizer);
+            if options.click {
+                let click = self.render_click_section(*section_type, song.tempo, section_audio.len());
+                for (sample, tick) in section_audio.iter_mut().zip(click) {
+                    *sample += tick;
+                }
+            }
+            section_lengths.push(Self::append_crossfaded(&mut full_audio, &section_audio, self.sample_rate));
+            if let (Some(stems), Some(section_stems)) = (stems.as_mut(), self.section_stems.take()) {
+                stems.append_crossfaded(&section_stems, self.sample_rate);
+            }
+        }
+        self.capture_stems = capture_stems;
         
-        for (section_type, riff) in &song.sections {
-            let section_audio = self.render_section(*section_type, riff, duration_per_section, song.tempo, song.subgenre);
-            full_audio.extend(section_audio);
+        if let Some(report) = options.progress {
+            report(1.0, "Rendering complete");
+        }
+        // Added after the sections so the intro doesn't crossfade into the count-in
+        let lead_in = self.prepend_lead_in(&mut full_audio, song.tempo);
+        if let Some(stems) = stems.as_mut() {
+            stems.prepend_silence(lead_in);
         }
         
         // Final Limiter instead of Normalize
         // Normalize just finds peak, Limiter compresses peaks
         Self::apply_limiter(&mut full_audio, 0.95);
+        self.apply_master_bus(&mut full_audio, song.subgenre);
         
-        full_audio
+        let stats = RenderStats::measure(&full_audio, &section_lengths, self.sample_rate)
+            .with_lead_in(lead_in, self.sample_rate);
+        RenderedSong { audio: full_audio, stats, stems }
     }
 
     pub fn render_section(
@@ -54,18 +661,41 @@ impl MetalAudioRenderer {
         duration: f32,
         tempo: u16,
         subgenre: MetalSubgenre,
+        humanizer: &DrumHumanizer,
+    ) -> Vec<f32> {
+        self.context()
+            .scope(|| self.render_section_in_context(section_type, riff, duration, tempo, subgenre, humanizer))
+    }
+
+    fn render_section_in_context(
+        &mut self,
+        section_type: MetalSection,
+        riff: &MetalRiff,
+        duration: f32,
+        tempo: u16,
+        subgenre: MetalSubgenre,
+        humanizer: &DrumHumanizer,
     ) -> Vec<f32> {
         let beat_duration = 60.0 / tempo as f32;
         let intensity = section_type.intensity();
+        self.section_swing = self.swing.unwrap_or_else(|| subgenre.default_swing());
+        self.section_guitar_humanizer = self.guitar_humanizer.unwrap_or_else(|| GuitarHumanizer::for_subgenre(subgenre));
+        self.drums.set_kit(self.drum_kit.unwrap_or_else(|| MetalDrumKit::for_subgenre(subgenre)));
+        self.section_bass_amp = self.bass_amp.clone().unwrap_or_else(|| BassAmp::for_subgenre(subgenre));
+        self.bass_subgenre = subgenre;
+        // Only rebuild the room when the subgenre changes, so tails carry across sections
+        if self.room.is_none() && self.room_subgenre != Some(subgenre) {
+            self.use_room(&Reverb::for_subgenre(subgenre));
+            self.room_subgenre = Some(subgenre);
+        }
         // CRITICAL: Get the rhythmic feel (HalfTime/Normal/Blast) from the section
-        let rhythmic_feel = section_type.rhythmic_feel();
+        let rhythmic_feel = section_type.rhythmic_feel_for(subgenre);
 
         let mut section_audio = Vec::new();
 
         // 1. THE DROP: Add an aggressive kick drop for breakdowns
         if matches!(section_type, MetalSection::Breakdown) {
-            let silence_duration = 0.5; // Shorter silence before the drop
-            let silence_samples = (silence_duration * self.sample_rate as f32) as usize;
+            let silence_samples = (BREAKDOWN_DROP_SILENCE * self.sample_rate as f32) as usize;
             
             // Generate Heavy Drop Kick
             let drop_kick = generate_drop_kick();
@@ -79,12 +709,36 @@ impl MetalAudioRenderer {
         }
 
         // 2. Render Guitar (Keeps Song Tempo - Guitars still chug on grid)
-        let guitar_audio = self.render_guitar_riff(riff, beat_duration);
+        // Gain follows intensity: cleaner intros, saturated breakdowns
+        let drive_scale = match intensity {
+            SectionIntensity::Low => 0.7,
+            SectionIntensity::Medium => 1.0,
+            SectionIntensity::High => 1.2,
+            SectionIntensity::Extreme => 1.5,
+        };
+        self.dsp_chain.set_drive(GUITAR_BASE_DRIVE * drive_scale);
+        // Clean channel: riffs the generator marked clean, or every intro once a chorus is set
+        let clean_intro = section_type == MetalSection::Intro && self.intro_chorus.is_some();
+        self.dsp_chain.set_distortion_bypass(riff.clean || clean_intro);
+        // Repeated verses/choruses reuse the guitar track instead of re-running the DSP chain
+        let cache_key = self.guitar_cache_key(section_type, riff, beat_duration);
+        let guitar_audio = match self.guitar_cache.get(&cache_key) {
+            Some(cached) => cached.clone(),
+            None => {
+                let rendered = self.render_section_guitars(section_type, riff, beat_duration, clean_intro);
+                self.guitar_cache.insert(cache_key, rendered.clone());
+                rendered
+            }
+        };
         
         // 3. Render Drums (Decoupled Tempo based on RhythmicFeel)
-        let (kick_pattern, _, _) = self.generate_drum_patterns(section_type, duration, tempo, subgenre, rhythmic_feel);
+        // Kicks lock to the riff's chugs; the grid is drawn once (blasts vary at random)
+        // so the drums, the bass lock, the sidechain and the sub-drops all see the same kicks
+        let guitar_context = GuitarContext::from_riff(riff);
+        let drum_grid = self.generate_drum_patterns(section_type, duration, tempo, subgenre, rhythmic_feel, &guitar_context);
+        let kick_pattern = &drum_grid.kick;
         
-        let drum_audio = self.render_drums(section_type, duration, tempo, subgenre, rhythmic_feel);
+        let drum_audio = self.render_drums(section_type, &drum_grid, duration, tempo, subgenre, humanizer);
 
         // 4. Render Bass (Locks to Kick OR Guitar depending on density)
         // If it's a breakdown, bass matches the sparse kick (Lock mode)
@@ -101,37 +755,90 @@ impl MetalAudioRenderer {
             beat_duration / 4.0 // 16th note bass
         };
 
-        let bass_audio = self.render_bass_riff_locked(
+        let mut bass_audio = self.render_bass_riff_locked(
             &riff.notes,
-            &kick_pattern,
+            kick_pattern,
             bass_note_duration,
             bass_mode,
             &riff,
+            Self::bass_voice(subgenre, section_type, tempo),
         );
+        // Bass amp: grit under the guitars, then the low-pass and compressor even it out
+        self.section_bass_amp.process_buffer(&mut bass_audio);
+
+        // Duck the bass under each kick (where the swung drums put it) so the low end doesn't smear
+        let sidechain = self.sidechain.unwrap_or_else(|| Self::sidechain_for_subgenre(subgenre));
+        let step_starts = self.swung_step_starts(kick_pattern.len(), beat_duration);
+        let kick_starts: Vec<usize> = kick_pattern
+            .iter()
+            .zip(&step_starts)
+            .filter(|&(&kick, _)| kick)
+            .map(|(_, &start)| start)
+            .collect();
+        sidechain_compress_at(&mut bass_audio, &kick_starts, sidechain);
+
+        // Breakdown stomp: the sub-drop goes in after the sidechain so the kick doesn't duck it
+        if section_type == MetalSection::Breakdown && self.sub_drop.unwrap_or_else(|| Self::sub_drop_for_subgenre(subgenre)) {
+            let root = riff.notes.first().map_or(BASS_FLOOR, |&note| Self::bass_note(note, self.bass_offset()));
+            let duration = beat_duration * SUB_DROP_BEATS;
+            Self::add_sub_drops(&mut bass_audio, kick_pattern, &step_starts, root, duration, &self.pitch);
+        }
 
         // 5. Dynamic Mixing (Turn down instruments to avoid clipping/noise)
-        let (guitar_level, bass_level, drum_level) = match intensity {
-            SectionIntensity::Low => (0.35, 0.40, 0.50),
-            SectionIntensity::Medium => (0.40, 0.45, 0.60),
-            SectionIntensity::High => (0.45, 0.50, 0.65),
-            SectionIntensity::Extreme => (0.50, 0.55, 0.70), // Louder, but safe
-        };
+        let (guitar_level, bass_level, drum_level) = self.mix.levels_for(intensity);
 
-        let max_len = guitar_audio.len().max(bass_audio.len()).max(drum_audio.len());
-        section_audio.resize(section_audio.len() + max_len, 0.0);
-        let offset = section_audio.len() - max_len;
+        let is_solo = section_type == MetalSection::Solo;
+        let reverb_send = self.reverb_send.scaled(Self::section_ambience(section_type));
+        // The section lasts exactly `duration` after its lead-in, whatever the riff's own length
+        let grid_len = self.section_length(section_type, duration) - section_audio.len();
+        section_audio.resize(section_audio.len() + grid_len, 0.0);
+        let offset = section_audio.len() - grid_len;
 
-        for i in 0..max_len {
-            let guitar = if i < guitar_audio.len() { guitar_audio[i] } else { 0.0 };
-            let bass = if i < bass_audio.len() { bass_audio[i] } else { 0.0 };
-            let drums = if i < drum_audio.len() { drum_audio[i] } else { 0.0 };
-            
-            section_audio[offset + i] = guitar * guitar_level + bass * bass_level + drums * drum_level;
+        // Shorter tracks are padded with silence and longer ones cut at the section's end;
+        // zipped iterators keep the loop free of bounds checks
+        fn padded(track: &[f32]) -> impl Iterator<Item = f32> + '_ {
+            track.iter().copied().chain(std::iter::repeat(0.0))
+        }
+        let tracks = padded(&guitar_audio).zip(padded(&bass_audio)).zip(padded(&drum_audio));
+        for (out, ((guitar, bass), drums)) in section_audio[offset..].iter_mut().zip(tracks) {
+            let (guitar, bass, drums) = (guitar * guitar_level, bass * bass_level, drums * drum_level);
+            let dry = guitar + bass + drums;
+
+            // Reverb sends: rhythm section into the room, solo guitar into the lead hall
+            let (rhythm_guitar, lead_guitar) = if is_solo { (0.0, guitar) } else { (guitar, 0.0) };
+            let room = self.room_reverb.process(reverb_send.room_input(drums, rhythm_guitar, bass)) * self.room_level;
+            let tail = self.lead_reverb.process(reverb_send.lead_input(lead_guitar));
+
+            *out = dry + room + tail;
+        }
+
+        if self.capture_stems {
+            // Same length and levels as the mix; the breakdown's drop kick belongs to the drums
+            let stem = |track: &[f32], level: f32| -> Vec<f32> {
+                std::iter::repeat_n(0.0, offset).chain(padded(track).take(grid_len).map(|s| s * level)).collect()
+            };
+            let mut drums = stem(&drum_audio, drum_level);
+            drums[..offset].copy_from_slice(&section_audio[..offset]);
+            self.section_stems = Some(Stems {
+                guitar: stem(&guitar_audio, guitar_level),
+                bass: stem(&bass_audio, bass_level),
+                drums,
+            });
         }
 
         section_audio
     }
 
+    /// Bass synthesis for a section: prog breakdowns swap the bass guitar for a
+    /// Reese whose wobble is locked to eighth notes
+    pub fn bass_voice(subgenre: MetalSubgenre, section: MetalSection, tempo: u16) -> BassVoice {
+        if subgenre == MetalSubgenre::ProgressiveMetal && section == MetalSection::Breakdown {
+            BassVoice::Reese { wobble_hz: tempo as f32 / 60.0 * 2.0 }
+        } else {
+            BassVoice::Picked
+        }
+    }
+
     /// Render bass guitar riff with locking support
     fn render_bass_riff_locked(
         &mut self,
@@ -139,11 +846,35 @@ impl MetalAudioRenderer {
         kick_pattern: &[bool],
         note_duration: f32,
         mode: BassMode,
-        _riff: &MetalRiff,
+        riff: &MetalRiff,
+        voice: BassVoice,
     ) -> Vec<f32> {
-        let mut bass_audio = Vec::new();
         let _sample_rate = self.sample_rate as f32;
+        let offset = self.bass_offset();
         let sixteenth_samples = (note_duration * _sample_rate) as usize;
+        let followed_notes = Self::follow_bass_notes(riff, note_duration);
+        // Lock mode rests half a sixteenth between kicks; the other modes play each followed note in full
+        let expected_samples = match mode {
+            BassMode::Lock => kick_pattern
+                .iter()
+                .map(|&kick_hit| if kick_hit { sixteenth_samples } else { sixteenth_samples / 2 })
+                .sum(),
+            BassMode::Counterpoint | BassMode::Follow => followed_notes
+                .iter()
+                .map(|&(_, duration)| (duration * _sample_rate) as usize)
+                .sum(),
+        };
+        // Per-note generators round their lengths to whole samples, so allow a couple per note
+        let rounding_slack = 2 * followed_notes.len().max(kick_pattern.len());
+        let mut bass_audio = Vec::with_capacity(expected_samples);
+        let generate_bass_note = |frequency: f32, duration: f32, velocity: f32| match voice {
+            BassVoice::Picked => generate_metal_bass_string(frequency, duration, velocity),
+            BassVoice::Reese { wobble_hz } => {
+                let mut note = generate_reese_bass_with_wobble(frequency, duration, wobble_hz);
+                note.iter_mut().for_each(|s| *s *= velocity);
+                note
+            },
+        };
         
         match mode {
             BassMode::Lock => {
@@ -159,11 +890,11 @@ impl MetalAudioRenderer {
                         };
                         
                         // Bass plays root of power chord or guitar note root
-                        let bass_note = guitar_note.saturating_sub(12);
-                        let frequency = 440.0 * 2.0_f32.powf((bass_note as f32 - 69.0) / 12.0);
+                        let bass_note = Self::bass_note(guitar_note, offset);
+                        let frequency = self.pitch.frequency(bass_note);
                         
                         // Generate bass note with heavy tone
-                        let bass_sample = generate_metal_bass_string(frequency, note_duration, 0.9);
+                        let bass_sample = generate_bass_note(frequency, note_duration, 0.9);
                         bass_audio.extend(bass_sample);
                     } else {
                         // No kick = sustain or silence
@@ -174,27 +905,83 @@ impl MetalAudioRenderer {
             },
             BassMode::Counterpoint => {
                 // Counterpoint Mode: Distinct bass lines
-                for &note in guitar_notes {
-                    let bass_note = note.saturating_sub(12);
-                    let frequency = 440.0 * 2.0_f32.powf((bass_note as f32 - 69.0) / 12.0);
-                    let bass_sample = generate_metal_bass_string(frequency, note_duration, 0.8);
+                for &(note, duration) in &followed_notes {
+                    let bass_note = Self::bass_note(note, offset);
+                    let frequency = self.pitch.frequency(bass_note);
+                    let bass_sample = generate_bass_note(frequency, duration, 0.8);
                     bass_audio.extend(bass_sample);
                 }
             },
             BassMode::Follow => {
                 // Follow Mode: Traditional bass following guitar
-                for &note in guitar_notes {
-                    let bass_note = note.saturating_sub(12);
-                    let frequency = 440.0 * 2.0_f32.powf((bass_note as f32 - 69.0) / 12.0);
-                    let bass_sample = generate_metal_bass_string(frequency, note_duration, 0.8);
+                for &(note, duration) in &followed_notes {
+                    let bass_note = Self::bass_note(note, offset);
+                    let frequency = self.pitch.frequency(bass_note);
+                    let bass_sample = generate_bass_note(frequency, duration, 0.8);
                     bass_audio.extend(bass_sample);
                 }
             },
         }
+        debug_assert!(
+            bass_audio.len().abs_diff(expected_samples) <= rounding_slack,
+            "bass riff rendered {} samples, expected {}",
+            bass_audio.len(),
+            expected_samples
+        );
         
         bass_audio
     }
 
+    /// Sample offset of each 16th step of the section's grid, swung like the drums
+    fn swung_step_starts(&self, steps: usize, beat_duration: f32) -> Vec<usize> {
+        (0..steps)
+            .map(|i| (Self::swing_position(i as f32 * 0.25, self.section_swing) * beat_duration * self.sample_rate as f32) as usize)
+            .collect()
+    }
+
+    /// Mix a sub-drop an octave below `bass_root` onto the first kick of each bar
+    /// (16 steps, starting at `step_starts`), extending the track if the last one rings past it
+    fn add_sub_drops(
+        bass_audio: &mut Vec<f32>,
+        kick_pattern: &[bool],
+        step_starts: &[usize],
+        bass_root: MidiNote,
+        duration: f32,
+        pitch: &PitchStandard,
+    ) {
+        let mut sub_note = bass_root.saturating_sub(12);
+        while sub_note < SUB_DROP_FLOOR {
+            sub_note += 12;
+        }
+        let drop = generate_sub_drop(pitch.frequency(sub_note), duration);
+
+        for (bar_index, steps) in kick_pattern.chunks(16).enumerate() {
+            let Some(step) = steps.iter().position(|&kick| kick) else { continue };
+            let Some(&start) = step_starts.get(bar_index * 16 + step) else { continue };
+            if bass_audio.len() < start + drop.len() {
+                bass_audio.resize(start + drop.len(), 0.0);
+            }
+            for (out, sample) in bass_audio[start..].iter_mut().zip(&drop) {
+                *out += sample * SUB_DROP_GAIN;
+            }
+        }
+    }
+
+    /// Notes the bass plays when following the guitar, with their lengths: one
+    /// `note_duration` per guitar note, except gallops, which the bass picks along
+    /// with the guitar (eighth + two sixteenths over the same beat)
+    fn follow_bass_notes(riff: &MetalRiff, note_duration: f32) -> Vec<(u8, f32)> {
+        let mut notes = Vec::with_capacity(riff.notes.len());
+        for (i, &note) in riff.notes.iter().enumerate() {
+            if riff.rhythms.get(i) == Some(&RhythmPattern::Gallop) {
+                notes.extend([2.0, 1.0, 1.0].map(|sixteenths| (note, note_duration * sixteenths)));
+            } else {
+                notes.push((note, note_duration));
+            }
+        }
+        notes
+    }
+
     /// Generate drum patterns based on RhythmicFeel (Tempo Decoupling)
     fn generate_drum_patterns(
         &self,
@@ -203,185 +990,326 @@ impl MetalAudioRenderer {
         tempo: u16,
         subgenre: MetalSubgenre,
         feel: RhythmicFeel,
-    ) -> (Vec<bool>, Vec<bool>, Vec<bool>) {
-        let _ = section; 
-        let beat_duration = 60.0 / tempo as f32;
-        let sixteenth_duration = beat_duration / 4.0; 
-        
-        let steps = (duration / sixteenth_duration).ceil() as usize;
-        
-        let mut kick = vec![false; steps];
-        let mut snare = vec![false; steps];
-        let mut cymbal = vec![false; steps];
-
-        match feel {
-            RhythmicFeel::HalfTime => {
-                // HALF TIME LOGIC (Breakdowns)
-                // Snare on beat 3 (Step 8 in a 0-15 grid)
-                // Kick is sparse, Euclidean pulses reduced
-                let pulses = 3; 
-                kick = rhythm_generator::generate_euclidean_pattern(steps, pulses);
-                
-                for i in 0..steps {
-                    // Snare on beat 3 (every 16 steps, offset 8)
-                    if i % 16 == 8 { 
-                        snare[i] = true; 
-                        kick[i] = false; // Don't kick on snare
-                    }
-                    // China/Crash on beat 1
-                    if i % 16 == 0 { cymbal[i] = true; kick[i] = true; }
-                }
-            },
-            RhythmicFeel::DoubleTime | RhythmicFeel::Blast => {
-                // BLAST LOGIC
-                // Every 2nd step (8th note at high tempo)
-                for i in 0..steps {
-                    if i % 2 == 0 {
-                        kick[i] = true;
-                        snare[i] = true; // Unison blast
-                        cymbal[i] = true;
-                    }
-                }
-            },
-            RhythmicFeel::Normal => {
-                // STANDARD METAL
-                // Snare on 2 and 4 (Steps 4 and 12)
-                let pulses = if matches!(subgenre, MetalSubgenre::ProgressiveMetal) { 7 } else { 5 };
-                kick = rhythm_generator::generate_euclidean_pattern(steps, pulses);
-                
-                for i in 0..steps {
-                    if i % 16 == 4 || i % 16 == 12 {
-                        snare[i] = true;
-                        kick[i] = false; // Clear kick for snare
-                    }
-                    // Sparse cymbals: only on beat 1 of each bar (every 16th step)
-                    if i % 16 == 0 {
-                        cymbal[i] = true;
-                        kick[i] = true;
-                    }
-                }
-            },
-        }
-        
-        (kick, snare, cymbal)
+        guitar: &GuitarContext,
+    ) -> DrumGrid {
+        generate_drum_grid(section, duration, tempo, subgenre, feel, guitar, &self.drum_options())
     }
 
-    /// Render guitar riff with chords support and variable durations
-    fn render_guitar_riff(&mut self, riff: &MetalRiff, beat_duration: f32) -> Vec<f32> {
-        let mut guitar_audio = Vec::new();
-        
-        for (i, &note) in riff.notes.iter().enumerate() {
-            let palm_muted = riff.palm_muted[i];
-            let chord_type = riff.chord_types.get(i).copied().unwrap_or(ChordType::Single);
-            let rhythm = riff.rhythms.get(i).copied().unwrap_or(RhythmPattern::SixteenthNote);
-            
-            // Handle rests
-            if rhythm == RhythmPattern::Rest {
-                let rest_duration = beat_duration / 4.0; // Default to sixteenth rest
-                let rest_samples = (rest_duration * self.sample_rate as f32) as usize;
-                guitar_audio.extend(vec![0.0; rest_samples]);
-                continue;
-            }
-            
-            // Calculate note duration from rhythm pattern
-            // Add minimum sustain to prevent cutoff at fast tempos
-            let base_duration = match rhythm {
-                RhythmPattern::QuarterNote => beat_duration,
-                RhythmPattern::EighthNote => beat_duration / 2.0,
-                RhythmPattern::SixteenthNote => beat_duration / 4.0,
-                RhythmPattern::ThirtySecondNote => beat_duration / 8.0,
-                RhythmPattern::Quintuplet => beat_duration * 0.8, // 5 notes in 4 beats
-                RhythmPattern::Septuplet => beat_duration * 0.571, // 7 notes in 4 beats
-                RhythmPattern::DottedEighth => beat_duration * 0.75, // 3/4 of a beat
-                RhythmPattern::Gallop => {
-                    // Gallop is handled specially - render 3 notes
-                    if let Some(gallop_samples) = self.render_gallop_pattern(riff, i, beat_duration, palm_muted, chord_type) {
-                        guitar_audio.extend(gallop_samples);
-                    }
-                    continue; // Skip normal note rendering for gallop
-                },
-                RhythmPattern::Rest => beat_duration / 4.0, // Shouldn't reach here
-            };
-            
-            // Add minimum sustain to prevent cutoff at fast tempos
-            // At 200+ BPM, sixteenth notes can be <0.075s which sounds clipped
-            let min_sustain = if palm_muted { 0.08 } else { 0.12 }; // Minimum sustain in seconds
-            let note_duration = base_duration.max(min_sustain);
-            
-            // Convert MIDI note to frequency: f = 440 * 2.0_f32.powf((n-69)/12)
-            let freq_root = 440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0);
-            
-            let is_palm_muted = palm_muted;
-            let velocity = 0.8;
-
-            let mut note_samples = Vec::new();
-
-            match chord_type {
-                ChordType::Power => {
-                    // Render Root
-                    let root_samples = generate_metal_guitar_note(freq_root, note_duration, velocity, is_palm_muted, PlayingTechnique::PowerChordRoot);
-                    
-                    // Render 5th (+7 semitones)
-                    let freq_5th = 440.0 * 2.0_f32.powf(((note + 7) as f32 - 69.0) / 12.0);
-                    let fifth_samples = generate_metal_guitar_note(freq_5th, note_duration, velocity, is_palm_muted, PlayingTechnique::PowerChordFifth);
-                    
-                    // Render Octave (+12 semitones)
-                    let freq_oct = 440.0 * 2.0_f32.powf(((note + 12) as f32 - 69.0) / 12.0);
-                    let oct_samples = generate_metal_guitar_note(freq_oct, note_duration, velocity, is_palm_muted, PlayingTechnique::PowerChordOctave);
-                    
-                    // Mix voices (Root loudest, 5th and Octave slightly quieter)
-                    let max_len = root_samples.len().max(fifth_samples.len()).max(oct_samples.len());
-                    note_samples.resize(max_len, 0.0);
-                    
-                    for j in 0..max_len {
-                        let s1 = if j < root_samples.len() { root_samples[j] } else { 0.0 };
-                        let s2 = if j < fifth_samples.len() { fifth_samples[j] } else { 0.0 };
-                        let s3 = if j < oct_samples.len() { oct_samples[j] } else { 0.0 };
-                        note_samples[j] = s1 * 0.5 + s2 * 0.3 + s3 * 0.2;
-                    }
-                },
-                ChordType::Minor => {
-                    // Render Minor chord (Root, 3rd, 5th)
-                    let root_samples = generate_metal_guitar_note(freq_root, note_duration, velocity, is_palm_muted, PlayingTechnique::MinorChordRoot);
-                    
-                    // Render 3rd (+3 semitones)
-                    let freq_3rd = 440.0 * 2.0_f32.powf(((note + 3) as f32 - 69.0) / 12.0);
-                    let third_samples = generate_metal_guitar_note(freq_3rd, note_duration, velocity, is_palm_muted, PlayingTechnique::MinorChordThird);
-                    
-                    // Render 5th (+7 semitones)
-                    let freq_5th = 440.0 * 2.0_f32.powf(((note + 7) as f32 - 69.0) / 12.0);
-                    let fifth_samples = generate_metal_guitar_note(freq_5th, note_duration, velocity, is_palm_muted, PlayingTechnique::MinorChordFifth);
-                    
-                    // Mix
-                    let max_len = root_samples.len().max(third_samples.len()).max(fifth_samples.len());
-                    note_samples.resize(max_len, 0.0);
-                    
-                    for j in 0..max_len {
-                        let s1 = if j < root_samples.len() { root_samples[j] } else { 0.0 };
-                        let s2 = if j < third_samples.len() { third_samples[j] } else { 0.0 };
-                        let s3 = if j < fifth_samples.len() { fifth_samples[j] } else { 0.0 };
-                        note_samples[j] = s1 * 0.4 + s2 * 0.3 + s3 * 0.3;
-                    }
-                },
-                ChordType::Diminished | ChordType::Octave => {
-                    // Fallback to single note for unsupported chord types
-                    note_samples = generate_metal_guitar_note(freq_root, note_duration, velocity, is_palm_muted, PlayingTechnique::SingleNote);
-                },
-                ChordType::Single => {
-                    // Render single note
-                    note_samples = generate_metal_guitar_note(freq_root, note_duration, velocity, is_palm_muted, PlayingTechnique::SingleNote);
-                },
-            }
-            
-            guitar_audio.extend(note_samples);
-        }
-        
-        // Apply distortion and cabinet simulation to the whole riff
-        self.process_guitar_chain(&guitar_audio)
+    /// Drum grid the renderer would play for a section of `duration` seconds, without
+    /// rendering it (kicks follow the chugs in `guitar`; blasts vary bar to bar)
+    pub fn drum_grid(
+        &self,
+        section: MetalSection,
+        guitar: &GuitarContext,
+        duration: f32,
+        tempo: u16,
+        subgenre: MetalSubgenre,
+    ) -> DrumGrid {
+        let feel = section.rhythmic_feel_for(subgenre);
+        self.context()
+            .scope(|| self.generate_drum_patterns(section, duration, tempo, subgenre, feel, guitar))
     }
 
-    /// Render a gallop pattern (eighth + two sixteenths)
-    fn render_gallop_pattern(
+    /// Full guitar track of a section: the riff, its double take and any harmony,
+    /// through the amp, cabinet and (for clean intros) the chorus
+    fn render_section_guitars(
+        &mut self,
+        section_type: MetalSection,
+        riff: &MetalRiff,
+        beat_duration: f32,
+        clean_intro: bool,
+    ) -> Vec<f32> {
+        let mut guitar_audio = self.render_guitar_riff(riff, beat_duration);
+
+        // Double-tracked rhythm guitar: a second, slightly different performance
+        // (the mix bus is mono, so the takes are summed rather than panned L/R)
+        if self.double_track && section_type != MetalSection::Solo {
+            let second_take = self.render_double_take(riff, beat_duration);
+            if second_take.len() > guitar_audio.len() {
+                guitar_audio.resize(second_take.len(), 0.0);
+            }
+            for (first, second) in guitar_audio.iter_mut().zip(second_take.iter()) {
+                *first = (*first + second) * 0.5;
+            }
+        }
+
+        // Harmonized lead: second voice with the same rhythm, mixed under the melody
+        // (the mix bus is mono, so the harmony sits centered rather than panned)
+        if let Some(harmony_notes) = &riff.harmony_notes {
+            // The pathfinder's positions belong to the melody, not the harmony
+            let harmony_riff = MetalRiff {
+                notes: harmony_notes.clone(),
+                chord_types: vec![ChordType::Single; harmony_notes.len()],
+                fret_positions: Vec::new(),
+                ..riff.clone()
+            };
+            let harmony_audio = self.render_guitar_riff(&harmony_riff, beat_duration);
+            if harmony_audio.len() > guitar_audio.len() {
+                guitar_audio.resize(harmony_audio.len(), 0.0);
+            }
+            for (lead, harmony) in guitar_audio.iter_mut().zip(harmony_audio.iter()) {
+                *lead = *lead * 0.75 + harmony * 0.45;
+            }
+        }
+
+        // Clean intro: modulation instead of gain
+        if clean_intro {
+            if let Some(chorus) = self.intro_chorus.as_mut() {
+                chorus.process_buffer(&mut guitar_audio);
+            }
+        }
+
+        guitar_audio
+    }
+
+    /// Identity of a section's guitar track: the riff plus every setting that changes how it
+    /// sounds (drive, channel, swing, picking feel, double tracking)
+    fn guitar_cache_key(&self, section_type: MetalSection, riff: &MetalRiff, beat_duration: f32) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        section_type.hash(&mut hasher);
+        riff.notes.hash(&mut hasher);
+        riff.chord_types.hash(&mut hasher);
+        riff.palm_muted.hash(&mut hasher);
+        riff.rhythms.hash(&mut hasher);
+        riff.harmony_notes.hash(&mut hasher);
+        riff.fret_positions.hash(&mut hasher);
+        riff.clean.hash(&mut hasher);
+        self.tuning.string_notes().hash(&mut hasher);
+        let humanizer = &self.section_guitar_humanizer;
+        let settings = [
+            beat_duration,
+            self.section_swing,
+            humanizer.timing_variance,
+            humanizer.timing_bias,
+            humanizer.articulation,
+            humanizer.length_variance,
+            self.dsp_chain.drive(),
+        ];
+        for value in riff.velocities.iter().chain(&settings) {
+            value.to_bits().hash(&mut hasher);
+        }
+        self.dsp_chain.is_distortion_bypassed().hash(&mut hasher);
+        self.double_track.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Second take of a riff: detuned by 2-4 cents and landing a few samples late
+    fn render_double_take(&mut self, riff: &MetalRiff, beat_duration: f32) -> Vec<f32> {
+        let mut rng = crate::utils::rng();
+        let cents = rng.gen_range(DOUBLE_TRACK_MIN_CENTS..=DOUBLE_TRACK_MAX_CENTS);
+        let cents = if rng.gen_bool(0.5) { cents } else { -cents };
+        let offset = rng.gen_range(1..=DOUBLE_TRACK_MAX_OFFSET);
+
+        self.take_detune = 2.0_f32.powf(cents / 1200.0);
+        let take = self.render_guitar_riff(riff, beat_duration);
+        self.take_detune = 1.0;
+
+        let mut delayed = vec![0.0; offset];
+        delayed.extend(take);
+        delayed
+    }
+
+    /// Frequency of a guitar note in the take being rendered
+    fn take_frequency(&self, note: MidiNote) -> f32 {
+        self.pitch.frequency(note) * self.take_detune
+    }
+
+    /// Voices of a chord on `note` as (note, technique, mix gain); tones that would pass
+    /// MIDI 127 are skipped instead of wrapping around to a low note
+    fn chord_voices(note: MidiNote, chord_type: ChordType) -> Vec<(MidiNote, PlayingTechnique, f32)> {
+        let voices: &[(u8, PlayingTechnique, f32)] = match chord_type {
+            // Root loudest, 5th and Octave slightly quieter
+            ChordType::Power => &[
+                (0, PlayingTechnique::PowerChordRoot, 0.5),
+                (7, PlayingTechnique::PowerChordFifth, 0.3),
+                (12, PlayingTechnique::PowerChordOctave, 0.2),
+            ],
+            ChordType::Minor => &[
+                (0, PlayingTechnique::MinorChordRoot, 0.4),
+                (3, PlayingTechnique::MinorChordThird, 0.3),
+                (7, PlayingTechnique::MinorChordFifth, 0.3),
+            ],
+            // Same triad stacked from its third or fifth; the root moves up an octave
+            ChordType::MinorInversion(Inversion::First) => &[
+                (3, PlayingTechnique::MinorChordThird, 0.4),
+                (7, PlayingTechnique::MinorChordFifth, 0.3),
+                (12, PlayingTechnique::MinorChordRoot, 0.3),
+            ],
+            ChordType::MinorInversion(Inversion::Second) => &[
+                (7, PlayingTechnique::MinorChordFifth, 0.4),
+                (12, PlayingTechnique::MinorChordRoot, 0.3),
+                (15, PlayingTechnique::MinorChordThird, 0.3),
+            ],
+            // Fallback to single note for unsupported chord types
+            ChordType::Diminished | ChordType::Octave | ChordType::Single => &[(0, PlayingTechnique::SingleNote, 1.0)],
+        };
+
+        voices
+            .iter()
+            .filter_map(|&(interval, technique, gain)| {
+                note.checked_add(interval)
+                    .filter(|&tone| tone <= 127)
+                    .map(|tone| (tone, technique, gain))
+            })
+            .collect()
+    }
+
+    /// Render a chord (or single note) by mixing its voices
+    fn render_chord(&self, note: MidiNote, chord_type: ChordType, duration: f32, velocity: f32, palm_muted: bool) -> Vec<f32> {
+        let mut mixed: Vec<f32> = Vec::new();
+        for (tone, technique, gain) in Self::chord_voices(note, chord_type) {
+            let voice = generate_metal_guitar_note(self.take_frequency(tone), duration, velocity, palm_muted, technique);
+            if voice.len() > mixed.len() {
+                mixed.resize(voice.len(), 0.0);
+            }
+            for (out, sample) in mixed.iter_mut().zip(&voice) {
+                *out += sample * gain;
+            }
+        }
+        mixed
+    }
+
+    /// Grid length in beats a riff note advances by (rests are sixteenths, gallops a full beat)
+    fn grid_beats(rhythm: RhythmPattern) -> f32 {
+        match rhythm {
+            RhythmPattern::Rest => 0.25,
+            RhythmPattern::Gallop => 1.0, // Eighth + two sixteenths
+            _ => rhythm.to_beats(),
+        }
+    }
+
+    /// Seconds a riff note occupies when it starts `position` beats into the riff
+    fn note_slot(&self, rhythm: RhythmPattern, position: f32, palm_muted: bool, beat_duration: f32) -> f32 {
+        // Calculate note duration from rhythm pattern
+        let base_duration = match rhythm {
+            RhythmPattern::QuarterNote => beat_duration,
+            RhythmPattern::EighthNote => beat_duration / 2.0,
+            RhythmPattern::SixteenthNote => beat_duration / 4.0,
+            RhythmPattern::ThirtySecondNote => beat_duration / 8.0,
+            RhythmPattern::DottedEighth => beat_duration * 0.75, // 3/4 of a beat
+            // Rendered as three notes, unswung
+            RhythmPattern::Gallop => return beat_duration,
+            // One held note re-picked across its duration
+            RhythmPattern::Tremolo { .. } => return rhythm.to_beats() * beat_duration,
+            // Tuplets split their span evenly (5 or 7 notes in 4 beats), unswung and
+            // without the minimum sustain, so the group always fills exactly its span
+            RhythmPattern::Quintuplet | RhythmPattern::Septuplet => return rhythm.to_beats() * beat_duration,
+            // Default to sixteenth rest
+            RhythmPattern::Rest => return self.swung_length(position, 0.25) * beat_duration,
+        };
+        // Swing stretches on-beat 16ths and squeezes the offbeats after them
+        let base_duration = base_duration * self.swung_length(position, rhythm.to_beats()) / rhythm.to_beats();
+
+        // Add minimum sustain to prevent cutoff at fast tempos
+        // At 200+ BPM, sixteenth notes can be <0.075s which sounds clipped
+        let min_sustain = if palm_muted { 0.08 } else { 0.12 }; // Minimum sustain in seconds
+        base_duration.max(min_sustain)
+    }
+
+    /// Expected length of a rendered riff in samples, before any humanized overhang
+    /// (gallops may differ by a sample or two of rounding)
+    fn guitar_riff_samples(&self, riff: &MetalRiff, beat_duration: f32) -> usize {
+        let mut position = 0.0;
+        let mut samples = 0;
+        for i in 0..riff.notes.len() {
+            let rhythm = riff.rhythms.get(i).copied().unwrap_or(RhythmPattern::SixteenthNote);
+            let slot = self.note_slot(rhythm, position, riff.palm_muted[i], beat_duration);
+            samples += (slot * self.sample_rate as f32) as usize;
+            position += Self::grid_beats(rhythm);
+        }
+        samples
+    }
+
+    /// Pitch actually played for note `i`: the pathfinder's string + fret when the riff has
+    /// one position per note (so tab, MIDI and audio agree), the abstract note otherwise
+    fn fretted_note(&self, riff: &MetalRiff, i: usize) -> MidiNote {
+        if riff.fret_positions.len() == riff.notes.len() {
+            let position = riff.fret_positions[i];
+            if let Some(&open) = self.tuning.string_notes().get(position.string as usize) {
+                return open.saturating_add(position.fret);
+            }
+        }
+        riff.notes[i]
+    }
+
+    /// Render guitar riff with chords support and variable durations
+    fn render_guitar_riff(&mut self, riff: &MetalRiff, beat_duration: f32) -> Vec<f32> {
+        let expected_samples = self.guitar_riff_samples(riff, beat_duration);
+        // Headroom for notes humanized past the end of the riff
+        let mut guitar_audio = Vec::with_capacity(expected_samples + self.sample_rate as usize / 50);
+        let mut position = 0.0; // Grid position in beats, for swing
+        let mut cursor = 0; // Grid position in samples; humanized notes land around it
+        
+        for i in 0..riff.notes.len() {
+            let note = self.fretted_note(riff, i);
+            let palm_muted = riff.palm_muted[i];
+            let chord_type = riff.chord_types.get(i).copied().unwrap_or(ChordType::Single);
+            let rhythm = riff.rhythms.get(i).copied().unwrap_or(RhythmPattern::SixteenthNote);
+            let note_duration = self.note_slot(rhythm, position, palm_muted, beat_duration);
+            position += Self::grid_beats(rhythm);
+            
+            match rhythm {
+                RhythmPattern::Rest => {
+                    cursor += (note_duration * self.sample_rate as f32) as usize;
+                    continue;
+                },
+                RhythmPattern::Gallop => {
+                    // Gallop is handled specially - render 3 notes
+                    if let Some(gallop_samples) = self.render_gallop_pattern(riff, i, beat_duration, palm_muted, chord_type) {
+                        Self::place(&mut guitar_audio, cursor, &gallop_samples);
+                        cursor += gallop_samples.len();
+                    }
+                    continue; // Skip normal note rendering for gallop
+                },
+                RhythmPattern::Tremolo { .. } => {
+                    let tremolo_samples = self.render_tremolo_note(note, rhythm, note_duration);
+                    Self::place(&mut guitar_audio, cursor, &tremolo_samples);
+                    cursor += tremolo_samples.len();
+                    continue;
+                },
+                _ => {},
+            }
+            
+            let velocity = riff.velocities.get(i).copied().unwrap_or(DEFAULT_GUITAR_VELOCITY);
+
+            // The note keeps its slot on the grid, but is picked slightly early/late
+            // and rings for only part of it (staccato) depending on the player's feel
+            let slot_samples = (note_duration * self.sample_rate as f32) as usize;
+            let sounding = self.section_guitar_humanizer.humanize_length(note_duration);
+            let offset = (self.section_guitar_humanizer.humanize_timing() * self.sample_rate as f32) as isize;
+            let onset = (cursor as isize + offset).max(0) as usize;
+
+            // Chord tones above MIDI 127 are dropped rather than wrapped
+            let note_samples = self.render_chord(note, chord_type, sounding, velocity, palm_muted);
+            Self::place(&mut guitar_audio, onset, &note_samples);
+            cursor += slot_samples;
+        }
+        debug_assert!(
+            cursor.abs_diff(expected_samples) <= 2 * riff.notes.len(),
+            "guitar riff rendered {} samples, expected {}",
+            cursor,
+            expected_samples
+        );
+        if guitar_audio.len() < cursor {
+            guitar_audio.resize(cursor, 0.0);
+        }
+        
+        // Apply distortion and cabinet simulation to the whole riff
+        self.process_guitar_chain(&guitar_audio)
+    }
+
+    /// Mix `samples` into `buffer` starting at `start`, growing the buffer as needed
+    fn place(buffer: &mut Vec<f32>, start: usize, samples: &[f32]) {
+        if buffer.len() < start + samples.len() {
+            buffer.resize(start + samples.len(), 0.0);
+        }
+        for (out, &sample) in buffer[start..].iter_mut().zip(samples) {
+            *out += sample;
+        }
+    }
+
+    /// Render a gallop pattern (eighth + two sixteenths)
+    fn render_gallop_pattern(
         &mut self,
         riff: &MetalRiff,
         start_idx: usize,
@@ -393,9 +1321,8 @@ impl MetalAudioRenderer {
             return None;
         }
         
-        let note = riff.notes[start_idx];
-        let freq_root = 440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0);
-        let velocity = 0.8;
+        let note = self.fretted_note(riff, start_idx);
+        let velocity = riff.velocities.get(start_idx).copied().unwrap_or(DEFAULT_GUITAR_VELOCITY);
         
         // Gallop durations: [eighth, sixteenth, sixteenth]
         let durations = vec![
@@ -407,26 +1334,8 @@ impl MetalAudioRenderer {
         let mut gallop_samples = Vec::new();
         for duration in durations {
             let note_samples = match chord_type {
-                ChordType::Power => {
-                    let root_samples = generate_metal_guitar_note(freq_root, duration, velocity, palm_muted, PlayingTechnique::PowerChordRoot);
-                    let freq_5th = 440.0 * 2.0_f32.powf(((note + 7) as f32 - 69.0) / 12.0);
-                    let fifth_samples = generate_metal_guitar_note(freq_5th, duration, velocity, palm_muted, PlayingTechnique::PowerChordFifth);
-                    let freq_oct = 440.0 * 2.0_f32.powf(((note + 12) as f32 - 69.0) / 12.0);
-                    let oct_samples = generate_metal_guitar_note(freq_oct, duration, velocity, palm_muted, PlayingTechnique::PowerChordOctave);
-                    
-                    let max_len = root_samples.len().max(fifth_samples.len()).max(oct_samples.len());
-                    let mut mixed = vec![0.0; max_len];
-                    for j in 0..max_len {
-                        let s1 = if j < root_samples.len() { root_samples[j] } else { 0.0 };
-                        let s2 = if j < fifth_samples.len() { fifth_samples[j] } else { 0.0 };
-                        let s3 = if j < oct_samples.len() { oct_samples[j] } else { 0.0 };
-                        mixed[j] = s1 * 0.5 + s2 * 0.3 + s3 * 0.2;
-                    }
-                    mixed
-                },
-                _ => {
-                    generate_metal_guitar_note(freq_root, duration, velocity, palm_muted, PlayingTechnique::SingleNote)
-                },
+                ChordType::Power => self.render_chord(note, chord_type, duration, velocity, palm_muted),
+                _ => self.render_chord(note, ChordType::Single, duration, velocity, palm_muted),
             };
             gallop_samples.extend(note_samples);
         }
@@ -434,6 +1343,29 @@ impl MetalAudioRenderer {
         Some(gallop_samples)
     }
 
+    /// Render a tremolo-picked note: `rhythm.tremolo_picks` re-articulations of the same pitch,
+    /// alternating down/up strokes with slight velocity variation
+    fn render_tremolo_note(&self, note: MidiNote, rhythm: RhythmPattern, duration: f32) -> Vec<f32> {
+        let mut rng = crate::utils::rng();
+        let freq = self.take_frequency(note);
+        let total_samples = (duration * self.sample_rate as f32) as usize;
+        let picks = rhythm.tremolo_picks(duration);
+        let pick_samples = total_samples / picks;
+        let pick_duration = pick_samples as f32 / self.sample_rate as f32;
+
+        let mut samples = Vec::with_capacity(total_samples);
+        for pick in 0..picks {
+            // Upstrokes are slightly weaker than downstrokes
+            let stroke = if pick % 2 == 0 { 1.0 } else { 0.88 };
+            let velocity = 0.75 * stroke * rng.gen_range(0.9..1.0);
+            let mut pick_audio = generate_metal_guitar_note(freq, pick_duration, velocity, false, PlayingTechnique::SingleNote);
+            pick_audio.resize(pick_samples, 0.0);
+            samples.extend(pick_audio);
+        }
+        samples.resize(total_samples, 0.0);
+        samples
+    }
+
     /// Process audio through the guitar DSP chain
     fn process_guitar_chain(&mut self, samples: &[f32]) -> Vec<f32> {
         let mut processed = Vec::with_capacity(samples.len());
@@ -451,53 +1383,245 @@ impl MetalAudioRenderer {
         processed
     }
 
-    fn render_drums(&self, section: MetalSection, duration: f32, tempo: u16, subgenre: MetalSubgenre, feel: RhythmicFeel) -> Vec<f32> {
+    fn render_drums(
+        &self,
+        section: MetalSection,
+        grid: &DrumGrid,
+        duration: f32,
+        tempo: u16,
+        subgenre: MetalSubgenre,
+        humanizer: &DrumHumanizer,
+    ) -> Vec<f32> {
+        let feel = section.rhythmic_feel_for(subgenre);
         let sample_rate = self.sample_rate as f32;
         let num_samples = (duration * sample_rate) as usize;
         let mut drum_audio = vec![0.0; num_samples];
         
         let beat_duration = 60.0 / tempo as f32;
-        let sixteenth_duration = beat_duration / 4.0;
         
-        // Pass 'feel' to pattern generator
-        let (kick_pattern, snare_pattern, cymbal_pattern) = self.generate_drum_patterns(section, duration, tempo, subgenre, feel);
+        let (kick_pattern, snare_pattern, cymbal_pattern) = (&grid.kick, &grid.snare, &grid.cymbal);
+
+        // Section dynamics: base MIDI velocity before humanization
+        let base_velocity = match section.intensity() {
+            SectionIntensity::Low => 100,
+            SectionIntensity::Medium => 108,
+            SectionIntensity::High => 114,
+            SectionIntensity::Extreme => 120,
+        };
+
+        // Per-section articulation state (ghost notes, flams, stamina, limb imbalance)
+        let mut articulations = DrumArticulationGenerator::new();
+        let allow_ghosts = feel == RhythmicFeel::Normal;
+        let allow_flams = matches!(feel, RhythmicFeel::Normal | RhythmicFeel::HalfTime);
+        let cymbal_style = self.drum_options().cymbal_style_for(section);
 
         // Render loop
         for i in 0..kick_pattern.len() {
-            let base_time = i as f32 * sixteenth_duration;
-            let sample_idx = (base_time * sample_rate) as usize;
-            
-            if sample_idx >= num_samples { break; }
+            let base_time = Self::swing_position(i as f32 * 0.25, self.section_swing) * beat_duration;
+            if (base_time * sample_rate) as usize >= num_samples { break; }
 
-            // Velocity logic...
-            let velocity = 0.9; // Less than 1.0 to prevent clipping
+            // Downbeats are always accented; blasts add (or shift to) their own accents
+            let is_downbeat = i % 16 == 0;
+            let accent = grid.accent[i];
 
+            // Each limb gets its own timing/velocity jitter so hits don't stack robotically
             if kick_pattern[i] {
+                let kick_velocity = articulations.kick_hit(base_velocity);
+                let (sample_idx, velocity) = self.humanized_hit(humanizer, base_time, beat_duration, kick_velocity, accent);
                 let kick_sound = self.drums.generate_kick(velocity);
                 self.mix_drum_hit(&mut drum_audio, &kick_sound, sample_idx);
             }
             if snare_pattern[i] {
-                let snare_sound = self.drums.generate_snare(velocity);
-                self.mix_drum_hit(&mut drum_audio, &snare_sound, sample_idx);
+                // Main snare hits: backbeat accents, occasional flam on beat 4
+                let is_backbeat = feel.is_backbeat(i);
+                let (snare_velocity, articulation) = if allow_flams && is_backbeat && articulations.should_flam(i / 4) {
+                    (Articulation::Flam.base_velocity(), Articulation::Flam)
+                } else {
+                    // Index 0 keeps main hits out of the ghost pattern
+                    articulations.snare_hit(0, is_backbeat)
+                };
+                let (sample_idx, velocity) = self.humanized_hit(humanizer, base_time, beat_duration, snare_velocity, accent);
+                for (onset, onset_velocity) in self.snare_onsets(articulation, sample_idx, velocity) {
+                    let snare_sound = self.drums.generate_snare(onset_velocity);
+                    self.mix_drum_hit(&mut drum_audio, &snare_sound, onset);
+                }
+            } else if allow_ghosts && i % 8 == 3 && !kick_pattern[i] {
+                // Ghost notes on the "a" before each backbeat
+                let (ghost_velocity, articulation) = articulations.snare_hit(i, false);
+                if articulation == Articulation::GhostNote {
+                    let (sample_idx, velocity) = self.humanized_hit(humanizer, base_time, beat_duration, ghost_velocity, false);
+                    let ghost_sound = self.drums.generate_snare(velocity);
+                    self.mix_drum_hit(&mut drum_audio, &ghost_sound, sample_idx);
+                }
             }
-            if cymbal_pattern[i] {
-                let crash_sound = self.drums.generate_crash(velocity * 0.8);
+            // The "1" of a new section always gets its crash (or china), even when the
+            // cymbal pattern leaves that step empty
+            let section_crash = if i == 0 { Self::section_crash(section) } else { None };
+            if let Some((china, gain)) = section_crash {
+                let (sample_idx, velocity) = self.humanized_hit(humanizer, base_time, beat_duration, base_velocity, true);
+                let crash_sound = if china {
+                    self.drums.generate_china(velocity * gain)
+                } else {
+                    self.drums.generate_crash(velocity * gain)
+                };
                 self.mix_drum_hit(&mut drum_audio, &crash_sound, sample_idx);
+            } else if cymbal_pattern[i] {
+                let (sample_idx, velocity) = self.humanized_hit(humanizer, base_time, beat_duration, base_velocity, is_downbeat);
+                let cymbal_sound = if is_downbeat {
+                    self.drums.generate_crash(velocity * 0.8)
+                } else {
+                    // Quarter notes a little louder than the offbeats
+                    let pulse = if i % 4 == 0 { 1.0 } else { 0.8 };
+                    match cymbal_style.time_keeper {
+                        TimeKeeper::ClosedHiHat => self.drums.generate_hihat(velocity * pulse, false),
+                        TimeKeeper::Ride => self.drums.generate_ride(velocity * pulse * 0.7),
+                    }
+                };
+                self.mix_drum_hit(&mut drum_audio, &cymbal_sound, sample_idx);
             }
         }
 
+        // Build-ups swell into the chorus under a noise riser over their back half
+        if section == MetalSection::PreChorus {
+            let riser = generate_riser(duration / 2.0);
+            self.mix_drum_hit(&mut drum_audio, &riser, num_samples.saturating_sub(riser.len()));
+        }
+
         drum_audio
     }
+
+    /// Cymbal marking the start of a section as (china instead of crash, gain):
+    /// choruses and breakdowns hit hardest, clean intros start without one
+    pub fn section_crash(section: MetalSection) -> Option<(bool, f32)> {
+        match section {
+            MetalSection::Intro => None,
+            MetalSection::Verse | MetalSection::PreChorus | MetalSection::Outro => Some((false, 0.8)),
+            MetalSection::Solo => Some((false, 0.9)),
+            MetalSection::Chorus => Some((false, 1.0)),
+            MetalSection::Breakdown => Some((true, 1.0)),
+        }
+    }
+
+    /// Expand a snare articulation into individual strokes (sample index, velocity)
+    /// Flams add a soft grace stroke ~5 ms before the main hit, drags add two
+    fn snare_onsets(&self, articulation: Articulation, sample_idx: usize, velocity: f32) -> Vec<(usize, f32)> {
+        let sample_rate = self.sample_rate as f32;
+        match articulation {
+            Articulation::Flam => {
+                let gap = (FLAM_GAP * sample_rate) as usize;
+                vec![(sample_idx.saturating_sub(gap), velocity * 0.5), (sample_idx, velocity)]
+            },
+            Articulation::Drag => {
+                let gap = (DRAG_GAP * sample_rate) as usize;
+                vec![
+                    (sample_idx.saturating_sub(gap * 2), velocity * 0.35),
+                    (sample_idx.saturating_sub(gap), velocity * 0.35),
+                    (sample_idx, velocity),
+                ]
+            },
+            _ => vec![(sample_idx, velocity)],
+        }
+    }
+
+    /// Apply humanizer timing jitter and velocity variation to a grid hit
+    /// Returns (sample index, normalized velocity 0.0-1.0)
+    fn humanized_hit(
+        &self,
+        humanizer: &DrumHumanizer,
+        base_time: f32,
+        beat_duration: f32,
+        base_velocity: u8,
+        accent: bool,
+    ) -> (usize, f32) {
+        let (velocity, timing_ticks) = humanizer.humanize_hit(base_velocity, accent);
+        let offset_seconds = timing_ticks as f32 * beat_duration / TICKS_PER_BEAT;
+        let hit_time = (base_time + offset_seconds).max(0.0);
+        let sample_idx = (hit_time * self.sample_rate as f32) as usize;
+
+        (sample_idx, normalize_velocity(velocity))
+    }
     
     /// Mix a drum hit into the main buffer
     fn mix_drum_hit(&self, buffer: &mut [f32], hit: &[f32], start_idx: usize) {
-        for (i, &sample) in hit.iter().enumerate() {
-            if start_idx + i < buffer.len() {
-                buffer[start_idx + i] += sample;
+        if let Some(tail) = buffer.get_mut(start_idx..) {
+            for (out, &sample) in tail.iter_mut().zip(hit) {
+                *out += sample;
             }
         }
     }
 
+    /// Samples before a section's grid starts (the breakdown's silence + drop kick)
+    pub fn section_lead_in(&self, section: MetalSection) -> usize {
+        match section {
+            MetalSection::Breakdown => {
+                (BREAKDOWN_DROP_SILENCE * self.sample_rate as f32) as usize
+                    + (DROP_KICK_DURATION * self.sample_rate as f32) as usize
+            },
+            _ => 0,
+        }
+    }
+
+    /// Samples `render_section` returns for a section of `duration` seconds:
+    /// the lead-in plus `duration` rounded to the nearest sample
+    pub fn section_length(&self, section: MetalSection, duration: f32) -> usize {
+        self.section_lead_in(section) + (duration.max(0.0) * self.sample_rate as f32).round() as usize
+    }
+
+    /// Metronome for one rendered section of `length` samples: a tick on every beat,
+    /// accented on each bar's downbeat, starting where the section's grid starts
+    pub fn render_click_section(&self, section: MetalSection, tempo: u16, length: usize) -> Vec<f32> {
+        let mut click = vec![0.0; length];
+        let beat_samples = 60.0 / tempo.max(1) as f32 * self.sample_rate as f32;
+        let lead_in = self.section_lead_in(section);
+
+        let mut beat = 0;
+        loop {
+            let start = lead_in + (beat as f32 * beat_samples) as usize;
+            if start >= length {
+                break;
+            }
+            // Four beats to the bar, as everywhere else in the renderer
+            let tick = self.context().scope(|| generate_click(beat % 4 == 0));
+            self.mix_drum_hit(&mut click, &tick, start);
+            beat += 1;
+        }
+
+        click
+    }
+
+    /// Click track for a whole song on the nominal grid (`duration_per_section` per section,
+    /// plus breakdown lead-ins), joined the way `render_song` joins sections.
+    /// Sections whose tails ring past their duration shift the rendered song; for an exact
+    /// match, mix `render_click_section` into each section as it's rendered.
+    pub fn render_click_track(&self, song: &MetalSong, duration_per_section: f32) -> Vec<f32> {
+        let section_samples = (duration_per_section * self.sample_rate as f32) as usize;
+        let mut click = Vec::new();
+        for (section, _) in &song.sections {
+            let length = self.section_lead_in(*section) + section_samples;
+            let section_click = self.render_click_section(*section, song.tempo, length);
+            Self::append_crossfaded(&mut click, &section_click, self.sample_rate);
+        }
+        click
+    }
+
+    /// Append a section with a short equal-power crossfade over the end of `buffer`.
+    /// Returns how many samples the buffer grew by.
+    pub fn append_crossfaded(buffer: &mut Vec<f32>, section: &[f32], sample_rate: u32) -> usize {
+        let fade_samples = ((SECTION_CROSSFADE * sample_rate as f32) as usize)
+            .min(buffer.len())
+            .min(section.len());
+        let overlap_start = buffer.len() - fade_samples;
+
+        for (i, &incoming) in section[..fade_samples].iter().enumerate() {
+            let t = (i as f32 + 0.5) / fade_samples as f32 * std::f32::consts::FRAC_PI_2;
+            let outgoing = &mut buffer[overlap_start + i];
+            *outgoing = *outgoing * t.cos() + incoming * t.sin();
+        }
+        buffer.extend_from_slice(&section[fade_samples..]);
+
+        section.len() - fade_samples
+    }
+
     /// Normalize audio buffer using soft clipping limiter
     fn apply_limiter(samples: &mut [f32], threshold: f32) {
         for sample in samples.iter_mut() {
@@ -514,4 +1638,770 @@ impl Default for MetalAudioRenderer {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composition::metal_song_generator::{MetalSongGenerator, TUPLET_SPAN_BEATS};
+    use crate::composition::drum_patterns::CymbalDensity;
+    use crate::composition::music_theory::midi_to_freq;
+    use crate::composition::rhythm_generator;
+
+    /// Palm-muted power chords on the given notes and rhythms; tests override the rest
+    fn test_riff(notes: Vec<MidiNote>, rhythms: Vec<RhythmPattern>) -> MetalRiff {
+        MetalRiff {
+            chord_types: vec![ChordType::Power; notes.len()],
+            palm_muted: vec![true; notes.len()],
+            notes,
+            rhythms,
+            velocities: Vec::new(),
+            fret_positions: Vec::new(),
+            harmony_notes: None,
+            playability_score: 1.0,
+            clean: false,
+        }
+    }
+
+    #[test]
+    fn test_humanized_hits_vary_at_same_grid_step() {
+        let renderer = MetalAudioRenderer::new();
+        let humanizer = DrumHumanizer::new();
+        let beat_duration = 0.5;
+        let base_time = 4.0 * beat_duration;
+        let grid_idx = (base_time * renderer.sample_rate as f32) as usize;
+
+        let offsets: Vec<usize> = (0..20)
+            .map(|_| renderer.humanized_hit(&humanizer, base_time, beat_duration, 100, false).0)
+            .collect();
+
+        // Not all hits land on the same sample
+        assert!(offsets.iter().any(|&o| o != offsets[0]));
+
+        // But every hit stays within the humanizer's timing window
+        let max_offset = (humanizer.timing_variance.abs() + humanizer.timing_bias.abs()) as f32
+            * beat_duration / TICKS_PER_BEAT * renderer.sample_rate as f32;
+        for &offset in &offsets {
+            assert!((offset as f32 - grid_idx as f32).abs() <= max_offset + 1.0);
+        }
+    }
+
+    #[test]
+    fn test_tremolo_note_expands_into_picks() {
+        let renderer = MetalAudioRenderer::new();
+        let tremolo = RhythmPattern::Tremolo { rate_hz: 12 };
+        let duration = 0.5;
+
+        // 12 picks per second over half a second
+        assert_eq!(tremolo.tremolo_picks(duration), 6);
+
+        // Picks fill exactly the held note's duration
+        let samples = renderer.render_tremolo_note(64, tremolo, duration);
+        assert_eq!(samples.len(), (duration * renderer.sample_rate as f32) as usize);
+        assert!(samples.iter().any(|s| s.abs() > 0.0));
+    }
+
+    #[test]
+    fn test_kicks_lock_to_guitar_accents() {
+        let renderer = MetalAudioRenderer::new();
+        // Palm-muted chugs land on 16th steps 0, 2, 4 (under the backbeat snare) and 13
+        let rhythms = vec![
+            RhythmPattern::EighthNote,
+            RhythmPattern::SixteenthNote,
+            RhythmPattern::SixteenthNote,
+            RhythmPattern::SixteenthNote,
+            RhythmPattern::QuarterNote,
+            RhythmPattern::QuarterNote,
+            RhythmPattern::EighthNote,
+        ];
+        let riff = MetalRiff {
+            palm_muted: vec![true, true, false, true, false, false, true],
+            ..test_riff(vec![40; rhythms.len()], rhythms)
+        };
+        let context = GuitarContext::from_riff(&riff);
+        assert_eq!(context.accent_steps, vec![0, 2, 4, 13]);
+
+        // One bar at 120 BPM
+        let kick = renderer.generate_drum_patterns(
+            MetalSection::Verse, 2.0, 120, MetalSubgenre::HeavyMetal, RhythmicFeel::Normal, &context,
+        ).kick;
+        for &step in &context.accent_steps {
+            assert!(kick[step], "no kick under the chug on step {}", step);
+        }
+    }
+
+    #[test]
+    fn test_pre_chorus_builds_into_the_chorus() {
+        let renderer = MetalAudioRenderer::new();
+        let context = GuitarContext {
+            palm_mute_density: 0.0,
+            riff_contour: Vec::new(),
+            interval_stress: Vec::new(),
+            bar_accents: Vec::new(),
+            accent_steps: Vec::new(),
+            note_count: 0,
+        };
+        // Four bars at 120 BPM: each bar hits the snare more often than the one before
+        let DrumGrid { kick, snare, .. } = renderer.generate_drum_patterns(
+            MetalSection::PreChorus, 8.0, 120, MetalSubgenre::HeavyMetal, RhythmicFeel::Normal, &context,
+        );
+        let per_bar: Vec<usize> = snare.chunks(16).map(|bar| bar.iter().filter(|&&hit| hit).count()).collect();
+        assert_eq!(per_bar.len(), 4);
+        assert!(per_bar.windows(2).skip(1).all(|pair| pair[1] > pair[0]), "{:?}", per_bar);
+        assert!(snare[48..].iter().all(|&hit| hit));
+        assert!(kick[48..].iter().all(|&hit| hit));
+
+        // The riser swells toward the chorus
+        let riser = generate_riser(1.0);
+        let energy = |part: &[f32]| part.iter().map(|s| s * s).sum::<f32>();
+        assert!(energy(&riser[riser.len() * 3 / 4..]) > energy(&riser[..riser.len() / 4]) * 4.0);
+    }
+
+    #[test]
+    fn test_rhythmic_feel_sets_snare_density() {
+        let renderer = MetalAudioRenderer::new();
+        let context = GuitarContext {
+            palm_mute_density: 0.0,
+            riff_contour: Vec::new(),
+            interval_stress: Vec::new(),
+            bar_accents: Vec::new(),
+            accent_steps: Vec::new(),
+            note_count: 0,
+        };
+        let snares = |feel| {
+            let snare = renderer.generate_drum_patterns(
+                MetalSection::Verse, 8.0, 120, MetalSubgenre::HeavyMetal, feel, &context,
+            ).snare;
+            snare.iter().filter(|&&hit| hit).count()
+        };
+
+        // Four bars at 120 BPM
+        let normal = snares(RhythmicFeel::Normal);
+        assert_eq!(normal, 8);
+        assert_eq!(snares(RhythmicFeel::HalfTime) * 2, normal);
+        assert_eq!(snares(RhythmicFeel::DoubleTime), normal * 2);
+        assert!(RhythmicFeel::HalfTime.is_backbeat(8) && !RhythmicFeel::HalfTime.is_backbeat(4));
+    }
+
+    #[test]
+    fn test_blast_accents_reach_the_grid() {
+        crate::utils::seed_rng(3);
+        let mut renderer = MetalAudioRenderer::new();
+        let context = GuitarContext::default();
+        let blast = |renderer: &MetalAudioRenderer| {
+            renderer.generate_drum_patterns(MetalSection::Verse, 16.0, 120, MetalSubgenre::DeathMetal, RhythmicFeel::Blast, &context)
+        };
+
+        // Without variation every bar accents its downbeat only
+        renderer.set_blast_variation(Some(0.0));
+        let steady = blast(&renderer);
+        assert_eq!(steady.hit_steps(&steady.accent), (0..8).map(|bar| bar * 16).collect::<Vec<_>>());
+
+        // Full variation shifts the accents onto the offbeat strokes
+        renderer.set_blast_variation(Some(1.0));
+        let varied = blast(&renderer);
+        let accents = varied.hit_steps(&varied.accent);
+        assert!(accents.iter().any(|step| step % 16 != 0));
+        assert!(accents.iter().all(|&step| RhythmicFeel::Blast.is_backbeat(step)));
+        assert_eq!(MetalSection::Verse.rhythmic_feel_for(MetalSubgenre::DeathMetal), RhythmicFeel::Blast);
+    }
+
+    #[test]
+    fn test_high_chord_tones_are_dropped_not_wrapped() {
+        // 123 + 7 and 123 + 12 would pass MIDI 127 (and wrap past 255 from higher notes)
+        let voices = MetalAudioRenderer::chord_voices(123, ChordType::Power);
+        assert_eq!(voices.iter().map(|v| v.0).collect::<Vec<_>>(), vec![123]);
+        assert_eq!(MetalAudioRenderer::chord_voices(250, ChordType::Minor).len(), 0);
+        assert_eq!(MetalAudioRenderer::chord_voices(40, ChordType::Power).len(), 3);
+
+        // Inversions keep the triad's pitch classes with the third or fifth lowest
+        let tones = |chord_type| MetalAudioRenderer::chord_voices(40, chord_type).iter().map(|v| v.0).collect::<Vec<_>>();
+        assert_eq!(tones(ChordType::MinorInversion(Inversion::First)), vec![43, 47, 52]);
+        assert_eq!(tones(ChordType::MinorInversion(Inversion::Second)), vec![47, 52, 55]);
+
+        let mut renderer = MetalAudioRenderer::new();
+        for (tone, _, _) in MetalAudioRenderer::chord_voices(120, ChordType::Minor) {
+            assert!(renderer.take_frequency(tone) >= midi_to_freq(120) - 0.01);
+        }
+
+        let riff = MetalRiff {
+            chord_types: vec![ChordType::Power, ChordType::Minor],
+            palm_muted: vec![false; 2],
+            ..test_riff(vec![123, 123], vec![RhythmPattern::EighthNote, RhythmPattern::Gallop])
+        };
+        let audio = renderer.render_guitar_riff(&riff, 0.5);
+        assert!(!audio.is_empty() && audio.iter().all(|s| s.is_finite()));
+    }
+
+    /// Fundamental of a steady tone: the shortest lag (40-400 Hz) whose autocorrelation
+    /// comes close to the strongest one, so octave multiples of the period don't win
+    fn estimate_pitch(samples: &[f32], sample_rate: u32) -> f32 {
+        let correlation = |lag: usize| -> f32 { samples.iter().zip(&samples[lag..]).map(|(a, b)| a * b).sum() };
+        let lags = (sample_rate / 400) as usize..=(sample_rate / 40) as usize;
+        let scores: Vec<(usize, f32)> = lags.map(|lag| (lag, correlation(lag))).collect();
+        let best = scores.iter().map(|&(_, c)| c).fold(f32::MIN, f32::max);
+        let period = scores.windows(3)
+            .find(|w| w[1].1 >= 0.9 * best && w[1].1 >= w[0].1 && w[1].1 >= w[2].1)
+            .map(|w| w[1].0)
+            .unwrap();
+        sample_rate as f32 / period as f32
+    }
+
+    #[test]
+    fn test_pitch_follows_pathfinder_position() {
+        use crate::composition::fretboard::FretPosition;
+
+        let mut renderer = MetalAudioRenderer::new();
+        renderer.set_tuning(GuitarTuning::EStandard);
+        renderer.dsp_chain.set_distortion_bypass(true);
+        // E3 in the abstract, but fretted as the open low E (E2)
+        let riff = MetalRiff {
+            chord_types: vec![ChordType::Single],
+            palm_muted: vec![false],
+            fret_positions: vec![FretPosition::new(0, 0)],
+            clean: true,
+            ..test_riff(vec![52], vec![RhythmPattern::QuarterNote])
+        };
+
+        let window = |audio: &[f32]| audio[audio.len() / 8..audio.len() / 2].to_vec();
+        let fretted = estimate_pitch(&window(&renderer.render_guitar_riff(&riff, 0.5)), renderer.sample_rate);
+        assert!((fretted - midi_to_freq(40)).abs() < 3.0, "fretted pitch {} Hz", fretted);
+
+        // Without a position per note the abstract note is played
+        let unfretted = MetalRiff { fret_positions: Vec::new(), ..riff };
+        let abstract_pitch = estimate_pitch(&window(&renderer.render_guitar_riff(&unfretted, 0.5)), renderer.sample_rate);
+        assert!((abstract_pitch - midi_to_freq(52)).abs() < 5.0, "abstract pitch {} Hz", abstract_pitch);
+    }
+
+    #[test]
+    fn test_tuplet_groups_fill_four_beats() {
+        let mut renderer = MetalAudioRenderer::new();
+        renderer.section_swing = 0.5; // Tuplets ignore the shuffle
+        let beat_duration = 0.25; // 240 BPM
+        let four_beats = (4.0 * beat_duration * renderer.sample_rate as f32) as usize;
+
+        for rhythm in [RhythmPattern::Quintuplet, RhythmPattern::Septuplet] {
+            let count = rhythm.tuplet_size().unwrap();
+            assert!((rhythm.to_beats() * count as f32 - TUPLET_SPAN_BEATS).abs() < 1e-5);
+
+            let riff = MetalRiff {
+                chord_types: vec![ChordType::Single; count],
+                ..test_riff(vec![40; count], vec![rhythm; count])
+            };
+            let onsets = riff.onset_beats();
+            assert!((onsets[count - 1].1 + rhythm.to_beats() - 4.0).abs() < 1e-5);
+
+            // Five quintuplets (or seven septuplets) take four beats of audio, give or take rounding
+            let samples = renderer.guitar_riff_samples(&riff, beat_duration);
+            assert!(samples.abs_diff(four_beats) <= count, "{:?}: {} vs {}", rhythm, samples, four_beats);
+            let audio = renderer.render_guitar_riff(&riff, beat_duration);
+            assert!(audio.len().abs_diff(four_beats) <= count + renderer.sample_rate as usize / 50);
+        }
+    }
+
+    #[test]
+    fn test_bass_picks_gallops_with_the_guitar() {
+        let mut renderer = MetalAudioRenderer::new();
+        let riff = test_riff(
+            vec![40, 40, 43, 40, 45],
+            vec![
+                RhythmPattern::Gallop,
+                RhythmPattern::Gallop,
+                RhythmPattern::SixteenthNote,
+                RhythmPattern::Gallop,
+                RhythmPattern::SixteenthNote,
+            ],
+        );
+        let sixteenth = 0.125;
+
+        // One bass onset per guitar pick, on the same 16th-grid positions
+        let bass = MetalAudioRenderer::follow_bass_notes(&riff, sixteenth);
+        let guitar_onsets = riff.onset_beats();
+        assert_eq!(bass.len(), guitar_onsets.len());
+        let mut position = 0.0;
+        for (&(note, duration), &(i, beats)) in bass.iter().zip(&guitar_onsets) {
+            assert_eq!(note, riff.notes[i]);
+            assert!((position - beats * 4.0 * sixteenth).abs() < 1e-4, "bass at {} vs guitar beat {}", position, beats);
+            position += duration;
+        }
+
+        // Each gallop fills its beat in the rendered bass line
+        let audio = renderer.render_bass_riff_locked(&riff.notes, &[], sixteenth, BassMode::Follow, &riff, BassVoice::Picked);
+        let expected = ((3 * 4 + 2) as f32 * sixteenth * renderer.sample_rate as f32) as usize;
+        assert!(audio.len().abs_diff(expected) <= bass.len(), "{} vs {} samples", audio.len(), expected);
+    }
+
+    #[test]
+    fn test_double_take_is_late_and_back_in_tune() {
+        let mut renderer = MetalAudioRenderer::new();
+        let riff = test_riff(vec![40, 40, 43, 40], vec![RhythmPattern::EighthNote; 4]);
+
+        let first = renderer.render_guitar_riff(&riff, 0.5);
+        let second = renderer.render_double_take(&riff, 0.5);
+
+        // Lands 1-12 samples behind the first take
+        let offset = second.len() - first.len();
+        assert!((1..=DOUBLE_TRACK_MAX_OFFSET).contains(&offset));
+        assert!(second[..offset].iter().all(|&s| s == 0.0));
+
+        // The detune only applies while the second take renders
+        assert_eq!(renderer.take_detune, 1.0);
+        assert!((renderer.take_frequency(69) - 440.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_humanized_guitar_keeps_the_grid() {
+        let mut renderer = MetalAudioRenderer::new();
+        let riff = test_riff(vec![40; 8], vec![RhythmPattern::EighthNote; 8]);
+
+        crate::utils::seed_rng(7);
+        let quantized = renderer.render_guitar_riff(&riff, 0.5);
+        crate::utils::seed_rng(7);
+        renderer.section_guitar_humanizer = GuitarHumanizer::loose();
+        let humanized = renderer.render_guitar_riff(&riff, 0.5);
+
+        // Same riff length (notes move around their slots, not the slots themselves)
+        let tail = (0.02 * renderer.sample_rate as f32) as usize;
+        assert!(humanized.len().abs_diff(quantized.len()) <= tail);
+        assert!(humanized.iter().zip(&quantized).any(|(a, b)| (a - b).abs() > 1e-3));
+    }
+
+    #[test]
+    fn test_repeated_sections_reuse_guitar_render() {
+        let mut renderer = MetalAudioRenderer::new();
+        let riff = test_riff(vec![40, 40, 43, 40], vec![RhythmPattern::EighthNote; 4]);
+        let humanizer = DrumHumanizer::new();
+        let subgenre = MetalSubgenre::HeavyMetal;
+
+        renderer.render_section(MetalSection::Chorus, &riff, 2.0, 120, subgenre, &humanizer);
+        renderer.render_section(MetalSection::Chorus, &riff, 2.0, 120, subgenre, &humanizer);
+        assert_eq!(renderer.guitar_cache.len(), 1);
+
+        // A different drive (verse intensity) or tempo is a different track
+        renderer.render_section(MetalSection::Verse, &riff, 2.0, 120, subgenre, &humanizer);
+        renderer.render_section(MetalSection::Chorus, &riff, 2.0, 140, subgenre, &humanizer);
+        assert_eq!(renderer.guitar_cache.len(), 3);
+
+        // Changing the amp throws the cached tracks away
+        renderer.set_noise_gate(NoiseGate::metal());
+        assert!(renderer.guitar_cache.is_empty());
+    }
+
+    #[test]
+    fn test_chorus_rides_eighths_while_verse_stays_sparser() {
+        let mut renderer = MetalAudioRenderer::new();
+        // Open, ringing chords: no chugs for the kick to lock to
+        let riff = MetalRiff {
+            palm_muted: vec![false; 4],
+            ..test_riff(vec![40; 4], vec![RhythmPattern::QuarterNote; 4])
+        };
+        let context = GuitarContext::from_riff(&riff);
+        let cymbals = |renderer: &MetalAudioRenderer, section: MetalSection| {
+            let cymbal = renderer.generate_drum_patterns(
+                section, 2.0, 120, MetalSubgenre::HeavyMetal, RhythmicFeel::Normal, &context,
+            ).cymbal;
+            cymbal[..16].iter().filter(|&&hit| hit).count()
+        };
+
+        assert_eq!(cymbals(&renderer, MetalSection::Chorus), 8);
+        assert_eq!(cymbals(&renderer, MetalSection::Verse), 4);
+        assert_eq!(cymbals(&renderer, MetalSection::Intro), 1);
+        assert_eq!(CymbalStyle::for_section(MetalSection::Chorus).time_keeper, TimeKeeper::Ride);
+
+        // An explicit style applies to every section
+        renderer.set_cymbal_style(Some(CymbalStyle::new(CymbalDensity::Eighth, TimeKeeper::ClosedHiHat)));
+        assert_eq!(cymbals(&renderer, MetalSection::Verse), 8);
+    }
+
+    #[test]
+    fn test_death_metal_verses_roll_double_bass() {
+        let mut renderer = MetalAudioRenderer::new();
+        let context = GuitarContext::from_riff(&MetalRiff {
+            palm_muted: vec![false; 4],
+            ..test_riff(vec![40; 4], vec![RhythmPattern::QuarterNote; 4])
+        });
+        let kicks = |renderer: &MetalAudioRenderer, subgenre: MetalSubgenre, section: MetalSection| {
+            let kick = renderer.generate_drum_patterns(section, 2.0, 120, subgenre, RhythmicFeel::Normal, &context).kick;
+            kick[..16].iter().filter(|&&hit| hit).count()
+        };
+
+        // One breath before the next downbeat, feet keep going under the backbeat
+        assert_eq!(kicks(&renderer, MetalSubgenre::DeathMetal, MetalSection::Verse), 15);
+        assert_eq!(kicks(&renderer, MetalSubgenre::ThrashMetal, MetalSection::Verse), 14);
+        assert!(kicks(&renderer, MetalSubgenre::HeavyMetal, MetalSection::Verse) < 8);
+        assert!(kicks(&renderer, MetalSubgenre::DeathMetal, MetalSection::Chorus) < 8);
+
+        renderer.set_double_bass(false);
+        assert!(kicks(&renderer, MetalSubgenre::DeathMetal, MetalSection::Verse) < 8);
+
+        let pattern = rhythm_generator::generate_double_bass_pattern(32, 2);
+        let rests: Vec<usize> = (0..32).filter(|&i| !pattern[i]).collect();
+        assert_eq!(rests, vec![7, 15, 23, 31]);
+    }
+
+    #[test]
+    fn test_swing_delays_offbeat_sixteenths() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+
+        // Straight grid is untouched
+        for step in 0..16 {
+            let beats = step as f32 * 0.25;
+            assert!(close(MetalAudioRenderer::swing_position(beats, 0.0), beats));
+        }
+
+        // Full swing: the offbeat 16th lands on the last triplet of its eighth
+        assert!(close(MetalAudioRenderer::swing_position(0.25, 1.0), 1.0 / 3.0));
+        assert!(close(MetalAudioRenderer::swing_position(1.75, 1.0), 1.5 + 1.0 / 3.0));
+        // Eighth notes and beats never move
+        assert!(close(MetalAudioRenderer::swing_position(0.5, 1.0), 0.5));
+        assert!(close(MetalAudioRenderer::swing_position(3.0, 0.6), 3.0));
+
+        assert!(MetalSubgenre::DoomMetal.default_swing() > 0.0);
+        assert_eq!(MetalSubgenre::ThrashMetal.default_swing(), 0.0);
+        assert_eq!(MetalSubgenre::DeathMetal.default_swing(), 0.0);
+
+        // The bass ducks and sub-drops on the same swung grid the drums use
+        let mut renderer = MetalAudioRenderer::new();
+        renderer.section_swing = 1.0;
+        let beat_samples = renderer.sample_rate as f32;
+        let starts = renderer.swung_step_starts(4, 1.0);
+        assert_eq!(starts[0], 0);
+        assert!(starts[1].abs_diff((beat_samples / 3.0) as usize) <= 1);
+        assert!(starts[2].abs_diff((beat_samples / 2.0) as usize) <= 1);
+    }
+
+    #[test]
+    fn test_eight_string_bass_stays_audible() {
+        let mut renderer = MetalAudioRenderer::new();
+        renderer.set_tuning(GuitarTuning::DropE8);
+        // Unison on the 8-string: an octave down would be ~20 Hz
+        assert_eq!(renderer.bass_offset(), 0);
+
+        for offset in [renderer.bass_offset(), -12, -24] {
+            for &guitar_note in &GuitarTuning::DropE8.string_notes() {
+                let bass = MetalAudioRenderer::bass_note(guitar_note, offset);
+                assert!(bass >= BASS_FLOOR);
+                assert!(midi_to_freq(bass) >= 30.0, "{} -> {:.1} Hz", guitar_note, midi_to_freq(bass));
+                assert_eq!(bass % 12, guitar_note % 12, "folding keeps the pitch class");
+            }
+        }
+
+        // Standard tuning still doubles an octave down
+        renderer.set_tuning(GuitarTuning::EStandard);
+        assert_eq!(MetalAudioRenderer::bass_note(40, renderer.bass_offset()), 28);
+
+        // An explicit relation replaces the subgenre default; a raw offset beats both
+        renderer.set_bass_relation(Some(BassPitchRelation::Fifth));
+        assert_eq!(MetalAudioRenderer::bass_note(40, renderer.bass_offset()), 35);
+        renderer.set_bass_octave_offset(Some(0));
+        assert_eq!(renderer.bass_offset(), 0);
+    }
+
+    #[test]
+    fn test_click_ticks_on_every_beat() {
+        let renderer = MetalAudioRenderer::new();
+        let sample_rate = get_sample_rate() as usize;
+        let beat = sample_rate / 2; // 120 BPM
+        let energy = |audio: &[f32], start: usize| audio[start..start + 200].iter().map(|s| s.abs()).sum::<f32>();
+
+        let click = renderer.render_click_section(MetalSection::Verse, 120, sample_rate * 4);
+        assert_eq!(click.len(), sample_rate * 4);
+        for n in 0..8 {
+            assert!(energy(&click, n * beat) > 1.0, "no tick on beat {}", n);
+            assert!(energy(&click, n * beat + beat / 2) == 0.0, "stray tick after beat {}", n);
+        }
+        // Bar downbeats are accented
+        assert!(energy(&click, 0) > energy(&click, beat) * 1.2);
+        assert!(energy(&click, 4 * beat) > energy(&click, 5 * beat) * 1.2);
+
+        // Breakdowns count in after the drop
+        let lead_in = renderer.section_lead_in(MetalSection::Breakdown);
+        let breakdown = renderer.render_click_section(MetalSection::Breakdown, 120, lead_in + sample_rate);
+        assert!(breakdown[..lead_in].iter().all(|&s| s == 0.0));
+        assert!(energy(&breakdown, lead_in) > 1.0);
+    }
+
+    #[test]
+    fn test_lead_in_counts_the_song_in() {
+        let mut renderer = MetalAudioRenderer::new();
+        let sample_rate = get_sample_rate() as usize;
+        let beat = sample_rate / 2; // 120 BPM
+        let energy = |audio: &[f32], start: usize| audio[start..start + 200].iter().map(|s| s.abs()).sum::<f32>();
+
+        // Off by default: the song is left untouched
+        let mut song = vec![1.0; 10];
+        assert_eq!(renderer.prepend_lead_in(&mut song, 120), 0);
+        assert_eq!(song, vec![1.0; 10]);
+
+        // Half a second of silence, then a click on each of four beats
+        renderer.set_lead_in(0.5, CountIn::Clicks);
+        let silence = sample_rate / 2;
+        assert_eq!(renderer.prepend_lead_in(&mut song, 120), silence + 4 * beat);
+        assert!(song[..silence].iter().all(|&s| s == 0.0));
+        for n in 0..4 {
+            assert!(energy(&song, silence + n * beat) > 1.0, "no count-in click on beat {}", n);
+        }
+        assert!(energy(&song, silence) > energy(&song, silence + beat) * 1.2);
+        assert_eq!(song[song.len() - 10..], [1.0; 10]);
+
+        // The crash swell builds to the first downbeat
+        renderer.set_lead_in(0.0, CountIn::Crash);
+        let swell = renderer.render_lead_in(120);
+        assert_eq!(swell.len(), 4 * beat);
+        assert!(energy(&swell, swell.len() - 200) > energy(&swell, beat) * 4.0);
+
+        // Markers start after the lead-in
+        let stats = RenderStats::measure(&vec![0.0; 3_000], &[2_000], 1000).with_lead_in(1_000, 1000);
+        let timeline = stats.timeline(&[MetalSection::Intro], 120);
+        assert_eq!((timeline[0].start_s, timeline[0].end_s, timeline[0].start_bar), (1.0, 3.0, 1.5));
+    }
+
+    #[test]
+    fn test_render_song_returns_aligned_stems() {
+        crate::utils::seed_rng(2);
+        let song = MetalSongGenerator::new(MetalSubgenre::HeavyMetal).generate_song();
+        let mut renderer = MetalAudioRenderer::new();
+        renderer.set_lead_in(0.25, CountIn::Clicks);
+
+        let reports = std::cell::Cell::new(0);
+        let progress = |_: f32, _: &str| reports.set(reports.get() + 1);
+        let rendered = renderer.render_song(&song, &SongRenderOptions {
+            section_seconds: Some(0.5),
+            progress: Some(&progress),
+            click: true,
+            stems: true,
+        });
+
+        assert_eq!(reports.get(), song.sections.len() + 1);
+        assert_eq!(rendered.stats.section_durations.len(), song.sections.len());
+        assert!((rendered.stats.duration_seconds * renderer.sample_rate as f32 - rendered.audio.len() as f32).abs() < 1.0);
+        assert!(rendered.audio.iter().all(|s| s.abs() <= 1.0));
+        let stems = rendered.stems.unwrap();
+        for (_, stem) in stems.tracks() {
+            assert_eq!(stem.len(), rendered.audio.len());
+        }
+        // Stems are opt-in
+        let plain = renderer.render_song(&song, &SongRenderOptions { section_seconds: Some(0.5), ..Default::default() });
+        assert!(plain.stems.is_none());
+    }
+
+    #[test]
+    fn test_concurrent_renders_at_their_own_rates() {
+        let global = get_sample_rate();
+        let riff = MetalSongGenerator::new(MetalSubgenre::HeavyMetal).generate_riff(MetalSection::Verse);
+        let render_at = |rate: u32| {
+            let riff = riff.clone();
+            std::thread::spawn(move || {
+                crate::utils::seed_rng(3);
+                let mut renderer = MetalAudioRenderer::with_context(RenderContext::new(rate));
+                let audio = renderer.render_section(
+                    MetalSection::Verse, &riff, 1.0, 120, MetalSubgenre::HeavyMetal, &DrumHumanizer::new(),
+                );
+                // The thread's own rate is untouched outside the render
+                assert_eq!(get_sample_rate(), global);
+                audio.len() as f32 / rate as f32
+            })
+        };
+
+        // Same material, same length in seconds at either rate
+        let (low, high) = (render_at(22050), render_at(48000));
+        let (low, high) = (low.join().unwrap(), high.join().unwrap());
+        assert!(low >= 1.0 && (low - high).abs() < 0.05, "{}s vs {}s", low, high);
+        assert_eq!(RenderContext::default().sample_rate, global);
+    }
+
+    #[test]
+    fn test_section_length_follows_tempo_math() {
+        let sample_rate = get_sample_rate() as f32;
+        // Tempos whose riffs end before, near and after the section boundary
+        let cases = [
+            (MetalSubgenre::HeavyMetal, MetalSection::Verse, 137, 1.37),
+            (MetalSubgenre::ThrashMetal, MetalSection::Chorus, 212, 1.1),
+            (MetalSubgenre::DeathMetal, MetalSection::Breakdown, 95, 1.25),
+            (MetalSubgenre::DoomMetal, MetalSection::Solo, 62, 0.9),
+            (MetalSubgenre::ProgressiveMetal, MetalSection::Intro, 173, 1.613),
+        ];
+        for (subgenre, section, tempo, duration) in cases {
+            crate::utils::seed_rng(tempo as u64);
+            let riff = MetalSongGenerator::new(subgenre).with_tempo(tempo).generate_riff(section);
+            let mut renderer = MetalAudioRenderer::new();
+            let audio = renderer.render_section(section, &riff, duration, tempo, subgenre, &DrumHumanizer::new());
+
+            let expected = (duration * sample_rate).round() as usize + renderer.section_lead_in(section);
+            assert_eq!(audio.len(), expected, "{:?} {:?} at {} BPM", subgenre, section, tempo);
+            assert_eq!(renderer.section_length(section, duration), expected);
+        }
+    }
+
+    #[test]
+    fn test_stems_line_up_with_the_mix() {
+        let riff = MetalSongGenerator::new(MetalSubgenre::DeathMetal).generate_riff(MetalSection::Breakdown);
+        let mut renderer = MetalAudioRenderer::new();
+        let render = |renderer: &mut MetalAudioRenderer| {
+            renderer.render_section(MetalSection::Breakdown, &riff, 1.0, 120, MetalSubgenre::DeathMetal, &DrumHumanizer::new())
+        };
+
+        render(&mut renderer);
+        assert!(renderer.take_section_stems().is_none(), "stems are only kept on request");
+
+        renderer.set_capture_stems(true);
+        let mix = render(&mut renderer);
+        let stems = renderer.take_section_stems().unwrap();
+        for (name, track) in stems.tracks() {
+            assert_eq!(track.len(), mix.len(), "{} stem length", name);
+            assert!(track.iter().any(|s| s.abs() > 0.01), "{} stem is silent", name);
+        }
+
+        // The drop kick before the grid is on the drum stem only
+        let lead_in = renderer.section_lead_in(MetalSection::Breakdown);
+        assert_eq!(stems.drums[..lead_in], mix[..lead_in]);
+        assert!(stems.guitar[..lead_in].iter().chain(&stems.bass[..lead_in]).all(|&s| s == 0.0));
+
+        // Appended stems crossfade like the mix
+        let mut song = Stems::default();
+        song.append_crossfaded(&stems, renderer.sample_rate);
+        song.append_crossfaded(&stems, renderer.sample_rate);
+        let mut song_mix = Vec::new();
+        MetalAudioRenderer::append_crossfaded(&mut song_mix, &mix, renderer.sample_rate);
+        MetalAudioRenderer::append_crossfaded(&mut song_mix, &mix, renderer.sample_rate);
+        assert_eq!(song.drums.len(), song_mix.len());
+    }
+
+    #[test]
+    fn test_section_ambience_keeps_rhythm_parts_dry() {
+        let ambience = MetalAudioRenderer::section_ambience;
+        assert!(ambience(MetalSection::Breakdown) < ambience(MetalSection::Chorus));
+        assert!(ambience(MetalSection::Verse) < ambience(MetalSection::Chorus));
+        for wet in [MetalSection::Intro, MetalSection::Outro, MetalSection::Solo] {
+            assert!(ambience(wet) > ambience(MetalSection::Chorus));
+        }
+
+        let send = ReverbSend::default().scaled(ambience(MetalSection::Outro));
+        assert!(send.drums > ReverbSend::default().drums);
+        assert_eq!(send.bass, 0.0);
+        assert_eq!(ReverbSend::new(0.8, 0.8, 0.8, 0.8).scaled(3.0), ReverbSend::new(1.0, 1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_sections_join_without_clicks() {
+        let mut audio = vec![0.8f32; 4000];
+        let first_len = audio.len();
+        let added = MetalAudioRenderer::append_crossfaded(&mut audio, &[-0.8f32; 4000], 44100);
+
+        assert_eq!(audio.len(), first_len + added);
+        let max_delta = audio.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0f32, f32::max);
+        assert!(max_delta < 0.05, "boundary jump {}", max_delta);
+        assert_eq!(audio[audio.len() - 1], -0.8);
+
+        // Appending to an empty buffer is a plain copy
+        let mut empty = Vec::new();
+        assert_eq!(MetalAudioRenderer::append_crossfaded(&mut empty, &[0.5; 10], 44100), 10);
+        assert_eq!(empty, vec![0.5; 10]);
+    }
+
+    #[test]
+    fn test_render_stats_measure_sections_and_levels() {
+        let sample_rate = 1000;
+        let mut samples = vec![0.5f32; 1500];
+        samples.extend(vec![-0.5f32; 500]);
+        samples[10] = 1.2;
+
+        let stats = RenderStats::measure(&samples, &[1500, 500], sample_rate);
+        assert_eq!(stats.duration_seconds, 2.0);
+        assert_eq!(stats.section_durations, vec![1.5, 0.5]);
+        assert_eq!(stats.peak, 1.2);
+        assert!(stats.clip_count >= 1);
+        assert!((stats.rms - 0.5).abs() < 0.01);
+        assert!(stats.peak_dbfs() > 0.0 && stats.rms_dbfs() < 0.0);
+
+        let silent = RenderStats::measure(&[], &[], sample_rate);
+        assert_eq!((silent.peak, silent.rms, silent.clip_count), (0.0, 0.0, 0));
+    }
+
+    #[test]
+    fn test_timeline_marks_section_boundaries() {
+        // Two bars of intro then four bars of verse at 120 BPM (2 s per bar)
+        let stats = RenderStats::measure(&vec![0.0; 12_000], &[4_000, 8_000], 1000);
+        let timeline = stats.timeline(&[MetalSection::Intro, MetalSection::Verse], 120);
+
+        assert_eq!(timeline.len(), 2);
+        assert_eq!((timeline[0].start_s, timeline[0].end_s, timeline[0].bars), (0.0, 4.0, 2.0));
+        assert_eq!(timeline[1].section, MetalSection::Verse);
+        assert_eq!((timeline[1].start_s, timeline[1].end_s), (4.0, 12.0));
+        assert_eq!((timeline[1].start_bar, timeline[1].bars), (3.0, 4.0));
+
+        let json = serde_json::to_value(&timeline).unwrap();
+        assert_eq!(json[1]["section"], "Verse");
+        assert_eq!(json[1]["start_s"], 4.0);
+    }
+
+    #[test]
+    fn test_sections_open_with_a_crash() {
+        let crash = MetalAudioRenderer::section_crash;
+        assert_eq!(crash(MetalSection::Intro), None);
+        assert_eq!(crash(MetalSection::Breakdown), Some((true, 1.0)));
+        let (china, chorus_gain) = crash(MetalSection::Chorus).unwrap();
+        assert!(!china);
+        assert!(chorus_gain > crash(MetalSection::Verse).unwrap().1);
+
+        // With nothing else on the grid, a verse's drums open on the crash at sample 0
+        // while an intro stays silent until its first possible ghost note on step 3
+        let renderer = MetalAudioRenderer::new();
+        let empty = DrumGrid { kick: vec![false; 16], snare: vec![false; 16], cymbal: vec![false; 16], accent: vec![false; 16] };
+        let on_grid = DrumHumanizer { timing_variance: 0, ..DrumHumanizer::new() };
+        let render = |section| renderer.render_drums(section, &empty, 1.0, 120, MetalSubgenre::HeavyMetal, &on_grid);
+        let verse = render(MetalSection::Verse);
+        let onset = verse.iter().position(|s| s.abs() > 1e-4).unwrap();
+        assert!(onset < 4, "crash starts at sample {onset}");
+        let step = get_sample_rate() as usize / 8;
+        assert!(verse[..step / 10].iter().any(|s| s.abs() > 0.05));
+        assert!(render(MetalSection::Intro)[..2 * step].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_breakdown_sub_drop_lands_on_each_bar() {
+        assert!(MetalAudioRenderer::sub_drop_for_subgenre(MetalSubgenre::DeathMetal));
+        assert!(!MetalAudioRenderer::sub_drop_for_subgenre(MetalSubgenre::HeavyMetal));
+
+        // Kicks on steps 2 and 6 of the first bar, step 0 of the second
+        let mut kicks = vec![false; 32];
+        kicks[2] = true;
+        kicks[6] = true;
+        kicks[16] = true;
+        let step = 1000;
+        let step_starts: Vec<usize> = (0..kicks.len()).map(|i| i * step).collect();
+        let mut bass = vec![0.0f32; 32 * step];
+        MetalAudioRenderer::add_sub_drops(&mut bass, &kicks, &step_starts, 40, 0.5, &PitchStandard::default());
+
+        // Silent until the first kick of each bar, then the drop rings
+        let loudness = |range: std::ops::Range<usize>| bass[range].iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert_eq!(loudness(0..2 * step), 0.0);
+        assert!(loudness(2 * step..4 * step) > 0.3);
+        assert!(loudness(16 * step..18 * step) > 0.3);
+
+        // An octave below the bass root (E2 -> E1), and the track grows to fit the last tail
+        let drop_len = (0.5 * get_sample_rate() as f32) as usize;
+        assert_eq!(bass.len(), (16 * step + drop_len).max(32 * step));
+        let tail = &bass[2 * step + drop_len / 4..2 * step + drop_len / 2];
+        let crossings = tail.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count() as f32;
+        let frequency = crossings * get_sample_rate() as f32 / tail.len() as f32;
+        assert!((frequency - midi_to_freq(28)).abs() < 5.0, "sub at {} Hz", frequency);
+    }
+
+    #[test]
+    fn test_flam_renders_two_close_snare_onsets() {
+        let renderer = MetalAudioRenderer::new();
+        let sample_idx = 10_000;
+
+        let onsets = renderer.snare_onsets(Articulation::Flam, sample_idx, 0.8);
+        assert_eq!(onsets.len(), 2);
+
+        // Gap scales with sample rate: ~220 samples at 44.1 kHz
+        let gap = onsets[1].0 - onsets[0].0;
+        let max_gap = 300 * renderer.sample_rate as usize / 44_100;
+        assert!(gap > 0 && gap <= max_gap, "flam gap {} samples", gap);
+
+        // Grace stroke is softer and the main stroke stays on the grid
+        assert!(onsets[0].1 < onsets[1].1);
+        assert_eq!(onsets[1].0, sample_idx);
+    }
+}
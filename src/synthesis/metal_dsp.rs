@@ -1,3 +1,5 @@
+use crate::composition::metal_song_generator::MetalSubgenre;
+use crate::synthesis::mixing::Compressor;
 use crate::utils::get_sample_rate;
 
 /// Advanced distortion with tube-style waveshaping and oversampling
@@ -44,6 +46,13 @@ impl TubeDistortion {
         }
     }
 
+    /// Change the drive and re-derive the output compensation so louder
+    /// settings don't push the stage into clipping
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive.max(1.0);
+        self.output_level = 1.0 / (1.0 + self.drive * 0.1);
+    }
+
     /// Process a single sample with tube-style waveshaping
     fn waveshape(&self, input: f32) -> f32 {
         let driven = input * self.drive;
@@ -97,36 +106,85 @@ impl TubeDistortion {
     }
 }
 
+/// Calibrated gate thresholds sit this far above the measured noise floor (~+12 dB)
+const GATE_CALIBRATION_HEADROOM: f32 = 4.0;
+/// Calibration never goes below/above these, so digital silence or a loud
+/// "silent" segment can't disable the gate or swallow quiet notes
+const GATE_MIN_THRESHOLD: f32 = 0.0005;
+const GATE_MAX_THRESHOLD: f32 = 0.1;
+
 /// Noise gate for metal - essential to stop hum between staccato riffs
 #[derive(Debug, Clone)]
 pub struct NoiseGate {
-    pub threshold: f32,       // Amplitude threshold (0.0 - 1.0)
+    pub threshold: f32,       // Open threshold (0.0 - 1.0)
+    pub close_threshold: f32, // Close threshold, below `threshold` (hysteresis)
+    pub hold: f32,            // Seconds the gate stays open after dropping below close threshold
     pub ratio: f32,           // Attenuation ratio (0.0 = full cut, 1.0 = no cut)
     pub attack: f32,          // Attack time in seconds
     pub release: f32,         // Release time in seconds
     envelope: f32,            // Current envelope value
+    open: bool,               // Current gate state
+    hold_remaining: usize,    // Samples left before the gate may close
 }
 
 impl NoiseGate {
     pub fn new(threshold: f32) -> Self {
+        Self::with_hysteresis(threshold, threshold * 0.5, 0.05)
+    }
+
+    /// Gate that opens above `open_threshold`, closes below `close_threshold`,
+    /// and stays open for `hold` seconds after the signal falls away
+    pub fn with_hysteresis(open_threshold: f32, close_threshold: f32, hold: f32) -> Self {
+        let threshold = open_threshold.clamp(0.0, 1.0);
         NoiseGate {
-            threshold: threshold.clamp(0.0, 1.0),
+            threshold,
+            close_threshold: close_threshold.clamp(0.0, threshold),
+            hold: hold.max(0.0),
             ratio: 0.0, // Full cut by default
             attack: 0.001, // 1ms attack
             release: 0.05, // 50ms release
             envelope: 0.0,
+            open: false,
+            hold_remaining: 0,
         }
     }
 
-    /// Metal preset: aggressive gating
+    /// Metal preset: aggressive gating, short hold so chug tails decay naturally
     pub fn metal() -> Self {
         NoiseGate {
-            threshold: 0.02,
-            ratio: 0.0,
             attack: 0.0005,
             release: 0.03,
-            envelope: 0.0,
+            ..Self::with_hysteresis(0.02, 0.012, 0.04)
+        }
+    }
+
+    /// Move the open threshold, keeping the same hysteresis ratio
+    pub fn set_threshold(&mut self, threshold: f32) {
+        let close_ratio = if self.threshold > 0.0 { self.close_threshold / self.threshold } else { 0.5 };
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self.close_threshold = self.threshold * close_ratio;
+    }
+
+    /// Set the threshold just above the noise floor of `silence` (a segment with no
+    /// playing in it): RMS of the segment plus GATE_CALIBRATION_HEADROOM
+    pub fn auto_calibrate(&mut self, silence: &[f32]) {
+        if silence.is_empty() {
+            return;
         }
+        let floor = (silence.iter().map(|s| s * s).sum::<f32>() / silence.len() as f32).sqrt();
+        self.set_threshold((floor * GATE_CALIBRATION_HEADROOM).clamp(GATE_MIN_THRESHOLD, GATE_MAX_THRESHOLD));
+    }
+
+    /// Whether the gate is currently passing signal
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Close the gate and forget the envelope, as if no signal had passed yet
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.open = false;
+        self.hold_remaining = 0;
     }
 
     /// Process a sample
@@ -147,8 +205,24 @@ impl NoiseGate {
             self.envelope = release_coeff * self.envelope + (1.0 - release_coeff) * input_level;
         }
 
-        // Apply gate
+        // Hysteresis + hold: open above threshold, close only after the envelope
+        // has stayed under close_threshold for the hold time
+        let hold_samples = (self.hold * sample_rate) as usize;
         if self.envelope > self.threshold {
+            self.open = true;
+            self.hold_remaining = hold_samples;
+        } else if self.open {
+            if self.envelope >= self.close_threshold {
+                self.hold_remaining = hold_samples;
+            } else if self.hold_remaining > 0 {
+                self.hold_remaining -= 1;
+            } else {
+                self.open = false;
+            }
+        }
+
+        // Apply gate
+        if self.open {
             input // Gate open
         } else {
             input * self.ratio // Gate closed (attenuate)
@@ -189,6 +263,11 @@ impl SimpleHighPass {
         }
     }
 
+    fn reset(&mut self) {
+        self.prev_input = 0.0;
+        self.prev_output = 0.0;
+    }
+
     fn process(&mut self, input: f32) -> f32 {
         let output = self.alpha * (self.prev_output + input - self.prev_input);
         self.prev_input = input;
@@ -217,6 +296,10 @@ impl SimpleLowPass {
         }
     }
 
+    fn reset(&mut self) {
+        self.prev_output = 0.0;
+    }
+
     fn process(&mut self, input: f32) -> f32 {
         let output = self.prev_output + self.alpha * (input - self.prev_output);
         self.prev_output = output;
@@ -274,6 +357,11 @@ impl PreGainEQ {
         }
     }
 
+    /// Clear filter state
+    pub fn reset(&mut self) {
+        self.high_pass.reset();
+    }
+
     pub fn process(&mut self, sample: f32) -> f32 {
         let sample = self.high_pass.process(sample);
         self.mid_boost.process(sample)
@@ -303,6 +391,11 @@ impl PostDistortionEQ {
         }
     }
 
+    /// Clear filter state
+    pub fn reset(&mut self) {
+        self.low_pass.reset();
+    }
+
     pub fn process(&mut self, sample: f32) -> f32 {
         let sample = self.presence.process(sample);
         let sample = self.air.process(sample);
@@ -316,6 +409,87 @@ impl PostDistortionEQ {
     }
 }
 
+// ============================================================================
+// Sidechain Ducking
+// ============================================================================
+
+/// How hard and how long a sidechained track ducks under its trigger
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SidechainSettings {
+    pub amount: f32,  // Gain reduction at the hit (0.0 = none, 1.0 = full cut)
+    pub release: f32, // Recovery time constant in seconds
+}
+
+impl SidechainSettings {
+    pub fn new(amount: f32, release: f32) -> Self {
+        SidechainSettings {
+            amount: amount.clamp(0.0, 1.0),
+            release: release.max(0.001),
+        }
+    }
+
+    /// Barely-there duck for slow, sustained low end (doom)
+    pub fn subtle() -> Self {
+        Self::new(0.2, 0.25)
+    }
+
+    /// Quick duck that clears room for each kick without audible pumping
+    pub fn tight() -> Self {
+        Self::new(0.45, 0.08)
+    }
+
+    /// Deep, audible pump for syncopated modern riffing
+    pub fn pumping() -> Self {
+        Self::new(0.65, 0.14)
+    }
+}
+
+/// Duck `target` on every step of a straight trigger grid (e.g. the kick pattern).
+/// Each `true` step drops the gain by `settings.amount` with a ~1ms attack,
+/// then it recovers exponentially over `settings.release` seconds.
+pub fn sidechain_compress(target: &mut [f32], trigger: &[bool], step_samples: usize, settings: SidechainSettings) {
+    if step_samples == 0 {
+        return;
+    }
+    let hits: Vec<usize> = trigger
+        .iter()
+        .enumerate()
+        .filter(|&(_, &hit)| hit)
+        .map(|(step, _)| step * step_samples)
+        .collect();
+    sidechain_compress_at(target, &hits, settings);
+}
+
+/// Duck `target` at each sample offset in `hits` (ascending), for triggers off a
+/// straight grid such as swung kicks
+pub fn sidechain_compress_at(target: &mut [f32], hits: &[usize], settings: SidechainSettings) {
+    if settings.amount <= 0.0 {
+        return;
+    }
+
+    let sample_rate = get_sample_rate() as f32;
+    let release_coeff = (-1.0 / (settings.release * sample_rate)).exp();
+    let attack_coeff = 1.0 - (-1.0 / (0.001 * sample_rate)).exp();
+
+    let mut key = 0.0f32; // Instant trigger level, decays after each hit
+    let mut envelope = 0.0f32; // Smoothed duck depth applied to the target
+    let mut next_hit = hits.iter().peekable();
+    for (i, sample) in target.iter_mut().enumerate() {
+        while next_hit.next_if(|&&hit| hit <= i).is_some() {
+            key = 1.0;
+        }
+
+        if key > envelope {
+            envelope += (key - envelope) * attack_coeff; // Ramp in to avoid clicks
+        } else {
+            envelope = key;
+        }
+
+        *sample *= 1.0 - settings.amount * envelope;
+        key *= release_coeff;
+    }
+}
+
 // ============================================================================
 // Unified Metal DSP Chain
 // ============================================================================
@@ -328,6 +502,7 @@ pub struct MetalDSPChain {
     pre_eq: PreGainEQ,
     distortion: TubeDistortion,
     post_eq: PostDistortionEQ,
+    bypass_distortion: bool, // Clean channel: gate and EQ only
 }
 
 impl MetalDSPChain {
@@ -338,6 +513,7 @@ impl MetalDSPChain {
             pre_eq: PreGainEQ::new(),
             distortion: TubeDistortion::new(drive, 1.0),
             post_eq: PostDistortionEQ::new(),
+            bypass_distortion: false,
         }
     }
 
@@ -348,6 +524,7 @@ impl MetalDSPChain {
             pre_eq: PreGainEQ::new(),
             distortion: TubeDistortion::metal(),
             post_eq: PostDistortionEQ::new(),
+            bypass_distortion: false,
         }
     }
 
@@ -358,14 +535,53 @@ impl MetalDSPChain {
             pre_eq: PreGainEQ::new(),
             distortion: TubeDistortion::high_gain(),
             post_eq: PostDistortionEQ::new(),
+            bypass_distortion: false,
         }
     }
 
+    /// Swap in a differently tuned noise gate
+    pub fn set_noise_gate(&mut self, gate: NoiseGate) {
+        self.noise_gate = gate;
+    }
+
+    /// The chain's noise gate (e.g. to `auto_calibrate` it)
+    pub fn noise_gate_mut(&mut self) -> &mut NoiseGate {
+        &mut self.noise_gate
+    }
+
+    /// Switch to the clean channel (distortion skipped) or back to the drive channel
+    pub fn set_distortion_bypass(&mut self, bypass: bool) {
+        self.bypass_distortion = bypass;
+    }
+
+    /// Re-gain the distortion stage (output level is re-compensated)
+    pub fn set_drive(&mut self, drive: f32) {
+        self.distortion.set_drive(drive);
+    }
+
+    /// Current distortion drive
+    pub fn drive(&self) -> f32 {
+        self.distortion.drive
+    }
+
+    /// Whether the chain is on the clean channel
+    pub fn is_distortion_bypassed(&self) -> bool {
+        self.bypass_distortion
+    }
+
+    /// Clear the gate and EQ state so the next riff doesn't inherit the last one's tail
+    /// (the distortion itself is stateless)
+    pub fn reset(&mut self) {
+        self.noise_gate.reset();
+        self.pre_eq.reset();
+        self.post_eq.reset();
+    }
+
     /// Process a single sample through the complete DSP chain
     pub fn process(&mut self, sample: f32) -> f32 {
         let sample = self.noise_gate.process(sample);
         let sample = self.pre_eq.process(sample);
-        let sample = self.distortion.process(sample);
+        let sample = if self.bypass_distortion { sample } else { self.distortion.process(sample) };
         self.post_eq.process(sample)
     }
 
@@ -377,6 +593,77 @@ impl MetalDSPChain {
     }
 }
 
+// ============================================================================
+// Bass Amp
+// ============================================================================
+
+/// Bass amp: overdrive blended with the clean DI (so the low end survives the grit),
+/// a low-pass to keep the fizz out of the guitars' range, and a compressor to even it out
+#[derive(Debug, Clone)]
+pub struct BassAmp {
+    distortion: TubeDistortion,
+    blend: f32, // Share of the overdriven signal (0.0 = clean DI only)
+    low_pass: SimpleLowPass,
+    compressor: Compressor,
+}
+
+impl BassAmp {
+    /// Create a bass amp with the given drive, overdrive blend and low-pass cutoff (Hz)
+    pub fn new(drive: f32, blend: f32, cutoff_hz: f32) -> Self {
+        BassAmp {
+            distortion: TubeDistortion::new(drive, 1.0),
+            blend: blend.clamp(0.0, 1.0),
+            low_pass: SimpleLowPass::new(cutoff_hz),
+            compressor: Compressor::new(-14.0, 3.0, 10.0, 80.0, 2.0),
+        }
+    }
+
+    /// Mostly clean, just a little hair (doom)
+    pub fn subtle() -> Self {
+        Self::new(2.0, 0.2, 2500.0)
+    }
+
+    /// Balanced grind under rhythm guitars
+    pub fn metal() -> Self {
+        Self::new(4.0, 0.4, 3500.0)
+    }
+
+    /// Heavily overdriven, bright bass that growls between the guitars (death/djent)
+    pub fn grind() -> Self {
+        Self::new(7.0, 0.6, 4500.0)
+    }
+
+    /// Amp each subgenre's bass runs through
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        match subgenre {
+            MetalSubgenre::DoomMetal => Self::subtle(),
+            MetalSubgenre::DeathMetal | MetalSubgenre::ThrashMetal | MetalSubgenre::ProgressiveMetal => Self::grind(),
+            MetalSubgenre::HeavyMetal => Self::metal(),
+        }
+    }
+
+    /// Clear filter and compressor state
+    pub fn reset(&mut self) {
+        self.low_pass.reset();
+        self.compressor.reset();
+    }
+
+    /// Process a single sample through the bass amp
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let driven = self.distortion.process(sample);
+        let blended = sample * (1.0 - self.blend) + driven * self.blend;
+        let filtered = self.low_pass.process(blended);
+        self.compressor.process(filtered)
+    }
+
+    /// Process a buffer of samples
+    pub fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +687,73 @@ mod tests {
         assert_eq!(dist.oversample_factor, 4);
     }
 
+    #[test]
+    fn test_set_drive_compensates_output() {
+        let mut dist = TubeDistortion::new(6.0, 1.0);
+        let quiet_level = dist.output_level;
+
+        dist.set_drive(12.0);
+        assert_eq!(dist.drive, 12.0);
+        assert!(dist.output_level < quiet_level);
+
+        // Hot input at high drive stays below full scale
+        for &input in &[1.0, -1.0, 0.8, -0.8] {
+            assert!(dist.process(input).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_noise_gate_does_not_flap_on_decaying_tail() {
+        let sample_rate = get_sample_rate() as f32;
+        let mut gate = NoiseGate::metal();
+
+        // Low, slowly decaying sine: the envelope ripples around the thresholds on its way down
+        let mut transitions = 0;
+        let mut was_open = false;
+        for i in 0..(sample_rate * 2.0) as usize {
+            let t = i as f32 / sample_rate;
+            let sample = 0.5 * (-t * 3.0).exp() * (2.0 * std::f32::consts::PI * 41.0 * t).sin();
+            gate.process(sample);
+            if gate.is_open() != was_open {
+                transitions += 1;
+                was_open = gate.is_open();
+            }
+        }
+
+        // Opens once for the note, closes once after the tail (no chatter)
+        assert!(transitions <= 2, "gate flapped {} times", transitions);
+    }
+
+    #[test]
+    fn test_sidechain_ducks_on_kick_and_recovers() {
+        let sample_rate = get_sample_rate() as usize;
+        let step = sample_rate / 4;
+        let mut bass = vec![1.0f32; step * 4];
+        let settings = SidechainSettings::new(0.6, 0.05);
+
+        // Kick on the first and third steps only
+        sidechain_compress(&mut bass, &[true, false, true, false], step, settings);
+
+        // Ducked right after the hit (past the attack ramp)
+        let just_after = bass[sample_rate / 200];
+        assert!(just_after < 0.5, "expected duck, got {}", just_after);
+
+        // Fully recovered by the end of the empty step
+        assert!(bass[step * 2 - 1] > 0.99);
+        assert!(bass[step * 3 - 1] > bass[step * 2 + sample_rate / 200]);
+
+        // Zero amount leaves the signal alone
+        let mut untouched = vec![1.0f32; step];
+        sidechain_compress(&mut untouched, &[true], step, SidechainSettings::new(0.0, 0.1));
+        assert!(untouched.iter().all(|&s| s == 1.0));
+
+        // Off-grid hits duck where they land
+        let mut swung = vec![1.0f32; step];
+        sidechain_compress_at(&mut swung, &[step / 3], settings);
+        assert_eq!(swung[step / 3 - 1], 1.0);
+        assert!(swung[step / 3 + sample_rate / 200] < 0.5);
+    }
+
     #[test]
     fn test_noise_gate() {
         let mut gate = NoiseGate::new(0.1);
@@ -418,6 +772,24 @@ mod tests {
         assert!(last_loud.abs() > 0.1, "Gate should open for loud signal after envelope builds up");
     }
 
+    #[test]
+    fn test_noise_gate_auto_calibrates_to_floor() {
+        let mut gate = NoiseGate::metal();
+        let hysteresis = gate.close_threshold / gate.threshold;
+
+        // Hiss at ~0.003 RMS: threshold lands above it, well under the metal preset
+        let hiss: Vec<f32> = (0..4410).map(|i| if i % 2 == 0 { 0.003 } else { -0.003 }).collect();
+        gate.auto_calibrate(&hiss);
+        assert!((gate.threshold - 0.012).abs() < 1e-4);
+        assert!((gate.close_threshold / gate.threshold - hysteresis).abs() < 1e-4);
+
+        // Digital silence can't disable the gate; empty input changes nothing
+        gate.auto_calibrate(&[0.0; 100]);
+        assert_eq!(gate.threshold, GATE_MIN_THRESHOLD);
+        gate.auto_calibrate(&[]);
+        assert_eq!(gate.threshold, GATE_MIN_THRESHOLD);
+    }
+
     #[test]
     fn test_asymmetric_clipping() {
         let dist = TubeDistortion {
@@ -446,6 +818,47 @@ mod tests {
         assert!(buffer.iter().all(|&s| s.abs() < 1.0));
     }
 
+    #[test]
+    fn test_reset_chain_matches_a_fresh_one() {
+        let riff: Vec<f32> = (0..2000).map(|i| (i as f32 * 0.05).sin() * 0.4).collect();
+        let mut fresh = MetalDSPChain::metal();
+        let expected: Vec<f32> = riff.iter().map(|&s| fresh.process(s)).collect();
+
+        // A loud chorus leaves the gate open and the filters charged...
+        let mut reused = MetalDSPChain::metal();
+        reused.process_buffer(&mut vec![0.9; 5000]);
+        assert!(reused.noise_gate.is_open());
+
+        // ...which reset clears completely
+        reused.reset();
+        let processed: Vec<f32> = riff.iter().map(|&s| reused.process(s)).collect();
+        assert_eq!(processed, expected);
+    }
+
+    #[test]
+    fn test_bass_amp_adds_grit_per_subgenre() {
+        let sample_rate = get_sample_rate() as f32;
+        let bass: Vec<f32> = (0..sample_rate as usize / 2)
+            .map(|i| (i as f32 * 55.0 * std::f32::consts::TAU / sample_rate).sin() * 0.5)
+            .collect();
+        // Harmonics show up as energy in the first difference
+        let edge = |samples: &[f32]| {
+            let total: f32 = samples.iter().map(|s| s * s).sum();
+            samples.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum::<f32>() / total
+        };
+        let amp = |mut preset: BassAmp| {
+            let mut out = bass.clone();
+            preset.process_buffer(&mut out);
+            out
+        };
+
+        let doom = amp(BassAmp::for_subgenre(MetalSubgenre::DoomMetal));
+        let death = amp(BassAmp::for_subgenre(MetalSubgenre::DeathMetal));
+        assert!(edge(&death) > edge(&doom));
+        assert!(edge(&doom) > edge(&bass));
+        assert!(death.iter().chain(&doom).all(|s| s.is_finite() && s.abs() < 1.0));
+    }
+
     #[test]
     fn test_dsp_chain_presets() {
         let metal = MetalDSPChain::metal();
@@ -1,3 +1,6 @@
+use crate::composition::metal_song_generator::{MetalSubgenre, SectionIntensity};
+use crate::config::{MasterEqConfig, MixConfig, MAX_SATURATION_DRIVE};
+use crate::synthesis::lfo::{Lfo, LfoShape};
 use crate::utils::get_sample_rate;
 
 /// Professional mixing effects for metal music
@@ -67,7 +70,45 @@ impl Reverb {
     pub fn hall() -> Self {
         Reverb::new(1.2, 0.6, 0.25) // Large room, longer decay
     }
+
+    /// Fully wet reverb for a send bus (the dry signal stays on the track)
+    pub fn send_bus(room_size: f32, decay: f32) -> Self {
+        Reverb::new(room_size, decay, 1.0)
+    }
+
+    /// Room preset for a subgenre: a tight room for thrash up to a long hall for doom
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        match subgenre {
+            MetalSubgenre::ThrashMetal => Reverb::new(0.4, 0.2, 0.10), // Tight room, barely there
+            MetalSubgenre::HeavyMetal => Reverb::metal(),
+            MetalSubgenre::DeathMetal => Reverb::new(0.9, 0.45, 0.15), // Dark, cavernous space
+            MetalSubgenre::ProgressiveMetal => Reverb::new(1.0, 0.5, 0.2), // Open studio hall
+            MetalSubgenre::DoomMetal => Reverb::new(1.5, 0.75, 0.3), // Long hall
+        }
+    }
+
+    /// Wet/dry balance (0.0 = dry, 1.0 = wet only)
+    pub fn mix(&self) -> f32 {
+        self.wet_dry_mix
+    }
+
+    /// Same space as a fully wet send bus, with an empty tail
+    pub fn to_send_bus(&self) -> Self {
+        let mut bus = self.clone();
+        bus.wet_dry_mix = 1.0;
+        bus.reset();
+        bus
+    }
     
+    /// Silence the tail (empty every delay line)
+    pub fn reset(&mut self) {
+        for buffer in self.comb_buffers.iter_mut().chain(self.allpass_buffers.iter_mut()) {
+            buffer.fill(0.0);
+        }
+        self.comb_indices.fill(0);
+        self.allpass_indices.fill(0);
+    }
+
     /// Process a single sample
     pub fn process(&mut self, input: f32) -> f32 {
         let mut output = 0.0;
@@ -112,6 +153,69 @@ impl Reverb {
     }
 }
 
+/// How loud each track sits in a section of a given intensity.
+/// `MixConfig` (the stock mix, or `[metal.mix]`) is the default profile; implement this
+/// for bass-forward, drum-forward or other custom balances.
+pub trait MixProfile: Send {
+    /// (guitar, bass, drums) gains for a section of this intensity
+    fn levels_for(&self, intensity: SectionIntensity) -> (f32, f32, f32);
+}
+
+impl MixProfile for MixConfig {
+    fn levels_for(&self, intensity: SectionIntensity) -> (f32, f32, f32) {
+        let levels = self.levels(intensity);
+        (levels.guitar, levels.bass, levels.drums)
+    }
+}
+
+/// Per-track reverb send levels (0.0 = dry, 1.0 = full send)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbSend {
+    pub drums: f32,   // Short room
+    pub guitars: f32, // Rhythm guitars stay mostly dry
+    pub bass: f32,    // Zero by default to keep the low end clean
+    pub lead: f32,    // Solos get the longest tail
+}
+
+impl ReverbSend {
+    /// Create send levels, clamped to 0.0-1.0
+    pub fn new(drums: f32, guitars: f32, bass: f32, lead: f32) -> Self {
+        ReverbSend {
+            drums: drums.clamp(0.0, 1.0),
+            guitars: guitars.clamp(0.0, 1.0),
+            bass: bass.clamp(0.0, 1.0),
+            lead: lead.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Fully dry mix
+    pub fn dry() -> Self {
+        ReverbSend::new(0.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Every send multiplied by `factor` (still clamped to 0.0-1.0)
+    pub fn scaled(&self, factor: f32) -> Self {
+        let factor = factor.max(0.0);
+        ReverbSend::new(self.drums * factor, self.guitars * factor, self.bass * factor, self.lead * factor)
+    }
+
+    /// Signal sent to the room bus from the rhythm section
+    pub fn room_input(&self, drums: f32, guitars: f32, bass: f32) -> f32 {
+        drums * self.drums + guitars * self.guitars + bass * self.bass
+    }
+
+    /// Signal sent to the lead (hall) bus
+    pub fn lead_input(&self, lead: f32) -> f32 {
+        lead * self.lead
+    }
+}
+
+impl Default for ReverbSend {
+    fn default() -> Self {
+        ReverbSend::new(0.15, 0.05, 0.0, 0.30)
+    }
+}
+
 /// 3-band parametric EQ for metal mixing
 #[derive(Debug, Clone)]
 pub struct ParametricEQ {
@@ -181,6 +285,163 @@ impl ParametricEQ {
     }
 }
 
+/// RBJ-cookbook biquad section (direct form I)
+#[derive(Debug, Clone)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+/// Shape of a master EQ band
+#[derive(Debug, Clone, Copy)]
+enum BandShape {
+    LowShelf,
+    Peak,
+    HighShelf,
+}
+
+impl Biquad {
+    fn new(shape: BandShape, sample_rate: f32, freq: f32, gain_db: f32, q: f32) -> Self {
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        // Keep the band below Nyquist at low sample rates
+        let w0 = 2.0 * std::f32::consts::PI * freq.min(sample_rate * 0.45) / sample_rate;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * q);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match shape {
+            BandShape::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos_w0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w0,
+                1.0 - alpha / a,
+            ),
+            BandShape::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 - shelf),
+                (a + 1.0) + (a - 1.0) * cos_w0 + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                (a + 1.0) + (a - 1.0) * cos_w0 - shelf,
+            ),
+            BandShape::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - shelf),
+                (a + 1.0) - (a - 1.0) * cos_w0 + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - shelf,
+            ),
+        };
+
+        Biquad {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = input;
+        self.y2 = self.y1;
+        self.y1 = output;
+        output
+    }
+}
+
+/// Final-stage 3-band mastering EQ: low shelf, mid peak and high shelf in series
+#[derive(Debug, Clone)]
+pub struct MasterEQ {
+    bands: [Biquad; 3],
+}
+
+impl MasterEQ {
+    /// Shelf slope of the low and high bands (Butterworth, no overshoot)
+    const SHELF_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+    pub fn new(eq: &MasterEqConfig) -> Self {
+        let sample_rate = get_sample_rate() as f32;
+        MasterEQ {
+            bands: [
+                Biquad::new(BandShape::LowShelf, sample_rate, eq.low_hz, eq.low_db, Self::SHELF_Q),
+                Biquad::new(BandShape::Peak, sample_rate, eq.mid_hz, eq.mid_db, eq.mid_q),
+                Biquad::new(BandShape::HighShelf, sample_rate, eq.high_hz, eq.high_db, Self::SHELF_Q),
+            ],
+        }
+    }
+
+    /// Per-subgenre mastering curve (see `MasterEqConfig::for_subgenre`)
+    pub fn for_subgenre(subgenre: MetalSubgenre) -> Self {
+        Self::new(&MasterEqConfig::for_subgenre(subgenre))
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.bands.iter_mut().fold(input, |sample, band| band.process(sample))
+    }
+
+    pub fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
+/// Master saturation drive used unless configured (gentle: ~+1.5 dB on quiet passages)
+pub const DEFAULT_SATURATION_DRIVE: f32 = 1.25;
+
+/// Level the saturated master can never exceed
+const SATURATION_CEILING: f32 = 0.95;
+
+/// Tanh soft-clip for the master bus: glues the mix by rounding off peaks while the
+/// drive acts as makeup gain on everything below them. Output stays under the ceiling
+#[derive(Debug, Clone, Copy)]
+pub struct MasterSaturation {
+    drive: f32,
+}
+
+impl MasterSaturation {
+    /// `drive` is clamped to 1.0-`MAX_SATURATION_DRIVE`
+    pub fn new(drive: f32) -> Self {
+        MasterSaturation { drive: drive.clamp(1.0, MAX_SATURATION_DRIVE) }
+    }
+
+    /// Barely-there glue (`DEFAULT_SATURATION_DRIVE`)
+    pub fn subtle() -> Self {
+        Self::new(DEFAULT_SATURATION_DRIVE)
+    }
+
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
+    pub fn process(&self, input: f32) -> f32 {
+        (input * self.drive).tanh() * SATURATION_CEILING
+    }
+
+    pub fn process_buffer(&self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
 /// Dynamic range compressor for metal mixing
 #[derive(Debug, Clone)]
 pub struct Compressor {
@@ -229,6 +490,11 @@ impl Compressor {
         )
     }
     
+    /// Release any gain reduction still being applied
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+
     /// Process a single sample
     pub fn process(&mut self, input: f32) -> f32 {
         // Convert to dB
@@ -267,10 +533,196 @@ impl Compressor {
     }
 }
 
+/// LFO auto-panner for a stereo pair (equal-power, sine sweep)
+#[derive(Debug, Clone)]
+pub struct AutoPanner {
+    sample_rate: u32,
+    pub lfo: Lfo,   // Sine sweep: full left-right-left cycles per second
+    pub depth: f32, // 0.0 = centered, 1.0 = hard left to hard right
+}
+
+impl AutoPanner {
+    /// Create an auto-panner (depth clamped to 0.0-1.0)
+    pub fn new(rate_hz: f32, depth: f32) -> Self {
+        AutoPanner {
+            sample_rate: get_sample_rate(),
+            lfo: Lfo::new(LfoShape::Sine, rate_hz),
+            depth: depth.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Rate locked to the song: one full sweep every `beats_per_cycle` beats
+    pub fn tempo_synced(bpm: f32, beats_per_cycle: f32, depth: f32) -> Self {
+        let rate_hz = if beats_per_cycle > 0.0 { bpm / 60.0 / beats_per_cycle } else { 0.0 };
+        AutoPanner::new(rate_hz, depth)
+    }
+
+    /// Current pan position (-1.0 = left, 1.0 = right)
+    pub fn position(&self) -> f32 {
+        self.depth * self.lfo.value()
+    }
+
+    /// (left, right) gains for the current position, normalized to unity at center
+    fn gains(&self) -> (f32, f32) {
+        let angle = (self.position() + 1.0) * std::f32::consts::FRAC_PI_4;
+        (angle.cos() * std::f32::consts::SQRT_2, angle.sin() * std::f32::consts::SQRT_2)
+    }
+
+    /// Pan one stereo frame
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let (left_gain, right_gain) = self.gains();
+        self.lfo.next(self.sample_rate);
+        (left * left_gain, right * right_gain)
+    }
+
+    /// Place a mono source in the moving stereo field
+    pub fn process_mono(&mut self, input: f32) -> (f32, f32) {
+        self.process(input, input)
+    }
+
+    /// Process a stereo pair of buffers in place
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.process(*l, *r);
+        }
+    }
+}
+
+/// Below this left/right correlation a stereo mix loses level (or whole parts) in mono
+pub const MONO_SAFE_CORRELATION: f32 = 0.2;
+
+/// Correlation between the channels: 1.0 = mono, 0.0 = unrelated, -1.0 = cancels in mono.
+/// Silence counts as fully compatible.
+pub fn mono_compatibility(left: &[f32], right: &[f32]) -> f32 {
+    let (mut cross, mut left_energy, mut right_energy) = (0.0f64, 0.0f64, 0.0f64);
+    for (&l, &r) in left.iter().zip(right) {
+        cross += l as f64 * r as f64;
+        left_energy += l as f64 * l as f64;
+        right_energy += r as f64 * r as f64;
+    }
+    let energy = (left_energy * right_energy).sqrt();
+    if energy < 1e-12 {
+        return 1.0;
+    }
+    (cross / energy) as f32
+}
+
+/// Bleeds a little of each channel into the other so hard-panned parts survive mono playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossfeed {
+    pub amount: f32, // Share of the opposite channel (0.0 = off, 0.5 = mono)
+}
+
+impl Crossfeed {
+    /// Create a crossfeed (amount clamped to 0.0-0.5)
+    pub fn new(amount: f32) -> Self {
+        Crossfeed { amount: amount.clamp(0.0, 0.5) }
+    }
+
+    /// Light blend that keeps most of the width
+    pub fn light() -> Self {
+        Crossfeed::new(0.15)
+    }
+
+    /// Blend one stereo frame
+    pub fn process(&self, left: f32, right: f32) -> (f32, f32) {
+        let keep = 1.0 - self.amount;
+        (left * keep + right * self.amount, right * keep + left * self.amount)
+    }
+
+    /// Process a stereo pair of buffers in place
+    pub fn process_stereo(&self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.process(*l, *r);
+        }
+    }
+}
+
+/// Result of `check_mono_compatibility`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonoCheck {
+    pub correlation: f32,      // Left/right correlation as the mix came in
+    pub crossfed: Option<f32>, // Correlation after the crossfeed, if one was applied
+}
+
+impl MonoCheck {
+    /// Correlation of the channels as they now stand
+    pub fn final_correlation(&self) -> f32 {
+        self.crossfed.unwrap_or(self.correlation)
+    }
+}
+
+/// Measure a stereo mix's correlation and, if it falls below `threshold` and a crossfeed
+/// is given, apply it to pull the channels back together. The caller decides what to report.
+pub fn check_mono_compatibility(left: &mut [f32], right: &mut [f32], threshold: f32, crossfeed: Option<Crossfeed>) -> MonoCheck {
+    let correlation = mono_compatibility(left, right);
+    let crossfed = match crossfeed {
+        Some(crossfeed) if correlation < threshold => {
+            crossfeed.process_stereo(left, right);
+            Some(mono_compatibility(left, right))
+        }
+        _ => None,
+    };
+    MonoCheck { correlation, crossfed }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_master_eq_shapes_each_band() {
+        let sample_rate = get_sample_rate() as f32;
+        // Steady-state level of a sine after the EQ, relative to the dry sine
+        let gain_at = |eq: &MasterEqConfig, freq: f32| {
+            let mut master = MasterEQ::new(eq);
+            let mut tone: Vec<f32> = (0..sample_rate as usize / 2)
+                .map(|i| (i as f32 * freq * 2.0 * std::f32::consts::PI / sample_rate).sin())
+                .collect();
+            master.process_buffer(&mut tone);
+            tone[tone.len() / 2..].iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+
+        // Flat bands pass the signal through untouched
+        let flat = MasterEqConfig::default();
+        for freq in [60.0, 800.0, 8000.0] {
+            assert!((gain_at(&flat, freq) - 1.0).abs() < 0.01, "{} Hz", freq);
+        }
+
+        // Each band boosts/cuts around its own frequency and leaves the others alone
+        let db = |gain: f32| 20.0 * gain.log10();
+        let scoop = MasterEqConfig { low_db: 6.0, mid_hz: 800.0, mid_db: -6.0, high_db: 6.0, ..flat };
+        assert!((db(gain_at(&scoop, 30.0)) - 6.0).abs() < 0.5);
+        assert!((db(gain_at(&scoop, 800.0)) + 6.0).abs() < 0.8);
+        assert!((db(gain_at(&scoop, 15000.0)) - 6.0).abs() < 0.8);
+
+        // Thrash scoops the mids; prog lifts the presence range
+        let thrash = MasterEqConfig::for_subgenre(MetalSubgenre::ThrashMetal);
+        assert!(gain_at(&thrash, 750.0) < 0.7);
+        let prog = MasterEqConfig::for_subgenre(MetalSubgenre::ProgressiveMetal);
+        assert!(gain_at(&prog, 6000.0) > 1.3);
+    }
+
+    #[test]
+    fn test_master_saturation_glues_without_clipping() {
+        let subtle = MasterSaturation::subtle();
+        // Quiet passages come up by roughly the drive, peaks are rounded off
+        let quiet = subtle.process(0.05) / 0.05;
+        assert!((quiet - DEFAULT_SATURATION_DRIVE * SATURATION_CEILING).abs() < 0.01);
+        assert!(quiet > 1.0);
+        assert!(subtle.process(0.95) < 0.95);
+        assert_eq!(subtle.process(-0.5), -subtle.process(0.5));
+
+        // Nothing gets past the ceiling, however hard it's driven
+        for drive in [1.0, 2.0, 10.0] {
+            let saturation = MasterSaturation::new(drive);
+            assert!(saturation.drive() <= MAX_SATURATION_DRIVE);
+            for input in [0.5, 1.0, 3.0, -8.0] {
+                assert!(saturation.process(input).abs() <= SATURATION_CEILING);
+            }
+        }
+    }
+
     #[test]
     fn test_reverb_creation() {
         let reverb = Reverb::metal();
@@ -292,6 +744,109 @@ mod tests {
         assert!(tail_energy > 0.0, "Reverb should create a tail");
     }
 
+    #[test]
+    fn test_mono_compatibility() {
+        let tone: Vec<f32> = (0..4410).map(|i| (i as f32 * 0.05).sin()).collect();
+        let inverted: Vec<f32> = tone.iter().map(|s| -s).collect();
+        assert!((mono_compatibility(&tone, &tone) - 1.0).abs() < 1e-4);
+        assert!((mono_compatibility(&tone, &inverted) + 1.0).abs() < 1e-4);
+        assert_eq!(mono_compatibility(&[0.0; 64], &[0.0; 64]), 1.0);
+
+        // Unrelated channels get pulled back toward mono only when below the threshold
+        let quadrature: Vec<f32> = (0..4410).map(|i| (i as f32 * 0.05).cos()).collect();
+        let (mut left, mut right) = (tone.clone(), quadrature.clone());
+        assert!(mono_compatibility(&left, &right) < MONO_SAFE_CORRELATION);
+        let check = check_mono_compatibility(&mut left, &mut right, MONO_SAFE_CORRELATION, Some(Crossfeed::new(0.3)));
+        assert!(check.correlation < MONO_SAFE_CORRELATION);
+        assert!(check.final_correlation() > 0.5, "correlation {:?}", check);
+
+        let (mut left, mut right) = (tone.clone(), tone.clone());
+        let check = check_mono_compatibility(&mut left, &mut right, MONO_SAFE_CORRELATION, Some(Crossfeed::light()));
+        assert_eq!(check.crossfed, None);
+        assert_eq!(left, tone);
+    }
+
+    #[test]
+    fn test_subgenre_reverb_sizes() {
+        // Energy left in the tail a quarter second after an impulse
+        let late_tail = |subgenre| {
+            let mut reverb = Reverb::for_subgenre(subgenre).to_send_bus();
+            let mut output = vec![0.0; 30000];
+            output[0] = 1.0;
+            reverb.process_buffer(&mut output);
+            output[11025..].iter().map(|s| s.abs()).sum::<f32>()
+        };
+        assert!(late_tail(MetalSubgenre::DoomMetal) > late_tail(MetalSubgenre::ThrashMetal) * 2.0);
+
+        let doom = Reverb::for_subgenre(MetalSubgenre::DoomMetal);
+        let thrash = Reverb::for_subgenre(MetalSubgenre::ThrashMetal);
+        assert!(doom.mix() > thrash.mix());
+        assert_eq!(doom.to_send_bus().mix(), 1.0);
+    }
+
+    #[test]
+    fn test_reverb_reset_silences_tail() {
+        let mut reverb = Reverb::hall();
+        reverb.process_buffer(&mut vec![0.8; 5000]);
+        reverb.reset();
+
+        let mut silence = vec![0.0; 5000];
+        reverb.process_buffer(&mut silence);
+        assert!(silence.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_custom_mix_profile() {
+        struct BassForward;
+        impl MixProfile for BassForward {
+            fn levels_for(&self, intensity: SectionIntensity) -> (f32, f32, f32) {
+                let (guitar, bass, drums) = MixConfig::default().levels_for(intensity);
+                (guitar * 0.8, bass * 1.5, drums)
+            }
+        }
+
+        // The stock profile keeps the stock levels
+        let stock: Box<dyn MixProfile> = Box::new(MixConfig::default());
+        assert_eq!(stock.levels_for(SectionIntensity::Medium), (0.40, 0.45, 0.60));
+
+        let custom: Box<dyn MixProfile> = Box::new(BassForward);
+        let (guitar, bass, _) = custom.levels_for(SectionIntensity::Extreme);
+        assert!(bass > guitar);
+    }
+
+    #[test]
+    fn test_reverb_send_keeps_bass_dry() {
+        let send = ReverbSend::default();
+        assert_eq!(send.bass, 0.0);
+        assert_eq!(send.room_input(0.0, 0.0, 1.0), 0.0);
+
+        // Leads get more tail than rhythm guitars
+        assert!(send.lead > send.guitars);
+        assert!(send.room_input(1.0, 0.0, 0.0) > 0.0);
+    }
+
+    #[test]
+    fn test_auto_panner_sweeps_between_channels() {
+        let sample_rate = get_sample_rate() as usize;
+        // 120 BPM, one sweep per bar = 0.5 Hz
+        let mut panner = AutoPanner::tempo_synced(120.0, 4.0, 1.0);
+        assert!((panner.lfo.rate - 0.5).abs() < 1e-6);
+
+        let frames: Vec<(f32, f32)> = (0..sample_rate * 2).map(|_| panner.process_mono(0.5)).collect();
+
+        // Starts centered at unity, hits hard right a quarter cycle in, hard left at three quarters
+        assert!((frames[0].0 - 0.5).abs() < 1e-4 && (frames[0].1 - 0.5).abs() < 1e-4);
+        let right = frames[sample_rate / 2];
+        let left = frames[sample_rate * 3 / 2];
+        assert!(right.0.abs() < 1e-3 && right.1 > 0.7);
+        assert!(left.1.abs() < 1e-3 && left.0 > 0.7);
+
+        // Zero depth leaves the pair untouched
+        let mut still = AutoPanner::new(3.0, 0.0);
+        let (l, r) = still.process(0.25, -0.5);
+        assert!((l - 0.25).abs() < 1e-6 && (r + 0.5).abs() < 1e-6);
+    }
+
     #[test]
     fn test_eq_creation() {
         let eq = ParametricEQ::metal();
@@ -4,9 +4,12 @@ pub mod fx;
 pub mod filters;         // Basic filters (LowPass, etc.)
 pub mod metal_dsp;       // Advanced distortion and noise gate for metal
 pub mod karplus_strong;  // String synthesis for guitar/bass
+pub mod reese_bass;      // Detuned-saw wobble bass for prog breakdowns
 pub mod cabinet;         // Cabinet simulation
 pub mod metal_audio_renderer; // Complete metal audio rendering
 pub mod mixing;          // Reverb, EQ, and compression
+pub mod modulation;      // Chorus and flanger
+pub mod lfo;             // Tempo-syncable LFO for modulation effects
 pub mod aggressive_mix;
 
 // Core exports
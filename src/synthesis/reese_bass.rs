@@ -0,0 +1,92 @@
+//! Detuned dual-oscillator "Reese" bass for djent-style breakdowns.
+//! Two slightly detuned saws beat against each other; a low-pass swept by an
+//! LFO gives the wobble.
+
+use crate::synthesis::lfo::{Lfo, LfoShape};
+use crate::utils::get_sample_rate;
+use std::f32::consts::PI;
+
+/// Total detune between the two saws, in cents (split evenly around the note)
+const REESE_DETUNE_CENTS: f32 = 18.0;
+
+/// Wobble filter sweep range (Hz)
+const WOBBLE_MIN_CUTOFF: f32 = 120.0;
+const WOBBLE_MAX_CUTOFF: f32 = 1400.0;
+
+/// Wobble rate used when the caller doesn't tempo-sync it (Hz)
+pub const DEFAULT_WOBBLE_HZ: f32 = 2.0;
+
+/// Reese bass note with the default wobble rate
+pub fn generate_reese_bass(frequency: f32, duration: f32) -> Vec<f32> {
+    generate_reese_bass_with_wobble(frequency, duration, DEFAULT_WOBBLE_HZ)
+}
+
+/// Reese bass note whose filter opens and closes `wobble_hz` times per second
+/// (0.0 = static filter, half open)
+pub fn generate_reese_bass_with_wobble(frequency: f32, duration: f32, wobble_hz: f32) -> Vec<f32> {
+    let sample_rate = get_sample_rate() as f32;
+    let num_samples = (duration.max(0.0) * sample_rate) as usize;
+    let mut samples = Vec::with_capacity(num_samples);
+
+    let spread = 2.0_f32.powf(REESE_DETUNE_CENTS / 2.0 / 1200.0);
+    let (low_freq, high_freq) = (frequency / spread, frequency * spread);
+    // Start the voices out of phase so the note doesn't open with a spike
+    let (mut low_phase, mut high_phase) = (0.0_f32, 0.25_f32);
+    let mut sub_phase = 0.0_f32;
+
+    // Two one-pole stages = 12 dB/oct, enough to hear the sweep
+    let (mut stage1, mut stage2) = (0.0_f32, 0.0_f32);
+
+    let attack_samples = (0.005 * sample_rate).max(1.0);
+    let release_samples = (0.02 * sample_rate).max(1.0);
+
+    // Sine started at its trough (a raised cosine): every note starts with the filter closed
+    let mut wobble = Lfo::new(LfoShape::Sine, wobble_hz).with_phase(0.75);
+
+    for i in 0..num_samples {
+        let saws = (2.0 * low_phase - 1.0) + (2.0 * high_phase - 1.0);
+        let sub = (2.0 * PI * sub_phase).sin();
+        let raw = saws * 0.4 + sub * 0.3;
+
+        let lfo = if wobble_hz > 0.0 { wobble.next_unipolar(sample_rate as u32) } else { 0.5 };
+        let cutoff = WOBBLE_MIN_CUTOFF * (WOBBLE_MAX_CUTOFF / WOBBLE_MIN_CUTOFF).powf(lfo);
+        let alpha = 1.0 - (-2.0 * PI * cutoff / sample_rate).exp();
+        stage1 += alpha * (raw - stage1);
+        stage2 += alpha * (stage1 - stage2);
+
+        let remaining = (num_samples - i) as f32;
+        let envelope = (i as f32 / attack_samples).min(1.0) * (remaining / release_samples).min(1.0);
+        samples.push((stage2 * 1.8).tanh() * envelope);
+
+        low_phase = (low_phase + low_freq / sample_rate).fract();
+        high_phase = (high_phase + high_freq / sample_rate).fract();
+        sub_phase = (sub_phase + frequency / sample_rate).fract();
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First-difference RMS: rises with high-frequency content
+    fn brightness(window: &[f32]) -> f32 {
+        let sum: f32 = window.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+        (sum / window.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_reese_filter_wobbles() {
+        let sample_rate = get_sample_rate() as usize;
+        let note = generate_reese_bass_with_wobble(55.0, 1.0, 2.0);
+        assert_eq!(note.len(), sample_rate);
+        assert!(note.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+
+        // 2 Hz wobble: filter wide open at 0.25s, closed again at 0.5s
+        let window = sample_rate / 20;
+        let open = brightness(&note[sample_rate / 4 - window / 2..sample_rate / 4 + window / 2]);
+        let closed = brightness(&note[sample_rate / 2 - window / 2..sample_rate / 2 + window / 2]);
+        assert!(open > closed * 2.0, "open {} vs closed {}", open, closed);
+    }
+}
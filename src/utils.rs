@@ -1,7 +1,17 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::{Cell, RefCell};
+use std::fs;
 use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 static SAMPLE_RATE_STORAGE: OnceLock<u32> = OnceLock::new();
 
+thread_local! {
+    static SONG_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+    static SAMPLE_RATE_OVERRIDE: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
 /// Initialize the sample rate from config (must be called before any synthesis)
 pub fn init_sample_rate(sample_rate: u32) {
     SAMPLE_RATE_STORAGE
@@ -9,14 +19,80 @@ pub fn init_sample_rate(sample_rate: u32) {
         .expect("Sample rate already initialized");
 }
 
-/// Get the current sample rate
+/// Get the current sample rate: the enclosing `RenderContext::scope` on this thread if any,
+/// otherwise the global one
 pub fn get_sample_rate() -> u32 {
-    *SAMPLE_RATE_STORAGE.get().unwrap_or(&44100) // Fallback to 44100 if not initialized
+    SAMPLE_RATE_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| *SAMPLE_RATE_STORAGE.get().unwrap_or(&44100)) // Fallback to 44100 if not initialized
 }
 
+/// Explicit render settings, so a library caller can render at its own sample rate
+/// (e.g. 48 kHz for video) without touching the global, and two renders at different
+/// rates can run on different threads at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderContext {
+    pub sample_rate: u32,
+}
 
-use std::fs;
-use std::time::{SystemTime, UNIX_EPOCH};
+impl RenderContext {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { sample_rate: sample_rate.max(1) }
+    }
+
+    /// Run `f` with `get_sample_rate()` returning this context's rate on the current thread
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        /// Puts the previous override back even if `f` panics
+        struct Restore(Option<u32>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SAMPLE_RATE_OVERRIDE.with(|rate| rate.set(self.0));
+            }
+        }
+
+        let _restore = Restore(SAMPLE_RATE_OVERRIDE.with(|rate| rate.replace(Some(self.sample_rate))));
+        f()
+    }
+}
+
+impl Default for RenderContext {
+    /// The global sample rate
+    fn default() -> Self {
+        Self::new(get_sample_rate())
+    }
+}
+
+/// Reseed the generation RNG for this thread (same seed = same song)
+pub fn seed_rng(seed: u64) {
+    SONG_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Handle to the seedable per-thread RNG used for all composition and synthesis randomness
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SongRng;
+
+impl RngCore for SongRng {
+    fn next_u32(&mut self) -> u32 {
+        SONG_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SONG_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        SONG_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        SONG_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+/// Get the song RNG: this thread's generator, reseeded by `seed_rng`
+pub fn rng() -> SongRng {
+    SongRng
+}
 
 /// Check if a year is a leap year
 pub fn is_leap_year(year: u32) -> bool {
@@ -67,6 +143,74 @@ pub fn sanitize_filename(s: &str) -> String {
         .collect()
 }
 
+/// Placeholders a filename template can use
+pub const FILENAME_FIELDS: [&str; 7] = ["date", "artist", "title", "subgenre", "key", "bpm", "seed"];
+
+/// Default filename template (`2025-11-26_petar_zarkov_frost_marsh`)
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{date}_{artist}_{title}";
+
+/// A filename template split into literal text and `{field}` placeholders
+enum TemplatePart<'a> {
+    Literal(&'a str),
+    Field(&'a str),
+}
+
+fn parse_filename_template(template: &str) -> Result<Vec<TemplatePart<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..].find('}').ok_or_else(|| format!("unclosed '{{' in \"{}\"", template))? + open;
+        parts.push(TemplatePart::Literal(&rest[..open]));
+        let field = &rest[open + 1..close];
+        if !FILENAME_FIELDS.contains(&field) {
+            return Err(format!("unknown field {{{}}} (expected one of {})", field, FILENAME_FIELDS.join(", ")));
+        }
+        parts.push(TemplatePart::Field(field));
+        rest = &rest[close + 1..];
+    }
+    parts.push(TemplatePart::Literal(rest));
+    Ok(parts)
+}
+
+/// Check a filename template: known `{field}`s only, at least one of them (so batch
+/// songs don't overwrite each other), and literal text that is safe in a path.
+/// `/` is allowed to sort songs into subfolders, but not `..` or absolute paths
+pub fn validate_filename_template(template: &str) -> Result<(), String> {
+    let parts = parse_filename_template(template)?;
+    if !parts.iter().any(|part| matches!(part, TemplatePart::Field(_))) {
+        return Err(format!("\"{}\" has no {{field}}, every song would get the same name", template));
+    }
+    for part in &parts {
+        if let TemplatePart::Literal(text) = part {
+            if let Some(c) = text.chars().find(|&c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))) {
+                return Err(format!("character '{}' is not allowed in \"{}\"", c, template));
+            }
+        }
+    }
+    if template.split('/').any(|folder| folder.is_empty() || folder == "." || folder == "..") {
+        return Err(format!("\"{}\" must be a relative path without empty, '.' or '..' folders", template));
+    }
+    Ok(())
+}
+
+/// Expand a filename template (see `validate_filename_template`); every field value
+/// is run through `sanitize_filename`, fields missing from `fields` expand to nothing
+pub fn format_filename(template: &str, fields: &[(&str, String)]) -> Result<String, String> {
+    validate_filename_template(template)?;
+    let mut filename = String::new();
+    for part in parse_filename_template(template)? {
+        match part {
+            TemplatePart::Literal(text) => filename.push_str(text),
+            TemplatePart::Field(name) => {
+                if let Some((_, value)) = fields.iter().find(|(field, _)| *field == name) {
+                    filename.push_str(&sanitize_filename(value));
+                }
+            }
+        }
+    }
+    Ok(filename)
+}
+
 /// Get current date in YYYY-MM-DD format
 /// Uses environment variable SONG_DATE if set (for testing), otherwise uses current date
 pub fn get_current_date() -> String {
@@ -119,3 +263,41 @@ pub fn get_current_date() -> String {
 pub fn create_output_directory(path: &str) -> Result<(), String> {
     fs::create_dir_all(path).map_err(|e| format!("Could not create output directory: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_seed_rng_is_deterministic() {
+        seed_rng(42);
+        let first: Vec<u32> = (0..8).map(|_| rng().gen_range(0..1000)).collect();
+        seed_rng(42);
+        let second: Vec<u32> = (0..8).map(|_| rng().gen_range(0..1000)).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_filename_template_expands_and_validates() {
+        let fields = [
+            ("date", "2025-11-26".to_string()),
+            ("artist", "Petar Zarkov".to_string()),
+            ("title", "Frost: Marsh!".to_string()),
+            ("subgenre", "death-metal".to_string()),
+            ("bpm", "200".to_string()),
+        ];
+        assert_eq!(
+            format_filename(DEFAULT_FILENAME_TEMPLATE, &fields).unwrap(),
+            "2025-11-26_petar_zarkov_frost_marsh"
+        );
+        assert_eq!(format_filename("{subgenre}/{bpm}bpm_{title}", &fields).unwrap(), "death-metal/200bpm_frost_marsh");
+
+        assert!(validate_filename_template("song").unwrap_err().contains("no {field}"));
+        assert!(validate_filename_template("{genre}").unwrap_err().contains("unknown field"));
+        assert!(validate_filename_template("{title").is_err());
+        assert!(validate_filename_template("../{title}").is_err());
+        assert!(validate_filename_template("/tmp/{title}").is_err());
+        assert!(validate_filename_template("{title} {date}").is_err());
+    }
+}